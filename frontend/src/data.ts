@@ -11,6 +11,8 @@ import {
     Boolean,
     Unknown,
     Dictionary,
+    Number,
+    Runtype,
 } from "runtypes";
 
 export const Place = Union(
@@ -19,6 +21,12 @@ export const Place = Union(
     Literal("Dental"),
     Literal("Alveolar"),
     Literal("Postalveolar"),
+    Literal("Retroflex"),
+    Literal("Palatal"),
+    Literal("Velar"),
+    Literal("Uvular"),
+    Literal("Pharyngeal"),
+    Literal("Glottal"),
 );
 export const ObstruentAttachment = Union(
     Literal("Ejective"),
@@ -26,6 +34,35 @@ export const ObstruentAttachment = Union(
     Literal("Aspirated"),
     Literal("Breathy"),
     Literal("Creaky"),
+    Literal("NasalRelease"),
+    Literal("LateralRelease"),
+    Literal("Unreleased"),
+);
+export const SecondaryArticulation = Union(
+    Literal("Labialized"),
+    Literal("Palatalized"),
+    Literal("Velarized"),
+    Literal("Pharyngealized"),
+);
+export const Height = Union(
+    Literal("Close"),
+    Literal("NearClose"),
+    Literal("CloseMid"),
+    Literal("Mid"),
+    Literal("OpenMid"),
+    Literal("NearOpen"),
+    Literal("Open"),
+);
+export const Backness = Union(
+    Literal("Front"),
+    Literal("Central"),
+    Literal("Back"),
+);
+export const Length = Union(
+    Literal("ExtraShort"),
+    Literal("Short"),
+    Literal("HalfLong"),
+    Literal("Long"),
 );
 export const Phone = Union(
     Record({
@@ -33,6 +70,8 @@ export const Phone = Union(
             place: Place,
             voiced: Boolean,
             attachments: Array(ObstruentAttachment),
+            secondary: Array(SecondaryArticulation),
+            length: Length,
         }),
     }),
     Record({
@@ -41,35 +80,594 @@ export const Phone = Union(
             end_place: Place,
             voiced: Boolean,
             attachments: Array(ObstruentAttachment),
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+        }),
+    }),
+    Record({
+        Fricative: Record({
+            place: Place,
+            voiced: Boolean,
+            attachments: Array(ObstruentAttachment),
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+        }),
+    }),
+    Record({
+        LateralFricative: Record({
+            place: Place,
+            voiced: Boolean,
+            attachments: Array(ObstruentAttachment),
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+        }),
+    }),
+    Record({
+        Nasal: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+            syllabic: Boolean,
+        }),
+    }),
+    Record({
+        Trill: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+            syllabic: Boolean,
+        }),
+    }),
+    Record({
+        Tap: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+        }),
+    }),
+    Record({
+        Approximant: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+            syllabic: Boolean,
+        }),
+    }),
+    Record({
+        LateralApproximant: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+            syllabic: Boolean,
+        }),
+    }),
+    Record({
+        Implosive: Record({
+            place: Place,
+            voiced: Boolean,
+            secondary: Array(SecondaryArticulation),
+            length: Length,
+        }),
+    }),
+    Record({
+        Click: Record({
+            place: Place,
+            lateral: Boolean,
+        }),
+    }),
+    Record({
+        Vowel: Record({
+            height: Height,
+            backness: Backness,
+            rounded: Boolean,
+            length: Length,
+            nasalized: Boolean,
         }),
     }),
 );
+export const Feature = Union(
+    Literal("Syllabic"),
+    Literal("Consonantal"),
+    Literal("Sonorant"),
+    Literal("Continuant"),
+    Literal("DelayedRelease"),
+    Literal("Approximant"),
+    Literal("Tap"),
+    Literal("Trill"),
+    Literal("Nasal"),
+    Literal("Voice"),
+    Literal("SpreadGlottis"),
+    Literal("ConstrictedGlottis"),
+    Literal("Click"),
+    Literal("Labial"),
+    Literal("Round"),
+    Literal("Labiodental"),
+    Literal("Coronal"),
+    Literal("Anterior"),
+    Literal("Distributed"),
+    Literal("Strident"),
+    Literal("Lateral"),
+    Literal("Dorsal"),
+    Literal("High"),
+    Literal("Low"),
+    Literal("Front"),
+    Literal("Back"),
+    Literal("Tense"),
+    Literal("Long"),
+);
+export const FeatureMatrix = Dictionary(Boolean, Feature);
+export const IpaDiagnosticKind = Union(
+    Record({ UnknownSymbol: String }),
+    Record({ UnsupportedDiacritic: String }),
+    Record({ DanglingDiacritic: String }),
+    Literal("UnsupportedTie"),
+    Literal("MismatchedTie"),
+    Literal("AmbiguousTap"),
+    Literal("AmbiguousAspiration"),
+);
+export const IpaDiagnostic = Record({
+    position: Number,
+    kind: IpaDiagnosticKind,
+});
+export const ParsedIpa = Record({
+    phones: Array(Phone),
+    diagnostics: Array(IpaDiagnostic),
+});
+export const Notation = Union(Literal("XSampa"), Literal("Kirshenbaum"));
+export const UnmappedSymbol = Record({
+    position: Number,
+    symbol: String,
+});
+export const Transliteration = Record({
+    output: String,
+    unmapped: Array(UnmappedSymbol),
+});
+export const ToneLevel = Union(
+    Literal("ExtraLow"),
+    Literal("Low"),
+    Literal("Mid"),
+    Literal("High"),
+    Literal("ExtraHigh"),
+);
+export const Tone = Union(
+    Record({ Level: ToneLevel }),
+    Record({ Contour: Array(ToneLevel) }),
+);
+export const Stress = Union(
+    Literal("Unstressed"),
+    Literal("Secondary"),
+    Literal("Primary"),
+);
+export const Suprasegmentals = Record({
+    tone: Tone.nullable(),
+    stress: Stress,
+    length: Length,
+});
+export const Syllable = Record({
+    phones: Array(Phone),
+    suprasegmentals: Suprasegmentals,
+});
 export const Phoneme = Record({
     ortho: String,
     primary: Phone,
     allo: Array(Phone),
+    suprasegmentals: Suprasegmentals,
+});
+export const CellValue = Union(
+    Literal("Null"),
+    Record({ Allophone: Number }),
+);
+export const MapmCell = Record({
+    property: String,
+    phoneme: String,
+    value: CellValue,
+});
+export const PhonemeSet = Record({
+    members: Array(String),
+    properties: Array(String),
+    mapm: Array(MapmCell),
+    additional: Dictionary(Phone, String),
+});
+export const Productions = Dictionary(Phone.nullable(), String);
+export const SyllabificationPrinciple = Union(
+    Literal("MaximalOnset"),
+    Literal("MaximalCoda"),
+);
+export const SyllableTemplate = Record({
+    pattern: String,
+    sets: Dictionary(String, String),
+    nucleus: String,
+    principle: SyllabificationPrinciple,
+});
+export const ParsedSyllable = Record({
+    onset: Array(String),
+    nucleus: String,
+    coda: Array(String),
+});
+export const StressPosition = Union(
+    Literal("Initial"),
+    Literal("Peninitial"),
+    Literal("Antepenultimate"),
+    Literal("Penultimate"),
+    Literal("Final"),
+);
+export const Edge = Union(Literal("Left"), Literal("Right"));
+export const Foot = Union(Literal("Trochee"), Literal("Iamb"));
+export const StressRule = Union(
+    Record({ Fixed: StressPosition }),
+    Record({
+        WeightSensitive: Record({
+            edge: Edge,
+            skip: Number,
+            window: Number.nullable(),
+            fallback: StressPosition,
+        }),
+    }),
+    Record({
+        Feet: Record({
+            foot: Foot,
+            direction: Edge,
+            main: Edge,
+            quantity_sensitive: Boolean,
+        }),
+    }),
+    Record({ Lexical: Record({ fallback: StressPosition }) }),
+);
+export const SyllableWeight = Record({
+    long_nucleus: Boolean,
+    coda: Boolean,
+    heavy: Number,
+});
+export const Prosody = Record({
+    rule: StressRule,
+    weight: SyllableWeight,
+});
+export const StressedWord = Record({
+    syllables: Array(Syllable),
+    ipa: String,
+});
+export const Target = Union(
+    Record({ Phoneme: String }),
+    Record({ Set: String }),
+);
+export const Context = Union(
+    Record({ Phoneme: String }),
+    Record({ Set: String }),
+    Literal("Boundary"),
+);
+export const Outcome = Union(
+    Record({ Property: String }),
+    Record({ Allophone: Number }),
+    Record({ InsertBefore: Phone }),
+    Record({ InsertAfter: Phone }),
+);
+export const EnvironmentRule = Record({
+    name: String,
+    target: Target,
+    before: Array(Context),
+    after: Array(Context),
+    outcome: Outcome,
+});
+export const Change = Union(
+    Record({ Realized: Phone.nullable() }),
+    Record({ Inserted: Phone }),
+);
+export const RuleFiring = Record({
+    rule: Number,
+    name: String,
+    position: Number,
+    change: Change,
+});
+export const Realization = Record({
+    phones: Array(Phone),
+    ipa: String,
+    trace: Array(RuleFiring),
+});
+export const WordPosition = Union(
+    Literal("WordInitial"),
+    Literal("WordFinal"),
+    Literal("Onset"),
+    Literal("Nucleus"),
+    Literal("Coda"),
+);
+export const Restriction = Record({
+    target: Target,
+    position: WordPosition,
+});
+export const Phonotactics = Record({
+    onset_clusters: Array(Array(String)).nullable(),
+    coda_clusters: Array(Array(String)).nullable(),
+    restrictions: Array(Restriction),
+});
+export const CandidateWord = Union(
+    Record({ Orthography: String }),
+    Record({ Phonemes: Array(String) }),
+);
+export const ViolationKind = Union(
+    Record({ UnknownPhoneme: String }),
+    Literal("Unsyllabifiable"),
+    Record({ ForbiddenOnset: Array(String) }),
+    Record({ ForbiddenCoda: Array(String) }),
+    Record({
+        Restricted: Record({ phoneme: String, position: WordPosition }),
+    }),
+);
+export const Violation = Record({
+    start: Number,
+    end: Number,
+    kind: ViolationKind,
+});
+export const Validation = Record({
+    phonemes: Array(String),
+    syllables: Array(ParsedSyllable).nullable(),
+    violations: Array(Violation),
+});
+export const Distribution = Union(
+    Literal("Uniform"),
+    Record({ Zipf: Record({ exponent: Number }) }),
+    Record({ Custom: Dictionary(Number, String) }),
+);
+export const GeneratorSettings = Record({
+    count: Number,
+    min_syllables: Number,
+    max_syllables: Number,
+    distribution: Distribution,
+    seed: Number.nullable(),
+    exclude: Array(Array(String)),
+    min_distance: Number,
+});
+export const GeneratedWord = Record({
+    ortho: String,
+    phonemes: Array(String),
+    ipa: String,
+});
+export const Generated = Record({
+    seed: Number,
+    words: Array(GeneratedWord),
+});
+export const SplitReflex = Record({
+    phoneme: String,
+    before: Array(Context),
+    after: Array(Context),
+});
+export const PhonemeChange = Union(
+    Record({ Split: Record({ source: String, into: Array(SplitReflex) }) }),
+    Record({ Merger: Record({ sources: Array(String), into: String }) }),
+    Record({ Loss: Record({ source: String }) }),
+);
+export const Correspondence = Record({
+    ancestor: String,
+    proto: String,
+    reflexes: Array(SplitReflex),
+});
+export const Source = Union(
+    Literal("Own"),
+    Record({ Ancestor: String }),
+);
+const Resolved = <T extends Runtype>(value: T) =>
+    Record({ value, source: Source });
+export const EffectiveLanguage = Record({
+    phonemes: Dictionary(Resolved(Phoneme), String),
+    tones: Array(Resolved(Tone)),
+    phoneme_sets: Dictionary(Resolved(PhonemeSet), String),
+    syllable_template: Resolved(SyllableTemplate).nullable(),
+    prosody: Resolved(Prosody).nullable(),
+    environment_rules: Resolved(Array(EnvironmentRule)),
+    phonotactics: Resolved(Phonotactics),
+    correspondences: Array(Correspondence),
 });
 export const Language = Record({
     name: String,
     description: Unknown.optional(),
     phonemes: Dictionary(Phoneme, String),
     ancestors: Array(String),
+    tones: Array(Tone),
+    phoneme_sets: Dictionary(PhonemeSet, String),
+    syllable_template: SyllableTemplate.nullable(),
+    prosody: Prosody.nullable(),
+    environment_rules: Array(EnvironmentRule),
+    phonotactics: Phonotactics,
+    sound_changes: String,
+    phoneme_changes: Array(PhonemeChange),
 });
 export const Protolanguage = Record({
     name: String,
     description: Unknown.optional(),
+    ancestors: Array(String),
     phonemes: Dictionary(Phoneme, String),
+    tones: Array(Tone),
+    phoneme_sets: Dictionary(PhonemeSet, String),
+    syllable_template: SyllableTemplate.nullable(),
+    prosody: Prosody.nullable(),
+    environment_rules: Array(EnvironmentRule),
+    phonotactics: Phonotactics,
+    correspondences: Array(Correspondence),
+});
+export const ChartManner = Union(
+    Literal("Plosive"),
+    Literal("Nasal"),
+    Literal("Trill"),
+    Literal("Tap"),
+    Literal("Fricative"),
+    Literal("LateralFricative"),
+    Literal("Approximant"),
+    Literal("LateralApproximant"),
+    Literal("Affricative"),
+    Literal("Implosive"),
+);
+export const ChartEntry = Record({
+    id: String,
+    ortho: String,
+    ipa: String,
+    allophone: Boolean,
+});
+export const ChartSlot = Record({
+    entries: Array(ChartEntry),
+    impossible: Boolean,
+    gap: Boolean,
+});
+export const ChartCell = Record({
+    left: ChartSlot,
+    right: ChartSlot,
+});
+export const ConsonantRow = Record({
+    manner: ChartManner,
+    cells: Array(ChartCell),
+});
+export const VowelRow = Record({
+    height: Height,
+    cells: Array(ChartCell),
+});
+export const InventoryChart = Record({
+    consonants: Array(ConsonantRow),
+    vowels: Array(VowelRow),
+    other: Array(ChartEntry),
+});
+export const ChartFormat = Union(Literal("Svg"), Literal("Typst"));
+export const DerivationStep = Record({
+    rule: Number,
+    before: String,
+    after: String,
+});
+export const Derivation = Record({
+    input: String,
+    output: String,
+    steps: Array(DerivationStep),
+});
+export const Interaction = Union(
+    Literal("Feeds"),
+    Literal("Bleeds"),
+    Literal("Counterfeeds"),
+    Literal("Counterbleeds"),
+);
+export const DerivationWarning = Union(
+    Record({ NeverFires: Record({ rule: Number }) }),
+    Record({
+        Interaction: Record({
+            interaction: Interaction,
+            rule: Number,
+            other: Number,
+            example: String,
+        }),
+    }),
+);
+export const DerivationReport = Record({
+    rules: Array(String),
+    derivations: Array(Derivation),
+    warnings: Array(DerivationWarning),
+});
+export const TableFormat = Union(Literal("Csv"), Literal("Typst"));
+export const TreeFormat = Union(
+    Literal("Newick"),
+    Literal("Dot"),
+    Literal("Svg"),
+);
+export const Ancestry = Dictionary(Array(String), String);
+export const Stage = Record({
+    name: String,
+    protolangs: Array(Protolanguage),
+    ancestry: Ancestry,
+});
+export const PhonemeDiff = Record({
+    id: String,
+    before: Phoneme.nullable(),
+    after: Phoneme.nullable(),
+});
+export const LanguageDiff = Record({
+    name: String,
+    ancestors: Array(String),
+    phonemes: Array(PhonemeDiff),
 });
 export const LanguageGroup = Record({
     version: String,
     protolangs: Array(Protolanguage),
     langs: Array(Language),
+    history: Array(Stage),
+    ancestry: Ancestry,
 });
 
 export type Place = Static<typeof Place>;
 export type ObstruentAttachment = Static<typeof ObstruentAttachment>;
+export type SecondaryArticulation = Static<typeof SecondaryArticulation>;
+export type Height = Static<typeof Height>;
+export type Backness = Static<typeof Backness>;
+export type Length = Static<typeof Length>;
 export type Phone = Static<typeof Phone>;
+export type Feature = Static<typeof Feature>;
+export type FeatureMatrix = Static<typeof FeatureMatrix>;
+export type IpaDiagnosticKind = Static<typeof IpaDiagnosticKind>;
+export type IpaDiagnostic = Static<typeof IpaDiagnostic>;
+export type ParsedIpa = Static<typeof ParsedIpa>;
+export type Notation = Static<typeof Notation>;
+export type UnmappedSymbol = Static<typeof UnmappedSymbol>;
+export type Transliteration = Static<typeof Transliteration>;
+export type ToneLevel = Static<typeof ToneLevel>;
+export type Tone = Static<typeof Tone>;
+export type Stress = Static<typeof Stress>;
+export type Suprasegmentals = Static<typeof Suprasegmentals>;
+export type Syllable = Static<typeof Syllable>;
 export type Phoneme = Static<typeof Phoneme>;
+export type CellValue = Static<typeof CellValue>;
+export type MapmCell = Static<typeof MapmCell>;
+export type PhonemeSet = Static<typeof PhonemeSet>;
+export type Productions = Static<typeof Productions>;
+export type SyllabificationPrinciple = Static<typeof SyllabificationPrinciple>;
+export type SyllableTemplate = Static<typeof SyllableTemplate>;
+export type ParsedSyllable = Static<typeof ParsedSyllable>;
+export type StressPosition = Static<typeof StressPosition>;
+export type Edge = Static<typeof Edge>;
+export type Foot = Static<typeof Foot>;
+export type StressRule = Static<typeof StressRule>;
+export type SyllableWeight = Static<typeof SyllableWeight>;
+export type Prosody = Static<typeof Prosody>;
+export type StressedWord = Static<typeof StressedWord>;
+export type Target = Static<typeof Target>;
+export type Context = Static<typeof Context>;
+export type Outcome = Static<typeof Outcome>;
+export type EnvironmentRule = Static<typeof EnvironmentRule>;
+export type Change = Static<typeof Change>;
+export type RuleFiring = Static<typeof RuleFiring>;
+export type Realization = Static<typeof Realization>;
+export type WordPosition = Static<typeof WordPosition>;
+export type Restriction = Static<typeof Restriction>;
+export type Phonotactics = Static<typeof Phonotactics>;
+export type CandidateWord = Static<typeof CandidateWord>;
+export type ViolationKind = Static<typeof ViolationKind>;
+export type Violation = Static<typeof Violation>;
+export type Validation = Static<typeof Validation>;
+export type Distribution = Static<typeof Distribution>;
+export type GeneratorSettings = Static<typeof GeneratorSettings>;
+export type GeneratedWord = Static<typeof GeneratedWord>;
+export type Generated = Static<typeof Generated>;
 export type Protolanguage = Static<typeof Protolanguage>;
+export type SplitReflex = Static<typeof SplitReflex>;
+export type PhonemeChange = Static<typeof PhonemeChange>;
+export type Correspondence = Static<typeof Correspondence>;
+export type Source = Static<typeof Source>;
+export type EffectiveLanguage = Static<typeof EffectiveLanguage>;
 export type Language = Static<typeof Language>;
+export type ChartManner = Static<typeof ChartManner>;
+export type ChartEntry = Static<typeof ChartEntry>;
+export type ChartSlot = Static<typeof ChartSlot>;
+export type ChartCell = Static<typeof ChartCell>;
+export type ConsonantRow = Static<typeof ConsonantRow>;
+export type VowelRow = Static<typeof VowelRow>;
+export type InventoryChart = Static<typeof InventoryChart>;
+export type ChartFormat = Static<typeof ChartFormat>;
+export type DerivationStep = Static<typeof DerivationStep>;
+export type Derivation = Static<typeof Derivation>;
+export type Interaction = Static<typeof Interaction>;
+export type DerivationWarning = Static<typeof DerivationWarning>;
+export type DerivationReport = Static<typeof DerivationReport>;
+export type TableFormat = Static<typeof TableFormat>;
+export type TreeFormat = Static<typeof TreeFormat>;
+export type Ancestry = Static<typeof Ancestry>;
+export type Stage = Static<typeof Stage>;
+export type PhonemeDiff = Static<typeof PhonemeDiff>;
+export type LanguageDiff = Static<typeof LanguageDiff>;
 export type LanguageGroup = Static<typeof LanguageGroup>;
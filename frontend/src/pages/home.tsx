@@ -108,9 +108,6 @@ export default function HomePage() {
     async function epochProject() {
         try {
             await invoke("epoch_language_group", {});
-            await nameMutate(async () => {
-                return await invoke("get_project_name", {});
-            });
             showAppNotification({
                 severity: "info",
                 message: "Epoch created",
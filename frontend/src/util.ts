@@ -1,7 +1,21 @@
 /* Error handling from https://kentcdodds.com/blog/get-a-catch-block-error-message-with-typescript */
 import { invoke } from "@tauri-apps/api";
-import { String as RString } from "runtypes";
-import { Phone } from "~/src/data";
+import { Array as RArray, Number as RNumber, String as RString } from "runtypes";
+import {
+    ChartFormat,
+    DerivationReport,
+    FeatureMatrix,
+    InventoryChart,
+    Notation,
+    ParsedIpa,
+    Phone,
+    Phoneme,
+    Syllable,
+    TableFormat,
+    Tone,
+    Transliteration,
+    TreeFormat,
+} from "~/src/data";
 
 type ErrorWithMessage = {
     message: string;
@@ -42,6 +56,113 @@ export async function fromSil(input: string) {
     return RString.check(await invoke("from_sil", { input }));
 }
 
+/* IPA conversion method: X-SAMPA and Kirshenbaum, both ways */
+
+export async function fromXsampa(input: string) {
+    return Transliteration.check(await invoke("from_xsampa", { input }));
+}
+
+export async function toXsampa(input: string) {
+    return Transliteration.check(await invoke("to_xsampa", { input }));
+}
+
+export async function fromKirshenbaum(input: string) {
+    return Transliteration.check(await invoke("from_kirshenbaum", { input }));
+}
+
+export async function toKirshenbaum(input: string) {
+    return Transliteration.check(await invoke("to_kirshenbaum", { input }));
+}
+
 export async function displayPhone(phone: Phone) {
     return RString.check(await invoke("display_phone", { phone }));
 }
+
+export async function displayPhoneAs(phone: Phone, notation: Notation) {
+    return RString.check(await invoke("display_phone_as", { phone, notation }));
+}
+
+export async function displayPhoneme(phoneme: Phoneme) {
+    return RString.check(await invoke("display_phoneme", { phoneme }));
+}
+
+export async function displaySyllable(syllable: Syllable) {
+    return RString.check(await invoke("display_syllable", { syllable }));
+}
+
+export async function parseTone(numerals: string) {
+    return Tone.nullable().check(await invoke("parse_tone", { numerals }));
+}
+
+export async function getPhoneDistance(from: Phone, to: Phone) {
+    return RNumber.check(await invoke("get_phone_distance", { from, to }));
+}
+
+export async function getSequenceDistance(from: Phone[], to: Phone[]) {
+    return RNumber.check(await invoke("get_sequence_distance", { from, to }));
+}
+
+export async function parseIpa(input: string) {
+    return ParsedIpa.check(await invoke("parse_ipa", { input }));
+}
+
+export async function getPhoneFeatures(phone: Phone) {
+    return FeatureMatrix.check(await invoke("get_phone_features", { phone }));
+}
+
+export async function getPhoneFromFeatures(features: FeatureMatrix) {
+    return Phone.nullable().check(
+        await invoke("get_phone_from_features", { features }),
+    );
+}
+
+export async function renderChart(chart: InventoryChart, format: ChartFormat) {
+    return RString.check(await invoke("render_chart", { chart, format }));
+}
+
+export async function exportChart(chart: InventoryChart, format: ChartFormat) {
+    await invoke("export_chart", { chart, format });
+}
+
+export async function renderDerivation(
+    report: DerivationReport,
+    format: TableFormat,
+) {
+    return RString.check(
+        await invoke("render_derivation", { report, format }),
+    );
+}
+
+export async function exportDerivation(
+    report: DerivationReport,
+    format: TableFormat,
+) {
+    await invoke("export_derivation", { report, format });
+}
+
+export async function renderFamilyTree(format: TreeFormat, annotate: boolean) {
+    return RString.check(
+        await invoke("render_family_tree", { format, annotate }),
+    );
+}
+
+export async function exportFamilyTree(format: TreeFormat, annotate: boolean) {
+    await invoke("export_family_tree", { format, annotate });
+}
+
+/** A WAV file of the phone, for an `<audio>` element or `AudioContext` */
+export async function synthesizePhone(phone: Phone) {
+    const bytes = RArray(RNumber).check(
+        await invoke("synthesize_phone", { phone }),
+    );
+
+    return new Uint8Array(bytes);
+}
+
+export async function synthesizePhones(phones: Phone[]) {
+    const bytes = RArray(RNumber).check(
+        await invoke("synthesize_phones", { phones }),
+    );
+
+    return new Uint8Array(bytes);
+}
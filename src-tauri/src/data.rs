@@ -1,9 +1,84 @@
+mod chart;
+mod derivation;
+mod distance;
+mod effective;
+mod environment;
+mod family_tree;
+mod features;
+mod generator;
+mod ipa;
 mod language;
 mod language_group;
 mod phoneme;
+mod phoneme_change;
+mod phoneme_set;
+mod phonotactics;
 mod protolanguage;
+mod sound_change;
+mod stage;
+mod stress;
+mod suprasegmental;
+mod syllabify;
 
+pub use chart::*;
+pub use derivation::*;
+pub use distance::*;
+pub use effective::*;
+pub use environment::*;
+pub use features::*;
+pub use generator::*;
+pub use ipa::*;
 pub use language::*;
 pub use language_group::*;
 pub use phoneme::*;
+pub use phoneme_change::*;
+pub use phoneme_set::*;
+pub use phonotactics::*;
 pub use protolanguage::*;
+pub use sound_change::*;
+pub use stage::*;
+pub use stress::*;
+pub use suprasegmental::*;
+pub use syllabify::*;
+
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use std::collections::HashMap;
+
+    use uuid::Uuid;
+
+    use super::{parse_ipa, Phone, Phoneme};
+
+    /// The first phone of an IPA string
+    pub fn phone(ipa: &str) -> Phone {
+        parse_ipa(ipa).phones.remove(0)
+    }
+
+    /// A phoneme spelled as its IPA, with no allophones
+    pub fn phoneme(ipa: &str) -> Phoneme {
+        Phoneme {
+            ortho: ipa.to_string(),
+            primary: phone(ipa),
+            allo: vec![],
+            suprasegmentals: Default::default(),
+        }
+    }
+
+    /// Phonemes from their spelling, IPA and allophones, with ids counting
+    /// from 0
+    pub fn phonemes(phonemes: &[(&str, &str, &[&str])]) -> HashMap<Uuid, Phoneme> {
+        phonemes
+            .iter()
+            .enumerate()
+            .map(|(i, (ortho, ipa, allo))| {
+                let phoneme = Phoneme {
+                    ortho: ortho.to_string(),
+                    allo: allo.iter().copied().map(phone).collect(),
+                    ..phoneme(ipa)
+                };
+
+                (Uuid::from_u128(i as u128), phoneme)
+            })
+            .collect()
+    }
+}
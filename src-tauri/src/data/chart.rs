@@ -0,0 +1,440 @@
+//! IPA chart layout of a phoneme inventory
+//!
+//! Consonants are laid out by manner and [`Place`], vowels by [`Height`] and
+//! [`Backness`]. Every cell has a left slot (voiceless or unrounded) and a
+//! right slot (voiced or rounded), as on the IPA chart. Allophones are placed
+//! in the chart like phonemes, but marked as such.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Backness, Height, Length, Phone, Phoneme, Place};
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ChartManner {
+    Plosive,
+    Nasal,
+    Trill,
+    Tap,
+    Fricative,
+    LateralFricative,
+    Approximant,
+    LateralApproximant,
+    Affricative,
+    Implosive,
+}
+
+impl ChartManner {
+    /// Rows of the IPA chart, which are always shown
+    const STANDARD: [ChartManner; 8] = [
+        ChartManner::Plosive,
+        ChartManner::Nasal,
+        ChartManner::Trill,
+        ChartManner::Tap,
+        ChartManner::Fricative,
+        ChartManner::LateralFricative,
+        ChartManner::Approximant,
+        ChartManner::LateralApproximant,
+    ];
+
+    /// Rows that are only shown if the inventory uses them
+    const EXTRA: [ChartManner; 2] = [ChartManner::Affricative, ChartManner::Implosive];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartManner::Plosive => "Plosive",
+            ChartManner::Nasal => "Nasal",
+            ChartManner::Trill => "Trill",
+            ChartManner::Tap => "Tap or Flap",
+            ChartManner::Fricative => "Fricative",
+            ChartManner::LateralFricative => "Lateral fricative",
+            ChartManner::Approximant => "Approximant",
+            ChartManner::LateralApproximant => "Lateral approximant",
+            ChartManner::Affricative => "Affricate",
+            ChartManner::Implosive => "Implosive",
+        }
+    }
+
+    /// The plainest phone of the manner, used to find impossible cells
+    fn phone(self, place: Place, voiced: bool) -> Phone {
+        let attachments = HashSet::new();
+        let secondary = HashSet::new();
+        let length = Length::Short;
+        let syllabic = false;
+
+        match self {
+            ChartManner::Plosive => Phone::Plosive {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                length,
+            },
+            ChartManner::Nasal => Phone::Nasal {
+                place,
+                voiced,
+                secondary,
+                length,
+                syllabic,
+            },
+            ChartManner::Trill => Phone::Trill {
+                place,
+                voiced,
+                secondary,
+                length,
+                syllabic,
+            },
+            ChartManner::Tap => Phone::Tap {
+                place,
+                voiced,
+                secondary,
+                length,
+            },
+            ChartManner::Fricative => Phone::Fricative {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                length,
+            },
+            ChartManner::LateralFricative => Phone::LateralFricative {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                length,
+            },
+            ChartManner::Approximant => Phone::Approximant {
+                place,
+                voiced,
+                secondary,
+                length,
+                syllabic,
+            },
+            ChartManner::LateralApproximant => Phone::LateralApproximant {
+                place,
+                voiced,
+                secondary,
+                length,
+                syllabic,
+            },
+            ChartManner::Affricative => Phone::Affricative {
+                start_place: place,
+                end_place: place,
+                voiced,
+                attachments,
+                secondary,
+                length,
+            },
+            ChartManner::Implosive => Phone::Implosive {
+                place,
+                voiced,
+                secondary,
+                length,
+            },
+        }
+    }
+}
+
+/// Where a phone goes in the chart
+enum Position {
+    Consonant {
+        manner: ChartManner,
+        place: Place,
+        voiced: bool,
+    },
+    Vowel {
+        height: Height,
+        backness: Backness,
+        rounded: bool,
+    },
+    Other,
+}
+
+impl Position {
+    fn of(phone: &Phone) -> Position {
+        let consonant = |manner, place: &Place, voiced: &bool| Position::Consonant {
+            manner,
+            place: *place,
+            voiced: *voiced,
+        };
+
+        match phone {
+            Phone::Plosive { place, voiced, .. } => consonant(ChartManner::Plosive, place, voiced),
+            // Affricates are placed by where their release is
+            Phone::Affricative {
+                end_place, voiced, ..
+            } => consonant(ChartManner::Affricative, end_place, voiced),
+            Phone::Fricative { place, voiced, .. } => {
+                consonant(ChartManner::Fricative, place, voiced)
+            }
+            Phone::LateralFricative { place, voiced, .. } => {
+                consonant(ChartManner::LateralFricative, place, voiced)
+            }
+            Phone::Nasal { place, voiced, .. } => consonant(ChartManner::Nasal, place, voiced),
+            Phone::Trill { place, voiced, .. } => consonant(ChartManner::Trill, place, voiced),
+            Phone::Tap { place, voiced, .. } => consonant(ChartManner::Tap, place, voiced),
+            Phone::Approximant { place, voiced, .. } => {
+                consonant(ChartManner::Approximant, place, voiced)
+            }
+            Phone::LateralApproximant { place, voiced, .. } => {
+                consonant(ChartManner::LateralApproximant, place, voiced)
+            }
+            Phone::Implosive { place, voiced, .. } => {
+                consonant(ChartManner::Implosive, place, voiced)
+            }
+            Phone::Vowel {
+                height,
+                backness,
+                rounded,
+                ..
+            } => Position::Vowel {
+                height: *height,
+                backness: *backness,
+                rounded: *rounded,
+            },
+            Phone::Click { .. } => Position::Other,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChartEntry {
+    /// The phoneme this phone belongs to
+    pub id: Uuid,
+    pub ortho: String,
+    pub ipa: String,
+    pub allophone: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChartSlot {
+    pub entries: Vec<ChartEntry>,
+    /// Shaded on the IPA chart, as no phone can be articulated there
+    pub impossible: bool,
+    /// The slot could hold a phone, but the inventory has none
+    pub gap: bool,
+}
+
+impl ChartSlot {
+    fn new(impossible: bool) -> ChartSlot {
+        ChartSlot {
+            entries: vec![],
+            impossible,
+            gap: !impossible,
+        }
+    }
+
+    fn push(&mut self, entry: ChartEntry) {
+        if !self
+            .entries
+            .iter()
+            .any(|existing| existing.ipa == entry.ipa)
+        {
+            self.entries.push(entry);
+            self.gap = false;
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChartCell {
+    /// Voiceless consonants or unrounded vowels
+    pub left: ChartSlot,
+    /// Voiced consonants or rounded vowels
+    pub right: ChartSlot,
+}
+
+impl ChartCell {
+    fn slot_mut(&mut self, right: bool) -> &mut ChartSlot {
+        if right {
+            &mut self.right
+        } else {
+            &mut self.left
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ConsonantRow {
+    pub manner: ChartManner,
+    /// Indexed by [`Place`]
+    pub cells: Vec<ChartCell>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct VowelRow {
+    pub height: Height,
+    /// Indexed by [`Backness`]
+    pub cells: Vec<ChartCell>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct InventoryChart {
+    pub consonants: Vec<ConsonantRow>,
+    pub vowels: Vec<VowelRow>,
+    /// Phones without a place in either chart, such as clicks
+    pub other: Vec<ChartEntry>,
+}
+
+impl InventoryChart {
+    pub fn new(phonemes: &HashMap<Uuid, Phoneme>) -> InventoryChart {
+        let mut consonants: Vec<_> = ChartManner::STANDARD
+            .into_iter()
+            .chain(ChartManner::EXTRA)
+            .map(|manner| ConsonantRow {
+                manner,
+                cells: Place::ALL
+                    .into_iter()
+                    .map(|place| ChartCell {
+                        left: ChartSlot::new(manner.phone(place, false).validate().is_err()),
+                        right: ChartSlot::new(manner.phone(place, true).validate().is_err()),
+                    })
+                    .collect(),
+            })
+            .collect();
+        let mut vowels: Vec<_> = Height::ALL
+            .into_iter()
+            .map(|height| VowelRow {
+                height,
+                cells: vec![
+                    ChartCell {
+                        left: ChartSlot::new(false),
+                        right: ChartSlot::new(false),
+                    };
+                    Backness::ALL.len()
+                ],
+            })
+            .collect();
+        let mut other = vec![];
+
+        // Primary phones first, so that an allophone never hides a phoneme
+        let mut phones: Vec<_> = phonemes
+            .iter()
+            .flat_map(|(id, phoneme)| {
+                let primary = (&phoneme.primary, false);
+                let allophones = phoneme.allo.iter().map(|allo| (allo, true));
+
+                std::iter::once(primary)
+                    .chain(allophones)
+                    .map(move |(phone, allophone)| {
+                        let entry = ChartEntry {
+                            id: *id,
+                            ortho: phoneme.ortho.clone(),
+                            ipa: phone.to_string(),
+                            allophone,
+                        };
+
+                        (entry, phone)
+                    })
+            })
+            .collect();
+        phones.sort_by(|(a, _), (b, _)| {
+            (a.allophone, &a.ipa, &a.ortho).cmp(&(b.allophone, &b.ipa, &b.ortho))
+        });
+
+        for (entry, phone) in phones {
+            match Position::of(phone) {
+                Position::Consonant {
+                    manner,
+                    place,
+                    voiced,
+                } => {
+                    let row = consonants
+                        .iter_mut()
+                        .find(|row| row.manner == manner)
+                        .unwrap();
+                    row.cells[place as usize].slot_mut(voiced).push(entry);
+                }
+                Position::Vowel {
+                    height,
+                    backness,
+                    rounded,
+                } => {
+                    vowels[height as usize].cells[backness as usize]
+                        .slot_mut(rounded)
+                        .push(entry);
+                }
+                Position::Other => {
+                    if !other
+                        .iter()
+                        .any(|existing: &ChartEntry| existing.ipa == entry.ipa)
+                    {
+                        other.push(entry);
+                    }
+                }
+            }
+        }
+
+        consonants.retain(|row| {
+            ChartManner::STANDARD.contains(&row.manner)
+                || row
+                    .cells
+                    .iter()
+                    .any(|cell| !cell.left.entries.is_empty() || !cell.right.entries.is_empty())
+        });
+
+        InventoryChart {
+            consonants,
+            vowels,
+            other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ChartManner, InventoryChart};
+    use crate::data::{fixtures, Backness, Height, Place};
+    use insta::assert_yaml_snapshot;
+
+    #[test]
+    fn places_phonemes_and_allophones() {
+        let phonemes = fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("t", "t", &["ɾ", "tʰ"]),
+            ("d", "d", &["ɾ"]),
+            ("t͡s", "t͡s", &[]),
+            ("ǃ", "ǃ", &[]),
+            ("o", "o", &[]),
+        ]);
+        let chart = InventoryChart::new(&phonemes);
+
+        let row = |manner| {
+            chart
+                .consonants
+                .iter()
+                .find(|row| row.manner == manner)
+                .unwrap()
+        };
+        let plosives = &row(ChartManner::Plosive).cells;
+        let taps = &row(ChartManner::Tap).cells;
+
+        assert_yaml_snapshot!(plosives[Place::Alveolar as usize]);
+        assert!(plosives[Place::Bilabial as usize].right.gap);
+        assert!(plosives[Place::Pharyngeal as usize].right.impossible);
+        // An allophone shared by two phonemes is only shown once
+        assert_eq!(taps[Place::Alveolar as usize].right.entries.len(), 1);
+        assert!(
+            row(ChartManner::Affricative).cells[Place::Alveolar as usize]
+                .left
+                .entries
+                .iter()
+                .any(|entry| entry.ipa == "t͡s")
+        );
+        assert!(!chart
+            .consonants
+            .iter()
+            .any(|row| row.manner == ChartManner::Implosive));
+        assert_eq!(chart.other.len(), 1);
+        assert_eq!(
+            chart.vowels[Height::CloseMid as usize].cells[Backness::Back as usize]
+                .right
+                .entries[0]
+                .ipa,
+            "o"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Step-by-step derivations of forms through sound changes
+//!
+//! Besides the derivation of every form, rules are checked for how they
+//! interact. A rule that makes a later rule apply feeds it, and one that
+//! stops it bleeds it. A later rule that would have fed or bled an earlier
+//! one, had it come first, counterfeeds or counterbleeds it.
+
+use serde::{Deserialize, Serialize};
+
+use super::{Phone, SoundChanges};
+
+/// A rule that changed a form
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DerivationStep {
+    /// Index of the rule
+    pub rule: usize,
+    /// The form before the rule, in IPA
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Derivation {
+    pub input: String,
+    pub output: String,
+    pub steps: Vec<DerivationStep>,
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Interaction {
+    Feeds,
+    Bleeds,
+    Counterfeeds,
+    Counterbleeds,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum DerivationWarning {
+    /// The rule changed none of the forms
+    NeverFires { rule: usize },
+    /// `rule` feeds, bleeds, counterfeeds or counterbleeds `other`, as seen
+    /// in the derivation of `example`
+    Interaction {
+        interaction: Interaction,
+        rule: usize,
+        other: usize,
+        example: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DerivationReport {
+    /// The rules as written, which steps and warnings index into
+    pub rules: Vec<String>,
+    pub derivations: Vec<Derivation>,
+    pub warnings: Vec<DerivationWarning>,
+}
+
+fn ipa(phones: &[Phone]) -> String {
+    phones.iter().map(Phone::to_string).collect()
+}
+
+impl SoundChanges {
+    /// Which rules change a form, when applied in `order`, by rule index
+    fn fired(&self, order: &[usize], word: &[Phone]) -> Vec<bool> {
+        let mut fired = vec![false; self.sources().len()];
+        let mut word = word.to_vec();
+
+        for &rule in order {
+            let changed = self.apply_nth(rule, &word);
+            fired[rule] = changed != word;
+            word = changed;
+        }
+
+        fired
+    }
+
+    /// Derives every form, and warns about rules that never fire or that
+    /// interact with each other
+    pub fn derive(&self, words: &[Vec<Phone>]) -> DerivationReport {
+        let rules = self.sources();
+        let count = rules.len();
+        let order: Vec<usize> = (0..count).collect();
+
+        let derivations = words
+            .iter()
+            .map(|word| {
+                let mut steps = vec![];
+                let mut form = word.clone();
+
+                for rule in 0..count {
+                    let changed = self.apply_nth(rule, &form);
+                    if changed != form {
+                        steps.push(DerivationStep {
+                            rule,
+                            before: ipa(&form),
+                            after: ipa(&changed),
+                        });
+                    }
+                    form = changed;
+                }
+
+                Derivation {
+                    input: ipa(word),
+                    output: ipa(&form),
+                    steps,
+                }
+            })
+            .collect();
+
+        let mut warnings = vec![];
+        let mut found = |warning: DerivationWarning| {
+            let seen = warnings.iter().any(|other| match (other, &warning) {
+                (
+                    DerivationWarning::Interaction {
+                        interaction,
+                        rule,
+                        other,
+                        ..
+                    },
+                    DerivationWarning::Interaction {
+                        interaction: new_interaction,
+                        rule: new_rule,
+                        other: new_other,
+                        ..
+                    },
+                ) => (interaction, rule, other) == (new_interaction, new_rule, new_other),
+                (other, warning) => other == warning,
+            });
+
+            if !seen {
+                warnings.push(warning);
+            }
+        };
+
+        let mut ever_fired = vec![false; count];
+        for word in words {
+            let example = ipa(word);
+            let actual = self.fired(&order, word);
+
+            for (fired, ever) in actual.iter().zip(&mut ever_fired) {
+                *ever |= fired;
+            }
+
+            for earlier in 0..count {
+                let skipped: Vec<usize> = order.iter().copied().filter(|r| *r != earlier).collect();
+                let without = self.fired(&skipped, word);
+
+                for later in earlier + 1..count {
+                    let interaction = match (actual[later], without[later]) {
+                        (true, false) => Interaction::Feeds,
+                        (false, true) => Interaction::Bleeds,
+                        _ => continue,
+                    };
+                    found(DerivationWarning::Interaction {
+                        interaction,
+                        rule: earlier,
+                        other: later,
+                        example: example.clone(),
+                    });
+                }
+
+                // Each later rule moved to just before this one
+                for later in earlier + 1..count {
+                    let swapped: Vec<usize> = order[..earlier]
+                        .iter()
+                        .copied()
+                        .chain([later, earlier])
+                        .chain(order[earlier + 1..].iter().copied().filter(|r| *r != later))
+                        .collect();
+                    let reordered = self.fired(&swapped, word);
+
+                    let interaction = match (actual[earlier], reordered[earlier]) {
+                        (false, true) => Interaction::Counterfeeds,
+                        (true, false) => Interaction::Counterbleeds,
+                        _ => continue,
+                    };
+                    found(DerivationWarning::Interaction {
+                        interaction,
+                        rule: later,
+                        other: earlier,
+                        example: example.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut never: Vec<DerivationWarning> = ever_fired
+            .iter()
+            .enumerate()
+            .filter(|(_, fired)| !**fired)
+            .map(|(rule, _)| DerivationWarning::NeverFires { rule })
+            .collect();
+        never.append(&mut warnings);
+
+        DerivationReport {
+            rules: rules.into_iter().map(str::to_string).collect(),
+            derivations,
+            warnings: never,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DerivationWarning, Interaction};
+    use crate::data::{parse_ipa, SoundChanges};
+    use insta::assert_yaml_snapshot;
+
+    fn derive(changes: &str, words: &[&str]) -> super::DerivationReport {
+        let words: Vec<_> = words.iter().map(|word| parse_ipa(word).phones).collect();
+
+        SoundChanges::parse(changes).unwrap().derive(&words)
+    }
+
+    fn interactions(changes: &str, words: &[&str]) -> Vec<(Interaction, usize, usize)> {
+        derive(changes, words)
+            .warnings
+            .into_iter()
+            .filter_map(|warning| match warning {
+                DerivationWarning::Interaction {
+                    interaction,
+                    rule,
+                    other,
+                    ..
+                } => Some((interaction, rule, other)),
+                DerivationWarning::NeverFires { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn derives_step_by_step() {
+        let report = derive("V = a i\np > b / V_V\nb > v / V_V\nk > x", &["apa", "pa"]);
+
+        assert_yaml_snapshot!(report);
+    }
+
+    #[test]
+    fn finds_feeding_and_bleeding() {
+        // Voicing makes the spirantization apply
+        assert_eq!(
+            interactions("V = a\np > b / V_V\nb > v / V_V", &["apa"]),
+            [(Interaction::Feeds, 0, 1)]
+        );
+        // Apocope removes the vowel that palatalization needs
+        assert_eq!(
+            interactions("i > ∅ / _#\nk > tʃ / _i", &["aki"]),
+            [(Interaction::Bleeds, 0, 1)]
+        );
+        // In the opposite order, it comes too late to stop it
+        assert_eq!(
+            interactions("k > tʃ / _i\ni > ∅ / _#", &["aki"]),
+            [(Interaction::Counterbleeds, 1, 0)]
+        );
+    }
+
+    #[test]
+    fn finds_counterfeeding_and_idle_rules() {
+        let report = derive("b > v / a_a\np > b / a_a\nq > ɢ", &["apa"]);
+
+        assert_eq!(
+            report.warnings[0],
+            DerivationWarning::NeverFires { rule: 0 }
+        );
+        assert_eq!(
+            report.warnings[1],
+            DerivationWarning::NeverFires { rule: 2 }
+        );
+        assert_eq!(
+            interactions("b > v / a_a\np > b / a_a", &["apa"]),
+            [(Interaction::Counterfeeds, 1, 0)]
+        );
+    }
+}
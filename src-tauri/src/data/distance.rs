@@ -0,0 +1,155 @@
+//! Feature-weighted phonetic distance
+//!
+//! Two phones are compared feature by feature. Features that differ cost
+//! their weight, features specified in only one of the phones cost half of
+//! it, and the total is normalized so that distances lie between 0 and 1.
+//! Sequences are compared by a weighted Levenshtein distance using the phone
+//! distance as the cost of a substitution.
+
+use super::{Feature, FeatureMatrix, Phone};
+
+/// The cost of inserting or deleting a phone in a sequence
+pub const INDEL_COST: f64 = 1.0;
+
+impl Feature {
+    /// How much a difference in the feature counts towards distance
+    ///
+    /// Major class and manner features outweigh place, which outweighs
+    /// laryngeal and secondary features.
+    fn weight(self) -> f64 {
+        match self {
+            Feature::Syllabic | Feature::Consonantal | Feature::Sonorant => 3.0,
+            Feature::Continuant | Feature::Nasal | Feature::Approximant => 2.0,
+            Feature::DelayedRelease
+            | Feature::Tap
+            | Feature::Trill
+            | Feature::Lateral
+            | Feature::Click
+            | Feature::Labial
+            | Feature::Coronal
+            | Feature::Dorsal => 1.5,
+            Feature::Labiodental
+            | Feature::Anterior
+            | Feature::Distributed
+            | Feature::Strident
+            | Feature::High
+            | Feature::Low
+            | Feature::Front
+            | Feature::Back
+            | Feature::Round => 1.0,
+            Feature::Voice
+            | Feature::SpreadGlottis
+            | Feature::ConstrictedGlottis
+            | Feature::Tense
+            | Feature::Long => 0.75,
+        }
+    }
+}
+
+impl FeatureMatrix {
+    /// Weighted distance between the matrices, between 0 and 1
+    pub fn distance(&self, other: &FeatureMatrix) -> f64 {
+        let total: f64 = Feature::ALL.iter().map(|feature| feature.weight()).sum();
+        let cost: f64 = Feature::ALL
+            .iter()
+            .map(|feature| match (self.get(*feature), other.get(*feature)) {
+                (Some(a), Some(b)) if a != b => feature.weight(),
+                (Some(_), None) | (None, Some(_)) => feature.weight() / 2.0,
+                _ => 0.0,
+            })
+            .sum();
+
+        cost / total
+    }
+}
+
+impl Phone {
+    /// Weighted feature distance to another phone, between 0 and 1
+    pub fn distance(&self, other: &Phone) -> f64 {
+        self.features().distance(&other.features())
+    }
+}
+
+/// Weighted Levenshtein distance between two sequences of phones
+///
+/// Inserting or deleting a phone costs [`INDEL_COST`], and substituting one
+/// costs the distance between the phones.
+pub fn sequence_distance(from: &[Phone], to: &[Phone]) -> f64 {
+    let from: Vec<_> = from.iter().map(Phone::features).collect();
+    let to: Vec<_> = to.iter().map(Phone::features).collect();
+
+    // Distances from every prefix of `from` to the current prefix of `to`
+    let mut previous: Vec<f64> = (0..=from.len()).map(|i| i as f64 * INDEL_COST).collect();
+
+    for (j, to_phone) in to.iter().enumerate() {
+        let mut current = vec![(j + 1) as f64 * INDEL_COST];
+
+        for (i, from_phone) in from.iter().enumerate() {
+            let substitution = previous[i] + from_phone.distance(to_phone);
+            let deletion = current[i] + INDEL_COST;
+            let insertion = previous[i + 1] + INDEL_COST;
+
+            current.push(substitution.min(deletion).min(insertion));
+        }
+
+        previous = current;
+    }
+
+    previous[from.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sequence_distance, INDEL_COST};
+    use crate::data::{fixtures::phone, parse_ipa, Phone};
+    use insta::assert_yaml_snapshot;
+
+    fn phones(ipa: &str) -> Vec<Phone> {
+        parse_ipa(ipa).phones
+    }
+
+    #[test]
+    fn known_pairs_are_ordered() {
+        let distance = |a, b| phone(a).distance(&phone(b));
+
+        assert_eq!(distance("p", "p"), 0.0);
+        assert_eq!(distance("p", "s"), distance("s", "p"));
+        assert!(distance("p", "b") < distance("p", "s"));
+        assert!(distance("t", "d") < distance("t", "n"));
+        assert!(distance("s", "ʃ") < distance("s", "x"));
+        assert!(distance("i", "e") < distance("i", "a"));
+        assert!(distance("j", "i") < distance("j", "k"));
+        assert!(distance("p", "a") > distance("p", "s"));
+    }
+
+    #[test]
+    fn distances_between_common_phones() {
+        let symbols = ["p", "b", "m", "t", "s", "k", "l", "j", "i", "a"];
+        let table: Vec<_> = symbols
+            .iter()
+            .map(|a| {
+                let row: Vec<_> = symbols
+                    .iter()
+                    .map(|b| format!("{:.2}", phone(a).distance(&phone(b))))
+                    .collect();
+
+                format!("{a}: {}", row.join(" "))
+            })
+            .collect();
+
+        assert_yaml_snapshot!(table);
+    }
+
+    #[test]
+    fn sequences_use_weighted_levenshtein() {
+        let distance = |a, b| sequence_distance(&phones(a), &phones(b));
+
+        assert_eq!(distance("pat", "pat"), 0.0);
+        assert_eq!(distance("", "pa"), 2.0 * INDEL_COST);
+        assert_eq!(distance("pat", "pa"), INDEL_COST);
+        assert_eq!(distance("pat", "bat"), phone("p").distance(&phone("b")));
+        assert!(distance("pat", "bat") < distance("pat", "sat"));
+        assert!(distance("pat", "sat") < distance("pat", "at"));
+        assert_eq!(distance("kasa", "kaza"), distance("kaza", "kasa"));
+    }
+}
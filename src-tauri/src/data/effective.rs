@@ -0,0 +1,347 @@
+//! What a language has once inheritance is resolved
+//!
+//! To look something up in a language, check the language first, then its
+//! ancestors in order, then theirs, a generation at a time.
+//! Everything found is tagged with where it came from.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    apply_phoneme_changes, Correspondence, EnvironmentRule, Language, LanguageGroup, Phoneme,
+    PhonemeChange, PhonemeSet, Phonotactics, Prosody, Protolanguage, SoundChanges,
+    SyllableTemplate, Tone,
+};
+
+/// Where an inherited item was found
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum Source {
+    Own,
+    /// The protolanguage with this name, after the language's sound changes
+    Ancestor(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+/// The phonemes and rules a language has, its own or inherited
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct EffectiveLanguage {
+    pub phonemes: HashMap<Uuid, Resolved<Phoneme>>,
+    pub tones: Vec<Resolved<Tone>>,
+    pub phoneme_sets: HashMap<String, Resolved<PhonemeSet>>,
+    pub syllable_template: Option<Resolved<SyllableTemplate>>,
+    pub prosody: Option<Resolved<Prosody>>,
+    pub environment_rules: Resolved<Vec<EnvironmentRule>>,
+    pub phonotactics: Resolved<Phonotactics>,
+    /// Inherited phonemes that split, merged or were lost
+    pub correspondences: Vec<Correspondence>,
+}
+
+/// The parts of a language or protolanguage that inheritance looks at
+pub(super) struct Heir<'a> {
+    name: &'a str,
+    ancestors: &'a [String],
+    phonemes: &'a HashMap<Uuid, Phoneme>,
+    tones: &'a [Tone],
+    phoneme_sets: &'a HashMap<String, PhonemeSet>,
+    syllable_template: &'a Option<SyllableTemplate>,
+    prosody: &'a Option<Prosody>,
+    environment_rules: &'a [EnvironmentRule],
+    phonotactics: &'a Phonotactics,
+    sound_changes: &'a str,
+    phoneme_changes: &'a [PhonemeChange],
+}
+
+impl<'a> From<&'a Language> for Heir<'a> {
+    fn from(value: &'a Language) -> Self {
+        Heir {
+            name: &value.name,
+            ancestors: &value.ancestors,
+            phonemes: &value.phonemes,
+            tones: &value.tones,
+            phoneme_sets: &value.phoneme_sets,
+            syllable_template: &value.syllable_template,
+            prosody: &value.prosody,
+            environment_rules: &value.environment_rules,
+            phonotactics: &value.phonotactics,
+            sound_changes: &value.sound_changes,
+            phoneme_changes: &value.phoneme_changes,
+        }
+    }
+}
+
+impl<'a> From<&'a Protolanguage> for Heir<'a> {
+    fn from(value: &'a Protolanguage) -> Self {
+        Heir {
+            name: &value.name,
+            ancestors: &value.ancestors,
+            phonemes: &value.phonemes,
+            tones: &value.tones,
+            phoneme_sets: &value.phoneme_sets,
+            syllable_template: &value.syllable_template,
+            prosody: &value.prosody,
+            environment_rules: &value.environment_rules,
+            phonotactics: &value.phonotactics,
+            sound_changes: "",
+            phoneme_changes: &[],
+        }
+    }
+}
+
+/// The heir's own value if it has one, else the nearest ancestor's
+fn first_set<'a, T: Clone + 'a>(
+    own: &T,
+    lineage: &[&'a Protolanguage],
+    field: impl Fn(&'a Protolanguage) -> &'a T,
+    is_set: impl Fn(&T) -> bool,
+) -> Option<Resolved<T>> {
+    if is_set(own) {
+        return Some(Resolved {
+            value: own.clone(),
+            source: Source::Own,
+        });
+    }
+
+    lineage
+        .iter()
+        .find(|anc| is_set(field(anc)))
+        .map(|anc| Resolved {
+            value: field(anc).clone(),
+            source: Source::Ancestor(anc.name.clone()),
+        })
+}
+
+impl LanguageGroup {
+    pub(super) fn resolve(&self, heir: Heir) -> EffectiveLanguage {
+        let lineage = self.lineage(heir.ancestors);
+        let changes = SoundChanges::parse(heir.sound_changes).unwrap_or_else(|e| {
+            log::warn!("skipping the sound changes of {}: {e}", heir.name);
+            SoundChanges::default()
+        });
+
+        // Nearer ancestors override farther ones, and the heir overrides them
+        // all
+        let mut inherited: HashMap<_, _> = lineage
+            .iter()
+            .rev()
+            .flat_map(|anc| {
+                anc.phonemes
+                    .iter()
+                    .map(move |(k, v)| (*k, (v.clone(), *anc)))
+            })
+            .collect();
+        let mut bare = inherited
+            .iter()
+            .map(|(k, (v, _))| (*k, v.clone()))
+            .collect();
+        let correspondences = apply_phoneme_changes(heir.phoneme_changes, &mut bare, |id| {
+            lineage
+                .iter()
+                .find(|anc| anc.phonemes.contains_key(&id))
+                .map(|anc| anc.name.clone())
+        });
+        inherited.retain(|k, _| bare.contains_key(k));
+        let phonemes = inherited
+            .into_iter()
+            .map(|(k, (mut v, anc))| {
+                changes.apply_to_phoneme(&mut v);
+                let source = Source::Ancestor(anc.name.clone());
+
+                (k, Resolved { value: v, source })
+            })
+            .chain(heir.phonemes.iter().map(|(k, v)| {
+                let resolved = Resolved {
+                    value: v.clone(),
+                    source: Source::Own,
+                };
+
+                (*k, resolved)
+            }))
+            .collect();
+
+        let mut tones: Vec<Resolved<Tone>> = heir
+            .tones
+            .iter()
+            .map(|tone| Resolved {
+                value: tone.clone(),
+                source: Source::Own,
+            })
+            .collect();
+        for anc in &lineage {
+            for tone in &anc.tones {
+                if !tones.iter().any(|t| &t.value == tone) {
+                    tones.push(Resolved {
+                        value: tone.clone(),
+                        source: Source::Ancestor(anc.name.clone()),
+                    });
+                }
+            }
+        }
+
+        let phoneme_sets = lineage
+            .iter()
+            .rev()
+            .flat_map(|anc| {
+                anc.phoneme_sets.iter().map(|(name, set)| {
+                    let source = Source::Ancestor(anc.name.clone());
+                    (name.clone(), (set, source))
+                })
+            })
+            .chain(
+                heir.phoneme_sets
+                    .iter()
+                    .map(|(name, set)| (name.clone(), (set, Source::Own))),
+            )
+            .map(|(name, (set, source))| {
+                let resolved = Resolved {
+                    value: set.clone(),
+                    source,
+                };
+
+                (name, resolved)
+            })
+            .collect();
+
+        let environment_rules = first_set(
+            &heir.environment_rules.to_vec(),
+            &lineage,
+            |anc| &anc.environment_rules,
+            |rules| !rules.is_empty(),
+        )
+        .unwrap_or_else(|| Resolved {
+            value: vec![],
+            source: Source::Own,
+        });
+        let phonotactics = first_set(
+            heir.phonotactics,
+            &lineage,
+            |anc| &anc.phonotactics,
+            |phonotactics| *phonotactics != Phonotactics::default(),
+        )
+        .unwrap_or_else(|| Resolved {
+            value: Phonotactics::default(),
+            source: Source::Own,
+        });
+
+        EffectiveLanguage {
+            phonemes,
+            tones,
+            phoneme_sets,
+            syllable_template: first_set(
+                heir.syllable_template,
+                &lineage,
+                |anc| &anc.syllable_template,
+                Option::is_some,
+            )
+            .map(|r| Resolved {
+                value: r.value.unwrap(),
+                source: r.source,
+            }),
+            prosody: first_set(heir.prosody, &lineage, |anc| &anc.prosody, Option::is_some).map(
+                |r| Resolved {
+                    value: r.value.unwrap(),
+                    source: r.source,
+                },
+            ),
+            environment_rules,
+            phonotactics,
+            correspondences,
+        }
+    }
+
+    /// The phonemes and rules of a language, with those of its ancestors
+    pub fn effective_language<S: AsRef<str>>(&self, name: S) -> Option<EffectiveLanguage> {
+        self.language(name).map(|lang| self.resolve(lang.into()))
+    }
+
+    /// The phonemes and rules of a protolanguage, with those of its ancestors
+    pub fn effective_protolanguage<S: AsRef<str>>(&self, name: S) -> Option<EffectiveLanguage> {
+        self.protolanguage(name)
+            .map(|proto| self.resolve(proto.into()))
+    }
+}
+
+impl EffectiveLanguage {
+    /// Replaces the phonemes and rules of a protolanguage with these,
+    /// dropping provenance
+    pub fn apply_to(self, proto: &mut Protolanguage) {
+        proto.phonemes = self
+            .phonemes
+            .into_iter()
+            .map(|(k, r)| (k, r.value))
+            .collect();
+        proto.tones = self.tones.into_iter().map(|r| r.value).collect();
+        proto.phoneme_sets = self
+            .phoneme_sets
+            .into_iter()
+            .map(|(k, r)| (k, r.value))
+            .collect();
+        proto.syllable_template = self.syllable_template.map(|r| r.value);
+        proto.prosody = self.prosody.map(|r| r.value);
+        proto.environment_rules = self.environment_rules.value;
+        proto.phonotactics = self.phonotactics.value;
+        proto.correspondences = self.correspondences;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Source;
+    use crate::data::{fixtures::phoneme, Language, LanguageGroup, Protolanguage, Tone, ToneLevel};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn resolves_with_provenance() {
+        let [p, t, k] = [0, 1, 2].map(Uuid::from_u128);
+        let group = LanguageGroup {
+            protolangs: vec![
+                Protolanguage {
+                    name: "1".to_string(),
+                    phonemes: HashMap::from([(p, phoneme("p")), (t, phoneme("t"))]),
+                    tones: vec![Tone::Level(ToneLevel::High)],
+                    ..Default::default()
+                },
+                Protolanguage {
+                    name: "1.1".to_string(),
+                    ancestors: vec!["1".to_string()],
+                    phonemes: HashMap::from([(t, phoneme("d"))]),
+                    ..Default::default()
+                },
+            ],
+            langs: vec![Language {
+                name: "1.1'".to_string(),
+                ancestors: vec!["1.1".to_string()],
+                phonemes: HashMap::from([(k, phoneme("k"))]),
+                sound_changes: "p > f".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let effective = group.effective_language("1.1'").unwrap();
+        let summary = |id| {
+            let resolved = &effective.phonemes[&id];
+            (resolved.value.ipa(), resolved.source.clone())
+        };
+
+        assert_eq!(
+            summary(p),
+            ("f".to_string(), Source::Ancestor("1".to_string()))
+        );
+        assert_eq!(
+            summary(t),
+            ("d".to_string(), Source::Ancestor("1.1".to_string()))
+        );
+        assert_eq!(summary(k), ("k".to_string(), Source::Own));
+        assert_eq!(effective.tones[0].source, Source::Ancestor("1".to_string()));
+        assert_eq!(effective.phonotactics.source, Source::Own);
+        assert!(group.effective_language("2").is_none());
+    }
+}
@@ -0,0 +1,401 @@
+//! Environment rules, which realize phonemes as phones from their context
+//!
+//! Rules are ordered: the first rule whose target and context match a
+//! phoneme chooses how it is realized, and later rules cannot change it.
+//! Insertions are the exception, since every matching insertion rule adds
+//! its phone. Contexts are matched against the phonemes, not against what
+//! earlier rules produced.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Phone, Phoneme, PhonemeSet};
+
+/// What a rule applies to
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+pub enum Target {
+    /// A phoneme, by orthography
+    Phoneme(String),
+    /// Every member of a phoneme set
+    Set(String),
+}
+
+/// A neighbour of the target
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+pub enum Context {
+    Phoneme(String),
+    Set(String),
+    /// The start or end of the word
+    Boundary,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum Outcome {
+    /// The target's production for a property of its phoneme set, which
+    /// may be null
+    Property(String),
+    /// One of the phoneme's allophones
+    Allophone(usize),
+    InsertBefore(Phone),
+    InsertAfter(Phone),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct EnvironmentRule {
+    #[serde(default)]
+    pub name: String,
+    pub target: Target,
+    /// Phonemes right before the target, in order
+    #[serde(default)]
+    pub before: Vec<Context>,
+    /// Phonemes right after the target, in order
+    #[serde(default)]
+    pub after: Vec<Context>,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnvironmentError {
+    #[error("there is no phoneme written {0}")]
+    UnknownPhoneme(String),
+    #[error("there is no phoneme set named {0}")]
+    UnknownSet(String),
+    #[error("the phoneme set {set} has no property {property}")]
+    UnknownProperty { set: String, property: String },
+    #[error("rule {0} picks a property, but only phoneme sets have properties")]
+    PropertyOfPhoneme(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum Change {
+    /// The phoneme is realized as the phone, or not at all
+    Realized(Option<Phone>),
+    Inserted(Phone),
+}
+
+/// A rule firing on the phoneme at `position`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RuleFiring {
+    /// Index of the rule
+    pub rule: usize,
+    pub name: String,
+    pub position: usize,
+    pub change: Change,
+}
+
+/// The phones of a phonemic sequence, and the rules that produced them
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Realization {
+    pub phones: Vec<Phone>,
+    pub ipa: String,
+    pub trace: Vec<RuleFiring>,
+}
+
+impl EnvironmentRule {
+    /// Checks that every phoneme set and property the rule uses exists
+    pub fn validate(&self, sets: &HashMap<String, PhonemeSet>) -> Result<(), EnvironmentError> {
+        let set = |name: &String| {
+            sets.get(name)
+                .ok_or_else(|| EnvironmentError::UnknownSet(name.clone()))
+        };
+
+        for context in self.before.iter().chain(&self.after) {
+            if let Context::Set(name) = context {
+                set(name)?;
+            }
+        }
+
+        match (&self.target, &self.outcome) {
+            (Target::Set(name), Outcome::Property(property)) => {
+                let target = set(name)?;
+                if !target.properties.contains(property)
+                    && !target.additional.contains_key(property)
+                {
+                    return Err(EnvironmentError::UnknownProperty {
+                        set: name.clone(),
+                        property: property.clone(),
+                    });
+                }
+            }
+            (Target::Phoneme(_), Outcome::Property(_)) => {
+                return Err(EnvironmentError::PropertyOfPhoneme(self.name.clone()))
+            }
+            (Target::Set(name), _) => {
+                set(name)?;
+            }
+            (Target::Phoneme(_), _) => {}
+        }
+
+        Ok(())
+    }
+
+    fn matches(&self, sets: &HashMap<String, PhonemeSet>, word: &[&str], position: usize) -> bool {
+        let is = |context: &Context, index: Option<&&str>| match (context, index) {
+            (Context::Boundary, None) => true,
+            (Context::Phoneme(ortho), Some(phoneme)) => ortho == phoneme,
+            (Context::Set(name), Some(phoneme)) => {
+                sets[name].members.iter().any(|member| member == phoneme)
+            }
+            _ => false,
+        };
+        // Only the word edges are boundaries, so contexts reaching further
+        // out never match
+        let at = |index: isize| match usize::try_from(index) {
+            Ok(index) if index <= word.len() => Some(word.get(index)),
+            Err(_) if index == -1 => Some(None),
+            _ => None,
+        };
+
+        let target = match &self.target {
+            Target::Phoneme(ortho) => ortho == word[position],
+            Target::Set(name) => is(&Context::Set(name.clone()), Some(&word[position])),
+        };
+        let position = position as isize;
+        let before = self.before.iter().rev().enumerate().all(|(i, context)| {
+            at(position - 1 - i as isize).is_some_and(|phoneme| is(context, phoneme))
+        });
+        let after = self.after.iter().enumerate().all(|(i, context)| {
+            at(position + 1 + i as isize).is_some_and(|phoneme| is(context, phoneme))
+        });
+
+        target && before && after
+    }
+}
+
+/// Realizes phonemes, by orthography, with ordered environment rules
+///
+/// Phonemes that no rule realizes keep their primary phone.
+pub fn realize<S: AsRef<str>>(
+    rules: &[EnvironmentRule],
+    phonemes: &HashMap<Uuid, Phoneme>,
+    sets: &HashMap<String, PhonemeSet>,
+    word: &[S],
+) -> Result<Realization, EnvironmentError> {
+    for rule in rules {
+        rule.validate(sets)?;
+    }
+
+    let word: Vec<&str> = word.iter().map(AsRef::as_ref).collect();
+    let resolved = word
+        .iter()
+        .map(|ortho| {
+            phonemes
+                .values()
+                .find(|phoneme| phoneme.ortho == *ortho)
+                .ok_or_else(|| EnvironmentError::UnknownPhoneme(ortho.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut realized: Vec<Option<Option<Phone>>> = vec![None; word.len()];
+    let mut before: Vec<Vec<Phone>> = vec![vec![]; word.len()];
+    let mut after: Vec<Vec<Phone>> = vec![vec![]; word.len()];
+    let mut trace = vec![];
+
+    for (index, rule) in rules.iter().enumerate() {
+        for position in 0..word.len() {
+            if !rule.matches(sets, &word, position) {
+                continue;
+            }
+
+            let phoneme = resolved[position];
+            let change = match &rule.outcome {
+                Outcome::InsertBefore(phone) => {
+                    before[position].push(phone.clone());
+                    Change::Inserted(phone.clone())
+                }
+                Outcome::InsertAfter(phone) => {
+                    after[position].push(phone.clone());
+                    Change::Inserted(phone.clone())
+                }
+                _ if realized[position].is_some() => continue,
+                Outcome::Allophone(allophone) => {
+                    let phone = phoneme.allo.get(*allophone).cloned().unwrap_or_else(|| {
+                        log::warn!(
+                            "phoneme {} has no allophone {allophone} for rule {}, using its primary phone",
+                            phoneme.ortho,
+                            rule.name
+                        );
+                        phoneme.primary.clone()
+                    });
+
+                    realized[position] = Some(Some(phone.clone()));
+                    Change::Realized(Some(phone))
+                }
+                Outcome::Property(property) => {
+                    let Target::Set(set) = &rule.target else {
+                        unreachable!("validated rules only pick properties of sets")
+                    };
+                    let phone = sets[set]
+                        .productions(phonemes, &phoneme.ortho)
+                        .and_then(|mut productions| productions.remove(property))
+                        .flatten();
+
+                    realized[position] = Some(phone.clone());
+                    Change::Realized(phone)
+                }
+            };
+
+            trace.push(RuleFiring {
+                rule: index,
+                name: rule.name.clone(),
+                position,
+                change,
+            });
+        }
+    }
+
+    let phones: Vec<Phone> = resolved
+        .iter()
+        .zip(realized)
+        .zip(before.into_iter().zip(after))
+        .flat_map(|((phoneme, realized), (before, after))| {
+            let phone = realized.unwrap_or_else(|| Some(phoneme.primary.clone()));
+
+            before.into_iter().chain(phone).chain(after)
+        })
+        .collect();
+
+    Ok(Realization {
+        ipa: phones.iter().map(Phone::to_string).collect(),
+        phones,
+        trace,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{realize, Context, EnvironmentError, EnvironmentRule, Outcome, Target};
+    use crate::data::{
+        fixtures::{self, phone},
+        CellValue, Phoneme, PhonemeSet,
+    };
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn phonemes() -> HashMap<Uuid, Phoneme> {
+        fixtures::phonemes(&[
+            ("p", "p", &["ɸ"]),
+            ("t", "t", &["θ"]),
+            ("n", "n", &[]),
+            ("a", "a", &[]),
+            ("i", "i", &[]),
+        ])
+    }
+
+    fn sets() -> HashMap<String, PhonemeSet> {
+        let members = |members: &[&str]| members.iter().map(|m| m.to_string()).collect();
+
+        HashMap::from([
+            (
+                "stops".to_string(),
+                PhonemeSet {
+                    members: members(&["p", "t"]),
+                    properties: vec!["lenited".to_string(), "final".to_string()],
+                    mapm: HashMap::from([
+                        (
+                            ("lenited".to_string(), "p".to_string()),
+                            CellValue::Allophone(0),
+                        ),
+                        (
+                            ("lenited".to_string(), "t".to_string()),
+                            CellValue::Allophone(0),
+                        ),
+                        (("final".to_string(), "t".to_string()), CellValue::Null),
+                    ]),
+                    additional: HashMap::new(),
+                },
+            ),
+            (
+                "vowels".to_string(),
+                PhonemeSet {
+                    members: members(&["a", "i"]),
+                    ..Default::default()
+                },
+            ),
+        ])
+    }
+
+    fn rules() -> Vec<EnvironmentRule> {
+        let vowel = || Context::Set("vowels".to_string());
+
+        vec![
+            EnvironmentRule {
+                name: "final deletion".to_string(),
+                target: Target::Set("stops".to_string()),
+                before: vec![],
+                after: vec![Context::Boundary],
+                outcome: Outcome::Property("final".to_string()),
+            },
+            EnvironmentRule {
+                name: "lenition".to_string(),
+                target: Target::Set("stops".to_string()),
+                before: vec![vowel()],
+                after: vec![vowel()],
+                outcome: Outcome::Property("lenited".to_string()),
+            },
+            EnvironmentRule {
+                name: "prothesis".to_string(),
+                target: Target::Phoneme("n".to_string()),
+                before: vec![Context::Boundary],
+                after: vec![],
+                outcome: Outcome::InsertBefore(phone("ə")),
+            },
+            // Never changes already realized phonemes
+            EnvironmentRule {
+                name: "spirants".to_string(),
+                target: Target::Phoneme("t".to_string()),
+                before: vec![],
+                after: vec![],
+                outcome: Outcome::Allophone(0),
+            },
+        ]
+    }
+
+    #[test]
+    fn realizes_by_environment() {
+        let realize = |word: &str| {
+            let word: Vec<_> = word.split(' ').collect();
+            realize(&rules(), &phonemes(), &sets(), &word).unwrap()
+        };
+
+        assert_eq!(realize("p a t a").ipa, "paθa");
+        assert_eq!(realize("n a p i t").ipa, "ənaɸi");
+        assert_eq!(realize("t a p").ipa, "θap");
+        assert_yaml_snapshot!(realize("n a p i t").trace);
+    }
+
+    #[test]
+    fn rejects_unknown_sets_and_properties() {
+        let rule = |target, outcome| EnvironmentRule {
+            name: "rule".to_string(),
+            target,
+            before: vec![],
+            after: vec![],
+            outcome,
+        };
+
+        assert!(matches!(
+            rule(Target::Set("nasals".to_string()), Outcome::Allophone(0)).validate(&sets()),
+            Err(EnvironmentError::UnknownSet(_))
+        ));
+        assert!(matches!(
+            rule(
+                Target::Set("stops".to_string()),
+                Outcome::Property("voiced".to_string())
+            )
+            .validate(&sets()),
+            Err(EnvironmentError::UnknownProperty { .. })
+        ));
+        assert!(matches!(
+            rule(
+                Target::Phoneme("p".to_string()),
+                Outcome::Property("lenited".to_string())
+            )
+            .validate(&sets()),
+            Err(EnvironmentError::PropertyOfPhoneme(_))
+        ));
+    }
+}
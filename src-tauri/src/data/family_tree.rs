@@ -0,0 +1,240 @@
+//! Ancestry between the protolanguages and languages of a group
+//!
+//! Protolanguages may descend from other protolanguages, so a family tree
+//! can be any number of levels deep. Inherited data is looked up in a
+//! language first, then in its ancestors in order, then in theirs, a
+//! generation at a time.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use uuid::Uuid;
+
+use super::{LanguageGroup, LanguageGroupError, Phoneme, PhonemeSet, Protolanguage};
+
+impl LanguageGroup {
+    /// Every protolanguage reachable from `ancestors`, nearest first
+    ///
+    /// The tree is walked a generation at a time, so parents come before
+    /// grandparents. Missing protolanguages are skipped, and each one
+    /// appears once.
+    pub fn lineage(&self, ancestors: &[String]) -> Vec<&Protolanguage> {
+        let mut lineage = vec![];
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<_> = ancestors.iter().collect();
+
+        while let Some(name) = queue.pop_front() {
+            let Some(proto) = self.protolanguage(name) else {
+                continue;
+            };
+            if !seen.insert(&proto.name) {
+                continue;
+            }
+
+            lineage.push(proto);
+            queue.extend(&proto.ancestors);
+        }
+
+        lineage
+    }
+
+    /// The phonemes of every protolanguage reachable from `ancestors`, the
+    /// nearest one winning for an id they share
+    pub fn inherited_phonemes(&self, ancestors: &[String]) -> HashMap<Uuid, Phoneme> {
+        self.lineage(ancestors)
+            .into_iter()
+            .rev()
+            .flat_map(|proto| proto.phonemes.iter().map(|(k, v)| (*k, v.clone())))
+            .collect()
+    }
+
+    /// The phoneme sets of every protolanguage reachable from `ancestors`,
+    /// the nearest one winning for a name they share
+    pub fn inherited_phoneme_sets(&self, ancestors: &[String]) -> HashMap<String, PhonemeSet> {
+        self.lineage(ancestors)
+            .into_iter()
+            .rev()
+            .flat_map(|proto| proto.phoneme_sets.clone())
+            .collect()
+    }
+
+    /// Checks that the ancestors exist
+    fn check_ancestors(&self, ancestors: &[String]) -> Result<(), LanguageGroupError> {
+        match ancestors
+            .iter()
+            .find(|name| self.protolanguage(name).is_none())
+        {
+            Some(name) => Err(LanguageGroupError::UnknownProtolanguage(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn set_language_ancestors(
+        &mut self,
+        name: &str,
+        ancestors: Vec<String>,
+    ) -> Result<(), LanguageGroupError> {
+        self.check_ancestors(&ancestors)?;
+
+        let lang = self
+            .language_mut(name)
+            .ok_or_else(|| LanguageGroupError::UnknownLanguage(name.to_string()))?;
+        lang.ancestors = ancestors;
+
+        Ok(())
+    }
+
+    /// Sets the ancestors of a protolanguage, unless it would descend from
+    /// itself
+    pub fn set_protolanguage_ancestors(
+        &mut self,
+        name: &str,
+        ancestors: Vec<String>,
+    ) -> Result<(), LanguageGroupError> {
+        self.check_ancestors(&ancestors)?;
+        if self.protolanguage(name).is_none() {
+            return Err(LanguageGroupError::UnknownProtolanguage(name.to_string()));
+        }
+        let cyclic = ancestors.iter().any(|anc| anc == name)
+            || self.lineage(&ancestors).iter().any(|p| p.name == name);
+        if cyclic {
+            return Err(LanguageGroupError::AncestryCycle(name.to_string()));
+        }
+
+        self.protolanguage_mut(name).unwrap().ancestors = ancestors;
+
+        Ok(())
+    }
+
+    /// Checks that every ancestor exists, and that no protolanguage
+    /// descends from itself
+    pub fn validate_family_tree(&self) -> Result<(), LanguageGroupError> {
+        for lang in &self.langs {
+            self.check_ancestors(&lang.ancestors)?;
+        }
+        for proto in &self.protolangs {
+            self.check_ancestors(&proto.ancestors)?;
+
+            if self
+                .lineage(&proto.ancestors)
+                .iter()
+                .any(|p| p.name == proto.name)
+            {
+                return Err(LanguageGroupError::AncestryCycle(proto.name.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{
+        fixtures::phoneme, validate_phoneme_changes, LanguageGroup, LanguageGroupError,
+        PhonemeChange, PhonemeSet, Protolanguage,
+    };
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn tree() -> LanguageGroup {
+        let proto = |name: &str, ancestors: &[&str]| Protolanguage {
+            name: name.to_string(),
+            ancestors: ancestors.iter().map(|anc| anc.to_string()).collect(),
+            ..Default::default()
+        };
+
+        LanguageGroup {
+            protolangs: vec![
+                proto("PIE", &[]),
+                proto("PGmc", &["PIE"]),
+                proto("PWGmc", &["PGmc"]),
+                proto("Substrate", &[]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lineage_walks_the_whole_chain() {
+        let group = tree();
+        let names: Vec<_> = group
+            .lineage(&["PWGmc".to_string(), "Substrate".to_string()])
+            .into_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["PWGmc", "Substrate", "PGmc", "PIE"]);
+    }
+
+    #[test]
+    fn lineage_puts_parents_before_grandparents() {
+        let group = tree();
+        let names: Vec<_> = group
+            .lineage(&["PGmc".to_string(), "Substrate".to_string()])
+            .into_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["PGmc", "Substrate", "PIE"]);
+    }
+
+    #[test]
+    fn rejects_cycles_and_missing_ancestors() {
+        let mut group = tree();
+
+        assert!(matches!(
+            group.set_protolanguage_ancestors("PIE", vec!["PWGmc".to_string()]),
+            Err(LanguageGroupError::AncestryCycle(name)) if name == "PIE"
+        ));
+        assert!(matches!(
+            group.set_protolanguage_ancestors("PIE", vec!["PIE".to_string()]),
+            Err(LanguageGroupError::AncestryCycle(_))
+        ));
+        assert!(matches!(
+            group.set_protolanguage_ancestors("PIE", vec!["Nostratic".to_string()]),
+            Err(LanguageGroupError::UnknownProtolanguage(name)) if name == "Nostratic"
+        ));
+        assert!(group.validate_family_tree().is_ok());
+
+        group.protolangs[0].ancestors = vec!["PGmc".to_string()];
+        assert!(matches!(
+            group.validate_family_tree(),
+            Err(LanguageGroupError::AncestryCycle(_))
+        ));
+    }
+
+    #[test]
+    fn inherits_phonemes_from_the_whole_chain() {
+        let [p, h] = [0, 1].map(Uuid::from_u128);
+        let mut group = tree();
+        group.protolangs[0].phonemes = HashMap::from([(p, phoneme("p")), (h, phoneme("h"))]);
+        group.protolangs[1].phonemes = HashMap::from([(p, phoneme("f"))]);
+
+        let inherited = group.inherited_phonemes(&["PWGmc".to_string()]);
+        assert_eq!(inherited[&p].ipa(), "f");
+        assert_eq!(inherited[&h].ipa(), "h");
+
+        let loss = [PhonemeChange::Loss { source: h }];
+        assert!(
+            validate_phoneme_changes(&loss, &inherited, &HashMap::new(), &HashMap::new()).is_ok()
+        );
+    }
+
+    #[test]
+    fn inherits_phoneme_sets_from_the_whole_chain() {
+        let set = |members: &[&str]| PhonemeSet {
+            members: members.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+        let mut group = tree();
+        group.protolangs[0].phoneme_sets = HashMap::from([
+            ("front".to_string(), set(&["i", "e"])),
+            ("stops".to_string(), set(&["p", "t"])),
+        ]);
+        group.protolangs[1].phoneme_sets = HashMap::from([("front".to_string(), set(&["i"]))]);
+
+        let sets = group.inherited_phoneme_sets(&["PWGmc".to_string()]);
+        assert_eq!(sets["front"], set(&["i"]));
+        assert_eq!(sets["stops"], set(&["p", "t"]));
+    }
+}
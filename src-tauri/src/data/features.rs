@@ -0,0 +1,718 @@
+use core::fmt;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Formatter,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    Backness, Height, Length, ObstruentAttachment, Phone, Phoneme, Place, SecondaryArticulation,
+};
+
+/// Distinctive features, roughly following Hayes (2009)
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Debug)]
+pub enum Feature {
+    Syllabic,
+    Consonantal,
+    Sonorant,
+    Continuant,
+    DelayedRelease,
+    Approximant,
+    Tap,
+    Trill,
+    Nasal,
+    Voice,
+    SpreadGlottis,
+    ConstrictedGlottis,
+    Click,
+    Labial,
+    Round,
+    Labiodental,
+    Coronal,
+    Anterior,
+    Distributed,
+    Strident,
+    Lateral,
+    Dorsal,
+    High,
+    Low,
+    Front,
+    Back,
+    Tense,
+    Long,
+}
+
+impl Feature {
+    /// Every feature, in matrix order
+    pub const ALL: [Feature; 28] = [
+        Feature::Syllabic,
+        Feature::Consonantal,
+        Feature::Sonorant,
+        Feature::Continuant,
+        Feature::DelayedRelease,
+        Feature::Approximant,
+        Feature::Tap,
+        Feature::Trill,
+        Feature::Nasal,
+        Feature::Voice,
+        Feature::SpreadGlottis,
+        Feature::ConstrictedGlottis,
+        Feature::Click,
+        Feature::Labial,
+        Feature::Round,
+        Feature::Labiodental,
+        Feature::Coronal,
+        Feature::Anterior,
+        Feature::Distributed,
+        Feature::Strident,
+        Feature::Lateral,
+        Feature::Dorsal,
+        Feature::High,
+        Feature::Low,
+        Feature::Front,
+        Feature::Back,
+        Feature::Tense,
+        Feature::Long,
+    ];
+
+    /// Reads a feature by name, with spaces or underscores between words
+    pub fn from_name(name: &str) -> Option<Feature> {
+        let name = name.trim().replace('_', " ");
+
+        Feature::ALL
+            .into_iter()
+            .find(|feature| feature.name().eq_ignore_ascii_case(&name))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Feature::Syllabic => "syllabic",
+            Feature::Consonantal => "consonantal",
+            Feature::Sonorant => "sonorant",
+            Feature::Continuant => "continuant",
+            Feature::DelayedRelease => "delayed release",
+            Feature::Approximant => "approximant",
+            Feature::Tap => "tap",
+            Feature::Trill => "trill",
+            Feature::Nasal => "nasal",
+            Feature::Voice => "voice",
+            Feature::SpreadGlottis => "spread glottis",
+            Feature::ConstrictedGlottis => "constricted glottis",
+            Feature::Click => "click",
+            Feature::Labial => "labial",
+            Feature::Round => "round",
+            Feature::Labiodental => "labiodental",
+            Feature::Coronal => "coronal",
+            Feature::Anterior => "anterior",
+            Feature::Distributed => "distributed",
+            Feature::Strident => "strident",
+            Feature::Lateral => "lateral",
+            Feature::Dorsal => "dorsal",
+            Feature::High => "high",
+            Feature::Low => "low",
+            Feature::Front => "front",
+            Feature::Back => "back",
+            Feature::Tense => "tense",
+            Feature::Long => "long",
+        }
+    }
+}
+
+/// A (possibly partial) matrix of feature values
+///
+/// A feature missing from the matrix is unspecified (0).
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(transparent)]
+pub struct FeatureMatrix(pub BTreeMap<Feature, bool>);
+
+impl FeatureMatrix {
+    pub fn get(&self, feature: Feature) -> Option<bool> {
+        self.0.get(&feature).copied()
+    }
+
+    pub fn set(&mut self, feature: Feature, value: bool) -> &mut Self {
+        self.0.insert(feature, value);
+        self
+    }
+
+    /// Whether every feature specified in `class` has the same value here
+    pub fn matches(&self, class: &FeatureMatrix) -> bool {
+        class
+            .0
+            .iter()
+            .all(|(feature, value)| self.get(*feature) == Some(*value))
+    }
+}
+
+impl<const N: usize> From<[(Feature, bool); N]> for FeatureMatrix {
+    fn from(value: [(Feature, bool); N]) -> Self {
+        Self(value.into_iter().collect())
+    }
+}
+
+impl fmt::Display for FeatureMatrix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let features: Vec<_> = self
+            .0
+            .iter()
+            .map(|(feature, value)| format!("{}{}", if *value { '+' } else { '-' }, feature.name()))
+            .collect();
+
+        write!(f, "[{}]", features.join(", "))
+    }
+}
+
+fn place_features(matrix: &mut FeatureMatrix, place: &Place) {
+    let labial = matches!(place, Place::Bilabial | Place::Labiodental);
+    let coronal = matches!(
+        place,
+        Place::Dental | Place::Alveolar | Place::Postalveolar | Place::Retroflex | Place::Palatal
+    );
+    let dorsal = matches!(place, Place::Palatal | Place::Velar | Place::Uvular);
+
+    matrix
+        .set(Feature::Labial, labial)
+        .set(Feature::Round, false)
+        .set(Feature::Coronal, coronal)
+        .set(Feature::Dorsal, dorsal);
+
+    if labial {
+        matrix.set(Feature::Labiodental, matches!(place, Place::Labiodental));
+    }
+
+    if coronal {
+        matrix
+            .set(
+                Feature::Anterior,
+                matches!(place, Place::Dental | Place::Alveolar),
+            )
+            .set(
+                Feature::Distributed,
+                matches!(place, Place::Dental | Place::Postalveolar | Place::Palatal),
+            );
+    }
+
+    if dorsal {
+        matrix
+            .set(
+                Feature::High,
+                matches!(place, Place::Palatal | Place::Velar),
+            )
+            .set(Feature::Low, false)
+            .set(Feature::Front, matches!(place, Place::Palatal))
+            .set(Feature::Back, matches!(place, Place::Velar | Place::Uvular));
+    }
+
+    if matches!(place, Place::Pharyngeal) {
+        matrix.set(Feature::Low, true);
+    }
+}
+
+fn secondary_features(matrix: &mut FeatureMatrix, secondary: &SecondaryArticulation) {
+    match secondary {
+        SecondaryArticulation::Labialized => {
+            matrix.set(Feature::Labial, true).set(Feature::Round, true);
+            matrix.0.entry(Feature::Labiodental).or_insert(false);
+        }
+        SecondaryArticulation::Palatalized => {
+            matrix
+                .set(Feature::Dorsal, true)
+                .set(Feature::High, true)
+                .set(Feature::Low, false)
+                .set(Feature::Front, true)
+                .set(Feature::Back, false);
+        }
+        SecondaryArticulation::Velarized => {
+            matrix
+                .set(Feature::Dorsal, true)
+                .set(Feature::High, true)
+                .set(Feature::Low, false)
+                .set(Feature::Front, false)
+                .set(Feature::Back, true);
+        }
+        SecondaryArticulation::Pharyngealized => {
+            matrix
+                .set(Feature::Dorsal, true)
+                .set(Feature::High, false)
+                .set(Feature::Low, true)
+                .set(Feature::Front, false)
+                .set(Feature::Back, true);
+        }
+    }
+}
+
+fn laryngeal_features(matrix: &mut FeatureMatrix, attachments: &HashSet<ObstruentAttachment>) {
+    let spread = attachments.contains(&ObstruentAttachment::Aspirated)
+        || attachments.contains(&ObstruentAttachment::Preaspirated)
+        || attachments.contains(&ObstruentAttachment::Breathy);
+    let constricted = attachments.contains(&ObstruentAttachment::Ejective)
+        || attachments.contains(&ObstruentAttachment::Creaky);
+
+    matrix
+        .set(Feature::SpreadGlottis, spread)
+        .set(Feature::ConstrictedGlottis, constricted);
+}
+
+/// Major class and manner features of a consonant
+struct Manner {
+    consonantal: bool,
+    sonorant: bool,
+    continuant: bool,
+    approximant: bool,
+    nasal: bool,
+    lateral: bool,
+}
+
+impl Manner {
+    const STOP: Manner = Manner {
+        consonantal: true,
+        sonorant: false,
+        continuant: false,
+        approximant: false,
+        nasal: false,
+        lateral: false,
+    };
+
+    fn apply(self, matrix: &mut FeatureMatrix, place: &Place) {
+        // Glottals are never consonantal
+        let consonantal = self.consonantal && !matches!(place, Place::Glottal);
+
+        matrix
+            .set(Feature::Syllabic, false)
+            .set(Feature::Consonantal, consonantal)
+            .set(Feature::Sonorant, self.sonorant)
+            .set(Feature::Continuant, self.continuant)
+            .set(Feature::Approximant, self.approximant)
+            .set(Feature::Tap, false)
+            .set(Feature::Trill, false)
+            .set(Feature::Nasal, self.nasal)
+            .set(Feature::Click, false)
+            .set(Feature::Lateral, self.lateral)
+            .set(Feature::SpreadGlottis, false)
+            .set(Feature::ConstrictedGlottis, false)
+            .set(Feature::Long, false);
+        place_features(matrix, place);
+    }
+}
+
+impl Phone {
+    /// The distinctive feature matrix of this phone
+    pub fn features(&self) -> FeatureMatrix {
+        let mut matrix = FeatureMatrix::default();
+
+        match self {
+            Phone::Plosive {
+                place,
+                voiced,
+                attachments,
+                ..
+            } => {
+                Manner::STOP.apply(&mut matrix, place);
+                matrix
+                    .set(Feature::DelayedRelease, false)
+                    .set(Feature::Voice, *voiced);
+                laryngeal_features(&mut matrix, attachments);
+
+                if matches!(place, Place::Glottal) {
+                    matrix.set(Feature::ConstrictedGlottis, true);
+                }
+            }
+            Phone::Affricative {
+                end_place,
+                voiced,
+                attachments,
+                ..
+            } => {
+                Manner::STOP.apply(&mut matrix, end_place);
+                matrix
+                    .set(Feature::DelayedRelease, true)
+                    .set(Feature::Voice, *voiced)
+                    .set(Feature::Strident, strident(end_place));
+                laryngeal_features(&mut matrix, attachments);
+            }
+            Phone::Fricative {
+                place,
+                voiced,
+                attachments,
+                ..
+            }
+            | Phone::LateralFricative {
+                place,
+                voiced,
+                attachments,
+                ..
+            } => {
+                Manner {
+                    continuant: true,
+                    lateral: matches!(self, Phone::LateralFricative { .. }),
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix
+                    .set(Feature::DelayedRelease, true)
+                    .set(Feature::Voice, *voiced)
+                    .set(Feature::Strident, strident(place));
+                laryngeal_features(&mut matrix, attachments);
+
+                if matches!(place, Place::Glottal) {
+                    matrix.set(Feature::SpreadGlottis, true);
+                }
+            }
+            Phone::Nasal { place, voiced, .. } => {
+                Manner {
+                    sonorant: true,
+                    nasal: true,
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix.set(Feature::Voice, *voiced);
+            }
+            Phone::Trill { place, voiced, .. } | Phone::Tap { place, voiced, .. } => {
+                Manner {
+                    sonorant: true,
+                    continuant: true,
+                    approximant: true,
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix
+                    .set(Feature::Voice, *voiced)
+                    .set(Feature::Trill, matches!(self, Phone::Trill { .. }))
+                    .set(Feature::Tap, matches!(self, Phone::Tap { .. }));
+            }
+            Phone::Approximant { place, voiced, .. } => {
+                // Only the coronal approximants are liquids, the rest are glides
+                let consonantal = matches!(
+                    place,
+                    Place::Dental | Place::Alveolar | Place::Postalveolar | Place::Retroflex
+                );
+
+                Manner {
+                    consonantal,
+                    sonorant: true,
+                    continuant: true,
+                    approximant: true,
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix.set(Feature::Voice, *voiced);
+            }
+            Phone::LateralApproximant { place, voiced, .. } => {
+                Manner {
+                    sonorant: true,
+                    continuant: true,
+                    approximant: true,
+                    lateral: true,
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix.set(Feature::Voice, *voiced);
+            }
+            Phone::Implosive { place, voiced, .. } => {
+                Manner::STOP.apply(&mut matrix, place);
+                matrix
+                    .set(Feature::DelayedRelease, false)
+                    .set(Feature::Voice, *voiced)
+                    .set(Feature::ConstrictedGlottis, true);
+            }
+            Phone::Click { place, lateral } => {
+                Manner {
+                    lateral: *lateral,
+                    ..Manner::STOP
+                }
+                .apply(&mut matrix, place);
+                matrix
+                    .set(Feature::DelayedRelease, false)
+                    .set(Feature::Voice, false)
+                    .set(Feature::Click, true);
+            }
+            Phone::Vowel {
+                height,
+                backness,
+                rounded,
+                nasalized,
+                ..
+            } => {
+                matrix
+                    .set(Feature::Syllabic, true)
+                    .set(Feature::Consonantal, false)
+                    .set(Feature::Sonorant, true)
+                    .set(Feature::Continuant, true)
+                    .set(Feature::Approximant, true)
+                    .set(Feature::Tap, false)
+                    .set(Feature::Trill, false)
+                    .set(Feature::Nasal, *nasalized)
+                    .set(Feature::Voice, true)
+                    .set(Feature::SpreadGlottis, false)
+                    .set(Feature::ConstrictedGlottis, false)
+                    .set(Feature::Click, false)
+                    .set(Feature::Labial, *rounded)
+                    .set(Feature::Round, *rounded)
+                    .set(Feature::Coronal, false)
+                    .set(Feature::Lateral, false)
+                    .set(Feature::Dorsal, true)
+                    .set(
+                        Feature::High,
+                        matches!(height, Height::Close | Height::NearClose),
+                    )
+                    .set(
+                        Feature::Low,
+                        matches!(height, Height::NearOpen | Height::Open),
+                    )
+                    .set(Feature::Front, matches!(backness, Backness::Front))
+                    .set(Feature::Back, matches!(backness, Backness::Back))
+                    .set(Feature::Long, false);
+
+                // Tenseness splits the heights that high and low cannot
+                match height {
+                    Height::Close | Height::CloseMid => {
+                        matrix.set(Feature::Tense, true);
+                    }
+                    Height::NearClose | Height::OpenMid | Height::NearOpen => {
+                        matrix.set(Feature::Tense, false);
+                    }
+                    Height::Mid | Height::Open => {}
+                }
+
+                if *rounded {
+                    matrix.set(Feature::Labiodental, false);
+                }
+            }
+        }
+
+        if self.syllabic() {
+            matrix.set(Feature::Syllabic, true);
+        }
+
+        if matches!(self.length(), Length::Long) {
+            matrix.set(Feature::Long, true);
+        }
+
+        for secondary in self.secondary().into_iter().flatten() {
+            secondary_features(&mut matrix, secondary);
+        }
+
+        matrix
+    }
+
+    /// Finds the phone with exactly the given features
+    ///
+    /// Candidates are the valid phones of the chart, their homorganic
+    /// affricatives, and their variants with one attachment, secondary
+    /// articulation, length or syllabicity, as well as nasalized and long
+    /// vowels. Nasal and lateral release and unreleased stops have no
+    /// features of their own.
+    pub fn from_features(matrix: &FeatureMatrix) -> Option<Phone> {
+        Self::feature_candidates().find(|phone| &phone.features() == matrix)
+    }
+
+    fn feature_candidates() -> impl Iterator<Item = Phone> {
+        let mut candidates: Vec<_> = Phone::chart()
+            .into_iter()
+            .filter(|phone| phone.validate().is_ok())
+            .collect();
+
+        candidates.extend(Place::ALL.into_iter().flat_map(|place| {
+            [false, true].map(|voiced| Phone::Affricative {
+                start_place: place,
+                end_place: place,
+                voiced,
+                attachments: HashSet::new(),
+                secondary: HashSet::new(),
+                length: Length::Short,
+            })
+        }));
+
+        let variants: Vec<_> = candidates
+            .iter()
+            .flat_map(Phone::feature_variants)
+            .collect();
+        candidates.extend(variants);
+
+        candidates
+            .into_iter()
+            .filter(|phone| phone.validate().is_ok())
+    }
+
+    /// Variants of the phone that differ in a feature the chart does not cover
+    fn feature_variants(&self) -> Vec<Phone> {
+        if let Phone::Vowel {
+            height,
+            backness,
+            rounded,
+            ..
+        } = self
+        {
+            return [
+                (true, Length::Short),
+                (false, Length::Long),
+                (true, Length::Long),
+            ]
+            .map(|(nasalized, length)| Phone::Vowel {
+                height: *height,
+                backness: *backness,
+                rounded: *rounded,
+                length,
+                nasalized,
+            })
+            .to_vec();
+        }
+
+        let variant = |change: &dyn Fn(&mut Phone) -> Option<()>| {
+            let mut phone = self.clone();
+            change(&mut phone).map(|_| phone)
+        };
+        let mut variants = vec![];
+
+        for attachment in [
+            ObstruentAttachment::Aspirated,
+            ObstruentAttachment::Ejective,
+            ObstruentAttachment::Breathy,
+            ObstruentAttachment::Creaky,
+        ] {
+            variants.extend(variant(&|phone| {
+                phone.attachments_mut()?.insert(attachment);
+                Some(())
+            }));
+        }
+
+        for secondary in [
+            SecondaryArticulation::Labialized,
+            SecondaryArticulation::Palatalized,
+            SecondaryArticulation::Velarized,
+            SecondaryArticulation::Pharyngealized,
+        ] {
+            variants.extend(variant(&|phone| {
+                phone.secondary_mut()?.insert(secondary);
+                Some(())
+            }));
+        }
+
+        variants.extend(variant(&|phone| {
+            *phone.length_mut()? = Length::Long;
+            Some(())
+        }));
+        variants.extend(variant(&|phone| {
+            *phone.syllabic_mut()? = true;
+            Some(())
+        }));
+
+        variants
+    }
+}
+
+fn strident(place: &Place) -> bool {
+    matches!(
+        place,
+        Place::Labiodental | Place::Alveolar | Place::Postalveolar | Place::Uvular
+    )
+}
+
+/// Phonemes whose primary phone belongs to the natural class
+pub fn natural_class<'a>(
+    phonemes: &'a HashMap<Uuid, Phoneme>,
+    class: &'a FeatureMatrix,
+) -> impl Iterator<Item = (&'a Uuid, &'a Phoneme)> {
+    phonemes
+        .iter()
+        .filter(move |(_, phoneme)| phoneme.primary.features().matches(class))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{natural_class, Feature, FeatureMatrix};
+    use crate::data::{fixtures, Backness, Height, Length, Phone, Place, SecondaryArticulation};
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashSet;
+
+    #[test]
+    fn chart_phones_round_trip_through_features() {
+        let phones: Vec<_> = Phone::chart()
+            .into_iter()
+            .filter(|phone| phone.validate().is_ok())
+            .collect();
+
+        for phone in &phones {
+            let matrix = phone.features();
+            let found = Phone::from_features(&matrix).unwrap();
+
+            // The alveolar and postalveolar clicks cannot be told apart
+            if !matches!(phone, Phone::Click { .. }) {
+                assert_eq!(&found, phone, "{matrix}");
+            }
+        }
+    }
+
+    #[test]
+    fn vowel_features() {
+        let vowel = Phone::Vowel {
+            height: Height::Close,
+            backness: Backness::Front,
+            rounded: true,
+            length: Length::Long,
+            nasalized: true,
+        };
+
+        assert_yaml_snapshot!(vowel.features());
+        assert_eq!(Phone::from_features(&vowel.features()), Some(vowel));
+    }
+
+    #[test]
+    fn secondary_articulations_add_features() {
+        let phone = Phone::Plosive {
+            place: Place::Alveolar,
+            voiced: false,
+            attachments: HashSet::new(),
+            secondary: [SecondaryArticulation::Labialized].into(),
+            length: Length::Long,
+        };
+        let matrix = phone.features();
+
+        assert_eq!(matrix.get(Feature::Round), Some(true));
+        assert_eq!(matrix.get(Feature::Coronal), Some(true));
+        assert_eq!(matrix.get(Feature::Long), Some(true));
+        assert_eq!(Phone::from_features(&matrix), None);
+
+        let phone = Phone::Nasal {
+            place: Place::Alveolar,
+            voiced: true,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: true,
+        };
+
+        assert_eq!(phone.features().get(Feature::Syllabic), Some(true));
+        assert_eq!(Phone::from_features(&phone.features()), Some(phone));
+    }
+
+    #[test]
+    fn natural_class_selects_matching_phonemes() {
+        let phonemes = fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("b", "b", &[]),
+            ("s", "s", &[]),
+            ("z", "z", &[]),
+            ("x", "x", &[]),
+            ("m", "m", &[]),
+        ]);
+
+        let class = FeatureMatrix::from([(Feature::Continuant, true), (Feature::Voice, false)]);
+        let mut voiceless_continuants: Vec<_> = natural_class(&phonemes, &class)
+            .map(|(_, phoneme)| phoneme.ortho.as_str())
+            .collect();
+        voiceless_continuants.sort();
+
+        assert_eq!(voiceless_continuants, ["s", "x"]);
+        assert_eq!(class.to_string(), "[+continuant, -voice]");
+
+        let class = FeatureMatrix::from([(Feature::Labial, true)]);
+        let mut labials: Vec<_> = natural_class(&phonemes, &class)
+            .map(|(_, phoneme)| phoneme.ortho.as_str())
+            .collect();
+        labials.sort();
+
+        assert_eq!(labials, ["b", "m", "p"]);
+    }
+}
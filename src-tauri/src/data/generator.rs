@@ -0,0 +1,431 @@
+//! Random word generation from phonotactics
+//!
+//! Words are built syllable by syllable from the shapes a syllable template
+//! allows, picking phonemes from each letter's phoneme set by a
+//! [`Distribution`]. Candidates that break the phonotactics, or that sound
+//! too much like an existing or already generated word, are thrown away.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    sequence_distance, CandidateWord, Phone, Phoneme, PhonemeSet, Phonotactics, PhonotacticsError,
+    SyllableTemplate, SyllableTemplateError,
+};
+
+/// How often each member of a phoneme set is picked
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug, Default)]
+pub enum Distribution {
+    #[default]
+    Uniform,
+    /// The member at rank `n`, counting from 1 in set order, has weight
+    /// `1 / n^exponent`
+    Zipf { exponent: f64 },
+    /// Weights by phoneme orthography, where missing phonemes weigh 1
+    Custom(HashMap<String, f64>),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct GeneratorSettings {
+    pub count: usize,
+    pub min_syllables: usize,
+    pub max_syllables: usize,
+    #[serde(default)]
+    pub distribution: Distribution,
+    /// Seed for reproducible words, or a random one if `None`
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Existing forms, as phoneme orthographies, to not generate again
+    #[serde(default)]
+    pub exclude: Vec<Vec<String>>,
+    /// Words closer than this to an excluded or generated word, by
+    /// [`sequence_distance`], are near-homophones and thrown away
+    #[serde(default)]
+    pub min_distance: f64,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct GeneratedWord {
+    pub ortho: String,
+    pub phonemes: Vec<String>,
+    /// The primary phones of the phonemes
+    pub ipa: String,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Generated {
+    /// The seed that reproduces these words
+    pub seed: u64,
+    /// Up to `count` words, fewer if not enough could be found
+    pub words: Vec<GeneratedWord>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GeneratorError {
+    #[error("there is no syllable template")]
+    NoTemplate,
+    #[error(transparent)]
+    Template(#[from] SyllableTemplateError),
+    #[error(transparent)]
+    Phonotactics(#[from] PhonotacticsError),
+    #[error("cannot generate between {min} and {max} syllables")]
+    SyllableRange { min: usize, max: usize },
+    #[error("every syllable shape uses a phoneme set without phonemes")]
+    EmptySets,
+}
+
+/// How many candidates are tried for every word asked for
+const ATTEMPTS_PER_WORD: usize = 100;
+
+/// Seeded SplitMix64, so the same seed always gives the same words
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `min..=max`
+    fn between(&mut self, min: usize, max: usize) -> usize {
+        min + (self.next() % (max - min + 1) as u64) as usize
+    }
+
+    /// An index picked by weight, which must sum above 0
+    fn weighted(&mut self, weights: &[f64]) -> usize {
+        let mut target = self.unit() * weights.iter().sum::<f64>();
+
+        for (i, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return i;
+            }
+            target -= weight;
+        }
+
+        weights.len() - 1
+    }
+}
+
+impl Distribution {
+    fn weights(&self, set: &PhonemeSet) -> Vec<f64> {
+        set.members
+            .iter()
+            .enumerate()
+            .map(|(rank, member)| match self {
+                Distribution::Uniform => 1.0,
+                Distribution::Zipf { exponent } => 1.0 / ((rank + 1) as f64).powf(*exponent),
+                Distribution::Custom(weights) => weights.get(member).copied().unwrap_or(1.0),
+            })
+            .map(|weight| weight.max(0.0))
+            .collect()
+    }
+}
+
+fn phones(phonemes: &HashMap<Uuid, Phoneme>, word: &[String]) -> Vec<Phone> {
+    word.iter()
+        .filter_map(|ortho| phonemes.values().find(|phoneme| phoneme.ortho == *ortho))
+        .map(|phoneme| phoneme.primary.clone())
+        .collect()
+}
+
+impl GeneratorSettings {
+    /// Generates words that satisfy the syllable template and phonotactics
+    pub fn generate(
+        &self,
+        phonemes: &HashMap<Uuid, Phoneme>,
+        sets: &HashMap<String, PhonemeSet>,
+        template: Option<&SyllableTemplate>,
+        phonotactics: &Phonotactics,
+    ) -> Result<Generated, GeneratorError> {
+        let template = template.ok_or(GeneratorError::NoTemplate)?;
+        template.validate(sets)?;
+        phonotactics.validate(sets)?;
+
+        if self.min_syllables == 0 || self.min_syllables > self.max_syllables {
+            return Err(GeneratorError::SyllableRange {
+                min: self.min_syllables,
+                max: self.max_syllables,
+            });
+        }
+
+        // Members and weights of each letter's set
+        let letters: HashMap<char, (&PhonemeSet, Vec<f64>)> = template
+            .sets
+            .iter()
+            .map(|(letter, name)| {
+                (
+                    *letter,
+                    (&sets[name], self.distribution.weights(&sets[name])),
+                )
+            })
+            .collect();
+        let shapes: Vec<Vec<char>> = template
+            .shapes()?
+            .into_iter()
+            .filter(|shape| {
+                shape
+                    .iter()
+                    .all(|letter| letters[letter].1.iter().any(|weight| *weight > 0.0))
+            })
+            .collect();
+        if shapes.is_empty() {
+            return Err(GeneratorError::EmptySets);
+        }
+
+        // Random seeds fit in 53 bits, so the frontend can hand them back
+        let seed = self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64 & ((1 << 53) - 1))
+        });
+        let mut rng = Rng(seed);
+
+        let mut known: Vec<Vec<Phone>> = self
+            .exclude
+            .iter()
+            .map(|word| phones(phonemes, word))
+            .collect();
+        let mut words = vec![];
+
+        for _ in 0..self.count * ATTEMPTS_PER_WORD {
+            if words.len() == self.count {
+                break;
+            }
+
+            let syllables = rng.between(self.min_syllables, self.max_syllables);
+            let mut word: Vec<String> = vec![];
+            for _ in 0..syllables {
+                for letter in &shapes[rng.between(0, shapes.len() - 1)] {
+                    let (set, weights) = &letters[letter];
+                    word.push(set.members[rng.weighted(weights)].clone());
+                }
+            }
+
+            if self.exclude.contains(&word) {
+                continue;
+            }
+
+            let validation = phonotactics.check(
+                phonemes,
+                sets,
+                Some(template),
+                &CandidateWord::Phonemes(word.clone()),
+            )?;
+            if !validation.violations.is_empty() {
+                continue;
+            }
+
+            // Also catches exact duplicates, which are at distance 0
+            let phones = phones(phonemes, &word);
+            if known
+                .iter()
+                .any(|other| sequence_distance(&phones, other) <= self.min_distance)
+            {
+                continue;
+            }
+
+            words.push(GeneratedWord {
+                ortho: word.concat(),
+                ipa: phones.iter().map(Phone::to_string).collect(),
+                phonemes: word,
+            });
+            known.push(phones);
+        }
+
+        Ok(Generated { seed, words })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Distribution, GeneratorError, GeneratorSettings};
+    use crate::data::{fixtures, Phoneme, PhonemeSet, Phonotactics, SyllableTemplate};
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn phonemes() -> HashMap<Uuid, Phoneme> {
+        fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("t", "t", &[]),
+            ("k", "k", &[]),
+            ("n", "n", &[]),
+            ("a", "a", &[]),
+            ("i", "i", &[]),
+            ("u", "u", &[]),
+        ])
+    }
+
+    fn sets() -> HashMap<String, PhonemeSet> {
+        let set = |members: &[&str]| PhonemeSet {
+            members: members.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+
+        HashMap::from([
+            ("c".to_string(), set(&["p", "t", "k", "n"])),
+            ("v".to_string(), set(&["a", "i", "u"])),
+        ])
+    }
+
+    fn template() -> SyllableTemplate {
+        SyllableTemplate {
+            pattern: "CV(C)".to_string(),
+            sets: HashMap::from([('C', "c".to_string()), ('V', "v".to_string())]),
+            nucleus: 'V',
+            principle: Default::default(),
+        }
+    }
+
+    fn settings() -> GeneratorSettings {
+        GeneratorSettings {
+            count: 8,
+            min_syllables: 1,
+            max_syllables: 3,
+            distribution: Distribution::Uniform,
+            seed: Some(42),
+            exclude: vec![],
+            min_distance: 0.0,
+        }
+    }
+
+    #[test]
+    fn generates_reproducibly() {
+        let generate = |settings: &GeneratorSettings| {
+            settings
+                .generate(
+                    &phonemes(),
+                    &sets(),
+                    Some(&template()),
+                    &Phonotactics::default(),
+                )
+                .unwrap()
+        };
+
+        let generated = generate(&settings());
+        assert_eq!(generated, generate(&settings()));
+        assert_eq!(generated.words.len(), 8);
+        for word in &generated.words {
+            let vowels = word
+                .phonemes
+                .iter()
+                .filter(|p| ["a", "i", "u"].contains(&p.as_str()));
+            assert!((1..=3).contains(&vowels.count()));
+        }
+        assert_yaml_snapshot!(generated);
+    }
+
+    #[test]
+    fn zipf_favours_earlier_members() {
+        let settings = GeneratorSettings {
+            count: 200,
+            min_syllables: 1,
+            max_syllables: 1,
+            distribution: Distribution::Zipf { exponent: 2.0 },
+            // Allows repeated words, so every pick counts
+            min_distance: -1.0,
+            ..settings()
+        };
+        let generated = settings
+            .generate(
+                &phonemes(),
+                &sets(),
+                Some(&template()),
+                &Phonotactics::default(),
+            )
+            .unwrap();
+
+        let onsets = |onset: &str| {
+            generated
+                .words
+                .iter()
+                .filter(|word| word.phonemes[0] == onset)
+                .count()
+        };
+        assert!(onsets("p") > onsets("t"));
+        assert!(onsets("t") > onsets("n"));
+    }
+
+    #[test]
+    fn excludes_existing_forms_and_near_homophones() {
+        // Only pa, pi and pu are possible
+        let sets = HashMap::from([
+            (
+                "c".to_string(),
+                PhonemeSet {
+                    members: vec!["p".to_string()],
+                    ..Default::default()
+                },
+            ),
+            ("v".to_string(), sets()["v"].clone()),
+        ]);
+        let template = SyllableTemplate {
+            pattern: "CV".to_string(),
+            ..template()
+        };
+        let only_p = GeneratorSettings {
+            count: 3,
+            max_syllables: 1,
+            exclude: vec![vec!["p".to_string(), "a".to_string()]],
+            ..settings()
+        };
+
+        let generated = only_p
+            .generate(
+                &phonemes(),
+                &sets,
+                Some(&template),
+                &Phonotactics::default(),
+            )
+            .unwrap();
+        let mut words: Vec<_> = generated
+            .words
+            .iter()
+            .map(|word| word.ortho.as_str())
+            .collect();
+        words.sort();
+        assert_eq!(words, ["pi", "pu"]);
+
+        let near = GeneratorSettings {
+            min_distance: 1.0,
+            ..only_p
+        };
+        assert!(near
+            .generate(
+                &phonemes(),
+                &sets,
+                Some(&template),
+                &Phonotactics::default()
+            )
+            .unwrap()
+            .words
+            .is_empty());
+
+        let empty = GeneratorSettings {
+            min_syllables: 2,
+            max_syllables: 1,
+            ..settings()
+        };
+        assert!(matches!(
+            empty.generate(
+                &phonemes(),
+                &sets,
+                Some(&template),
+                &Phonotactics::default()
+            ),
+            Err(GeneratorError::SyllableRange { .. })
+        ));
+    }
+}
@@ -0,0 +1,734 @@
+//! Table-driven IPA rendering of [`Phone`]s
+//!
+//! Base symbols are stored directly in IPA, indexed by [`Place`] (and by
+//! [`Height`], [`Backness`] and rounding for vowels). Cells without a dedicated
+//! symbol use a neighbouring symbol with a place diacritic. Impossible
+//! articulations still render as their nearest neighbour, so use
+//! [`Phone::validate`] to reject them.
+//!
+//! Diacritics follow the usual IPA order: those below (or above) the symbol
+//! first, then the superscripts of secondary articulations, then the release,
+//! and length last, e.g. `kʷʰː` or `n̥̩`.
+
+use core::fmt;
+use std::{collections::HashSet, fmt::Formatter, sync::OnceLock};
+
+use ipa_translate::branner_to_ipa;
+
+use super::{Backness, Height, Length, ObstruentAttachment, Phone, Place, SecondaryArticulation};
+
+mod notation;
+mod parse;
+
+pub use notation::*;
+pub use parse::*;
+
+/// (voiceless, voiced) symbols, indexed by [`Place`]
+type ObstruentTable = [(&'static str, &'static str); 11];
+/// Voiced symbols, indexed by [`Place`]
+type SonorantTable = [&'static str; 11];
+
+const PLOSIVES: ObstruentTable = [
+    ("p", "b"),
+    ("p̪", "b̪"),
+    ("t̪", "d̪"),
+    ("t", "d"),
+    ("t̠", "d̠"),
+    ("ʈ", "ɖ"),
+    ("c", "ɟ"),
+    ("k", "ɡ"),
+    ("q", "ɢ"),
+    ("ʡ", "ʡ̬"),
+    ("ʔ", "ʔ̬"),
+];
+
+const FRICATIVES: ObstruentTable = [
+    ("ɸ", "β"),
+    ("f", "v"),
+    ("θ", "ð"),
+    ("s", "z"),
+    ("ʃ", "ʒ"),
+    ("ʂ", "ʐ"),
+    ("ç", "ʝ"),
+    ("x", "ɣ"),
+    ("χ", "ʁ"),
+    ("ħ", "ʕ"),
+    ("h", "ɦ"),
+];
+
+const LATERAL_FRICATIVES: ObstruentTable = [
+    ("ɬ̪", "ɮ̪"),
+    ("ɬ̪", "ɮ̪"),
+    ("ɬ̪", "ɮ̪"),
+    ("ɬ", "ɮ"),
+    ("ɬ̠", "ɮ̠"),
+    ("ɭ̝̊", "ɭ̝"),
+    ("ʎ̝̊", "ʎ̝"),
+    ("ʟ̝̊", "ʟ̝"),
+    ("ʟ̠̝̊", "ʟ̠̝"),
+    ("ʟ̠̝̊", "ʟ̠̝"),
+    ("ʟ̠̝̊", "ʟ̠̝"),
+];
+
+const NASALS: SonorantTable = ["m", "ɱ", "n̪", "n", "n̠", "ɳ", "ɲ", "ŋ", "ɴ", "ɴ", "ɴ"];
+
+const TRILLS: SonorantTable = ["ʙ", "ʙ̪", "r̪", "r", "r̠", "ɽ͡r", "ʀ", "ʀ", "ʀ", "ʢ", "ʢ"];
+
+const TAPS: SonorantTable = ["ⱱ̟", "ⱱ", "ɾ̪", "ɾ", "ɾ̠", "ɽ", "ɟ̆", "ɢ̆", "ɢ̆", "ʡ̆", "ʡ̆"];
+
+const APPROXIMANTS: SonorantTable = ["β̞", "ʋ", "ð̞", "ɹ", "ɹ̠", "ɻ", "j", "ɰ", "ʁ̞", "ʕ̞", "ʔ̞"];
+
+const LATERAL_APPROXIMANTS: SonorantTable = ["l̪", "l̪", "l̪", "l", "l̠", "ɭ", "ʎ", "ʟ", "ʟ̠", "ʟ̠", "ʟ̠"];
+
+const IMPLOSIVES: SonorantTable = ["ɓ", "ɓ̪", "ɗ̪", "ɗ", "ɗ̠", "ᶑ", "ʄ", "ɠ", "ʛ", "ʛ", "ʛ"];
+
+/// (unrounded, rounded) symbols, indexed by [`Height`] then [`Backness`]
+const VOWELS: [[(&str, &str); 3]; 7] = [
+    [("i", "y"), ("ɨ", "ʉ"), ("ɯ", "u")],
+    [("ɪ", "ʏ"), ("ɪ̈", "ʊ̈"), ("ɯ̽", "ʊ")],
+    [("e", "ø"), ("ɘ", "ɵ"), ("ɤ", "o")],
+    [("e̞", "ø̞"), ("ə", "ə̹"), ("ɤ̞", "o̞")],
+    [("ɛ", "œ"), ("ɜ", "ɞ"), ("ʌ", "ɔ")],
+    [("æ", "œ̞"), ("ɐ", "ɐ̹"), ("ɑ̝", "ɒ̝")],
+    [("a", "ɶ"), ("ä", "ɒ̈"), ("ɑ", "ɒ")],
+];
+
+/// Symbols with a descender take the voiceless diacritic above
+const DESCENDERS: &str = "ɱɳɲŋɭʎɻɽjɰʄɠɟ";
+
+/// Diacritics that are spelled in Branner, converted once on first use
+struct Diacritics {
+    aspirated: String,
+    breathy: String,
+    creaky: String,
+    ejective: String,
+    tie: String,
+}
+
+impl Diacritics {
+    fn get() -> &'static Diacritics {
+        static DIACRITICS: OnceLock<Diacritics> = OnceLock::new();
+
+        DIACRITICS.get_or_init(|| {
+            let convert = |branner: &str| branner_to_ipa(branner).replace('\u{200b}', "");
+
+            Diacritics {
+                aspirated: convert("h^"),
+                breathy: convert(r#"h")"#),
+                creaky: convert("~"),
+                ejective: convert("`"),
+                tie: convert("))"),
+            }
+        })
+    }
+}
+
+fn obstruent_base(
+    table: &ObstruentTable,
+    place: &Place,
+    voiced: bool,
+    attachments: &HashSet<ObstruentAttachment>,
+) -> &'static str {
+    let (voiceless, voiced_symbol) = table[*place as usize];
+
+    if voiced && !attachments.contains(&ObstruentAttachment::Ejective) {
+        voiced_symbol
+    } else {
+        voiceless
+    }
+}
+
+fn phonation(phone: &mut String, attachments: &HashSet<ObstruentAttachment>) {
+    let diacritics = Diacritics::get();
+
+    if attachments.contains(&ObstruentAttachment::Breathy) {
+        phone.push_str(&diacritics.breathy);
+    }
+
+    if attachments.contains(&ObstruentAttachment::Creaky) {
+        phone.push_str(&diacritics.creaky);
+    }
+}
+
+fn secondary_articulations(phone: &mut String, secondary: &HashSet<SecondaryArticulation>) {
+    for (articulation, symbol) in [
+        (SecondaryArticulation::Labialized, 'ʷ'),
+        (SecondaryArticulation::Palatalized, 'ʲ'),
+        (SecondaryArticulation::Velarized, 'ˠ'),
+        (SecondaryArticulation::Pharyngealized, 'ˤ'),
+    ] {
+        if secondary.contains(&articulation) {
+            phone.push(symbol);
+        }
+    }
+}
+
+fn release(phone: String, attachments: &HashSet<ObstruentAttachment>) -> String {
+    let diacritics = Diacritics::get();
+    let mut phone = phone;
+
+    if attachments.contains(&ObstruentAttachment::Ejective) {
+        phone.push_str(&diacritics.ejective);
+    }
+
+    for (attachment, symbol) in [
+        (ObstruentAttachment::NasalRelease, 'ⁿ'),
+        (ObstruentAttachment::LateralRelease, 'ˡ'),
+        (ObstruentAttachment::Unreleased, '\u{31a}'),
+    ] {
+        if attachments.contains(&attachment) {
+            phone.push(symbol);
+        }
+    }
+
+    if attachments.contains(&ObstruentAttachment::Preaspirated) {
+        diacritics.aspirated.clone() + &phone
+    } else if attachments.contains(&ObstruentAttachment::Aspirated) {
+        phone + &diacritics.aspirated
+    } else {
+        phone
+    }
+}
+
+fn obstruent(
+    table: &ObstruentTable,
+    place: &Place,
+    voiced: bool,
+    attachments: &HashSet<ObstruentAttachment>,
+    secondary: &HashSet<SecondaryArticulation>,
+) -> String {
+    let mut phone = obstruent_base(table, place, voiced, attachments).to_string();
+    phonation(&mut phone, attachments);
+    secondary_articulations(&mut phone, secondary);
+    release(phone, attachments)
+}
+
+fn sonorant(
+    table: &SonorantTable,
+    place: &Place,
+    voiced: bool,
+    is_syllabic: bool,
+    secondary: &HashSet<SecondaryArticulation>,
+) -> String {
+    let symbol = table[*place as usize];
+    let descender = matches!(symbol.chars().next(), Some(c) if DESCENDERS.contains(c));
+    let mut phone = symbol.to_string();
+
+    if !voiced {
+        phone.push(if descender { '\u{30a}' } else { '\u{325}' });
+    }
+
+    if is_syllabic {
+        phone.push(if descender { '\u{30d}' } else { '\u{329}' });
+    }
+
+    secondary_articulations(&mut phone, secondary);
+    phone
+}
+
+/// Invalid clicks render as the generic (post)alveolar click
+fn click(place: &Place, lateral: bool) -> &'static str {
+    match (place, lateral) {
+        (Place::Bilabial, false) => "ʘ",
+        (Place::Dental, false) => "ǀ",
+        (Place::Palatal, false) => "ǂ",
+        (Place::Alveolar, true) => "ǁ",
+        _ => "ǃ",
+    }
+}
+
+fn vowel(height: &Height, backness: &Backness, rounded: bool, nasalized: bool) -> String {
+    let (unrounded, rounded_symbol) = VOWELS[*height as usize][*backness as usize];
+    let mut phone = if rounded { rounded_symbol } else { unrounded }.to_string();
+
+    if nasalized {
+        phone.push('\u{303}');
+    }
+
+    phone
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mark = match self {
+            Length::ExtraShort => "\u{306}",
+            Length::Short => "",
+            Length::HalfLong => "ˑ",
+            Length::Long => "ː",
+        };
+
+        write!(f, "{mark}")
+    }
+}
+
+impl fmt::Display for Phone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let phone = match self {
+            Phone::Plosive {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                ..
+            } => obstruent(&PLOSIVES, place, *voiced, attachments, secondary),
+            Phone::Affricative {
+                start_place,
+                end_place,
+                voiced,
+                attachments,
+                secondary,
+                ..
+            } => {
+                let mut start_phone =
+                    obstruent_base(&PLOSIVES, start_place, *voiced, attachments).to_string();
+                let mut end_phone =
+                    obstruent_base(&FRICATIVES, end_place, *voiced, attachments).to_string();
+
+                phonation(&mut start_phone, attachments);
+                phonation(&mut end_phone, attachments);
+                secondary_articulations(&mut end_phone, secondary);
+
+                release(
+                    start_phone + &Diacritics::get().tie + &end_phone,
+                    attachments,
+                )
+            }
+            Phone::Fricative {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                ..
+            } => obstruent(&FRICATIVES, place, *voiced, attachments, secondary),
+            Phone::LateralFricative {
+                place,
+                voiced,
+                attachments,
+                secondary,
+                ..
+            } => obstruent(&LATERAL_FRICATIVES, place, *voiced, attachments, secondary),
+            Phone::Nasal {
+                place,
+                voiced,
+                secondary,
+                syllabic,
+                ..
+            } => sonorant(&NASALS, place, *voiced, *syllabic, secondary),
+            Phone::Trill {
+                place,
+                voiced,
+                secondary,
+                syllabic,
+                ..
+            } => sonorant(&TRILLS, place, *voiced, *syllabic, secondary),
+            Phone::Tap {
+                place,
+                voiced,
+                secondary,
+                ..
+            } => sonorant(&TAPS, place, *voiced, false, secondary),
+            Phone::Approximant {
+                place,
+                voiced,
+                secondary,
+                syllabic,
+                ..
+            } => sonorant(&APPROXIMANTS, place, *voiced, *syllabic, secondary),
+            Phone::LateralApproximant {
+                place,
+                voiced,
+                secondary,
+                syllabic,
+                ..
+            } => sonorant(&LATERAL_APPROXIMANTS, place, *voiced, *syllabic, secondary),
+            Phone::Implosive {
+                place,
+                voiced,
+                secondary,
+                ..
+            } => sonorant(&IMPLOSIVES, place, *voiced, false, secondary),
+            Phone::Click { place, lateral } => click(place, *lateral).to_string(),
+            Phone::Vowel {
+                height,
+                backness,
+                rounded,
+                nasalized,
+                ..
+            } => vowel(height, backness, *rounded, *nasalized),
+        };
+
+        write!(f, "{}{}", phone, self.length())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data::{
+        Backness, Height, Length, ObstruentAttachment, Phone, Place, SecondaryArticulation,
+    };
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashSet;
+
+    const ATTACHMENTS: [ObstruentAttachment; 5] = [
+        ObstruentAttachment::Ejective,
+        ObstruentAttachment::Preaspirated,
+        ObstruentAttachment::Aspirated,
+        ObstruentAttachment::Breathy,
+        ObstruentAttachment::Creaky,
+    ];
+
+    /// Renders the voiceless and voiced phone at every place
+    fn by_place(phone: impl Fn(Place, bool) -> Phone) -> Vec<String> {
+        Place::ALL
+            .iter()
+            .map(|place| {
+                format!(
+                    "{place:?}: {} {}",
+                    phone(*place, false),
+                    phone(*place, true)
+                )
+            })
+            .collect()
+    }
+
+    /// Renders the phone with every subset of attachments
+    fn by_attachments(phone: impl Fn(HashSet<ObstruentAttachment>) -> Phone) -> Vec<String> {
+        (0..1 << ATTACHMENTS.len())
+            .map(|mask: usize| {
+                let attachments: Vec<_> = ATTACHMENTS
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, attachment)| *attachment)
+                    .collect();
+                let rendered = phone(attachments.iter().copied().collect());
+
+                format!("{attachments:?}: {rendered}")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plosives_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Plosive {
+            place,
+            voiced,
+            attachments: HashSet::new(),
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn plosive_attachments_render() {
+        assert_yaml_snapshot!(by_attachments(|attachments| Phone::Plosive {
+            place: Place::Alveolar,
+            voiced: true,
+            attachments,
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn affricatives_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Affricative {
+            start_place: place,
+            end_place: place,
+            voiced,
+            attachments: HashSet::new(),
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn affricative_attachments_render() {
+        assert_yaml_snapshot!(by_attachments(|attachments| Phone::Affricative {
+            start_place: Place::Alveolar,
+            end_place: Place::Postalveolar,
+            voiced: false,
+            attachments,
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn fricatives_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Fricative {
+            place,
+            voiced,
+            attachments: HashSet::new(),
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn lateral_fricatives_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::LateralFricative {
+            place,
+            voiced,
+            attachments: HashSet::new(),
+            secondary: HashSet::new(),
+            length: Length::Short,
+        }));
+    }
+
+    #[test]
+    fn nasals_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Nasal {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: false
+        }));
+    }
+
+    #[test]
+    fn trills_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Trill {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: false
+        }));
+    }
+
+    #[test]
+    fn taps_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Tap {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short
+        }));
+    }
+
+    #[test]
+    fn approximants_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Approximant {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: false,
+        }));
+    }
+
+    #[test]
+    fn lateral_approximants_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::LateralApproximant {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: false,
+        }));
+    }
+
+    #[test]
+    fn implosives_render() {
+        assert_yaml_snapshot!(by_place(|place, voiced| Phone::Implosive {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short
+        }));
+    }
+
+    #[test]
+    fn clicks_render() {
+        assert_yaml_snapshot!(by_place(|place, lateral| Phone::Click { place, lateral }));
+    }
+
+    #[test]
+    fn vowels_render() {
+        let rendered: Vec<_> = Height::ALL
+            .iter()
+            .flat_map(|height| Backness::ALL.iter().map(move |backness| (height, backness)))
+            .map(|(height, backness)| {
+                let vowel = |rounded| Phone::Vowel {
+                    height: *height,
+                    backness: *backness,
+                    rounded,
+                    length: Length::Short,
+                    nasalized: false,
+                };
+
+                format!("{height:?} {backness:?}: {} {}", vowel(false), vowel(true))
+            })
+            .collect();
+
+        assert_yaml_snapshot!(rendered);
+    }
+
+    #[test]
+    fn consonants_display_as_ipa() {
+        let fricative = |place, voiced| Phone::Fricative {
+            place,
+            voiced,
+            attachments: HashSet::new(),
+            secondary: HashSet::new(),
+            length: Length::Short,
+        };
+
+        assert_eq!(fricative(Place::Dental, false).to_string(), "θ");
+        assert_eq!(fricative(Place::Uvular, true).to_string(), "ʁ");
+        assert_eq!(
+            Phone::Nasal {
+                place: Place::Velar,
+                voiced: true,
+                secondary: HashSet::new(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .to_string(),
+            "ŋ"
+        );
+        assert_eq!(
+            Phone::Nasal {
+                place: Place::Velar,
+                voiced: false,
+                secondary: HashSet::new(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .to_string(),
+            "ŋ\u{30a}"
+        );
+        assert_eq!(
+            Phone::Approximant {
+                place: Place::Alveolar,
+                voiced: false,
+                secondary: HashSet::new(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .to_string(),
+            "ɹ\u{325}"
+        );
+        assert_eq!(
+            Phone::Click {
+                place: Place::Alveolar,
+                lateral: true
+            }
+            .to_string(),
+            "ǁ"
+        );
+        assert_eq!(
+            Phone::Implosive {
+                place: Place::Velar,
+                voiced: true,
+                secondary: HashSet::new(),
+                length: Length::Short,
+            }
+            .to_string(),
+            "ɠ"
+        );
+    }
+
+    #[test]
+    fn vowels_display_as_ipa() {
+        let vowel = |height, backness, rounded| Phone::Vowel {
+            height,
+            backness,
+            rounded,
+            length: Length::Short,
+            nasalized: false,
+        };
+
+        assert_eq!(
+            vowel(Height::Close, Backness::Front, false).to_string(),
+            "i"
+        );
+        assert_eq!(vowel(Height::Close, Backness::Back, true).to_string(), "u");
+        assert_eq!(
+            vowel(Height::Mid, Backness::Central, false).to_string(),
+            "ə"
+        );
+        assert_eq!(
+            vowel(Height::OpenMid, Backness::Back, true).to_string(),
+            "ɔ"
+        );
+        assert_eq!(vowel(Height::Open, Backness::Front, true).to_string(), "ɶ");
+    }
+
+    #[test]
+    fn vowel_nasalization_precedes_length() {
+        let phone = Phone::Vowel {
+            height: Height::Open,
+            backness: Backness::Front,
+            rounded: false,
+            length: Length::Long,
+            nasalized: true,
+        };
+
+        assert_eq!(phone.to_string(), "a\u{303}ː");
+    }
+
+    #[test]
+    fn consonant_diacritics_follow_ipa_order() {
+        let plosive = |attachments: &[ObstruentAttachment], secondary: &[SecondaryArticulation]| {
+            Phone::Plosive {
+                place: Place::Velar,
+                voiced: false,
+                attachments: attachments.iter().copied().collect(),
+                secondary: secondary.iter().copied().collect(),
+                length: Length::Long,
+            }
+        };
+        let nasal = |place, voiced| Phone::Nasal {
+            place,
+            voiced,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: true,
+        };
+
+        assert_eq!(
+            plosive(
+                &[ObstruentAttachment::Aspirated],
+                &[SecondaryArticulation::Labialized]
+            )
+            .to_string(),
+            "kʷʰː"
+        );
+        assert_eq!(
+            plosive(
+                &[ObstruentAttachment::Ejective],
+                &[
+                    SecondaryArticulation::Labialized,
+                    SecondaryArticulation::Palatalized
+                ]
+            )
+            .to_string(),
+            "kʷʲʼː"
+        );
+        assert_eq!(
+            plosive(&[ObstruentAttachment::Unreleased], &[]).to_string(),
+            "k\u{31a}ː"
+        );
+        assert_eq!(
+            plosive(&[ObstruentAttachment::NasalRelease], &[]).to_string(),
+            "kⁿː"
+        );
+        assert_eq!(nasal(Place::Alveolar, false).to_string(), "n\u{325}\u{329}");
+        assert_eq!(nasal(Place::Velar, true).to_string(), "ŋ\u{30d}");
+        assert_eq!(
+            Phone::Affricative {
+                start_place: Place::Alveolar,
+                end_place: Place::Alveolar,
+                voiced: false,
+                attachments: [ObstruentAttachment::Aspirated].into(),
+                secondary: [SecondaryArticulation::Pharyngealized].into(),
+                length: Length::Short,
+            }
+            .to_string(),
+            "t͡sˤʰ"
+        );
+    }
+}
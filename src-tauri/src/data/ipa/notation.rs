@@ -0,0 +1,547 @@
+//! Conversion between IPA and the ASCII notations X-SAMPA and Kirshenbaum
+//!
+//! Both directions use the same table of (IPA, notation) pairs, matching the
+//! longest symbol first. When several IPA symbols share a spelling, the first
+//! pair in the table is the one read back. Characters without a mapping are
+//! kept as they are and reported, so nothing is lost silently.
+
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Phone, PhoneError};
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Notation {
+    XSampa,
+    Kirshenbaum,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct Transliteration {
+    pub output: String,
+    pub unmapped: Vec<UnmappedSymbol>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnmappedSymbol {
+    /// Character (not byte) offset into the input
+    pub position: usize,
+    pub symbol: char,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotationError {
+    #[error("{symbol} cannot be written in {notation:?}")]
+    Unmappable { notation: Notation, symbol: char },
+    #[error(transparent)]
+    Phone(#[from] PhoneError),
+}
+
+impl Serialize for NotationError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+/// (IPA, X-SAMPA) pairs
+const X_SAMPA: &[(&str, &str)] = &[
+    // Symbols shared with ASCII
+    ("a", "a"),
+    ("b", "b"),
+    ("c", "c"),
+    ("d", "d"),
+    ("e", "e"),
+    ("f", "f"),
+    ("ɡ", "g"),
+    ("g", "g"),
+    ("h", "h"),
+    ("i", "i"),
+    ("j", "j"),
+    ("k", "k"),
+    ("l", "l"),
+    ("m", "m"),
+    ("n", "n"),
+    ("o", "o"),
+    ("p", "p"),
+    ("q", "q"),
+    ("r", "r"),
+    ("s", "s"),
+    ("t", "t"),
+    ("u", "u"),
+    ("v", "v"),
+    ("w", "w"),
+    ("x", "x"),
+    ("y", "y"),
+    ("z", "z"),
+    // Consonants
+    ("β", "B"),
+    ("ʙ", "B\\"),
+    ("ɓ", "b_<"),
+    ("ç", "C"),
+    ("ɕ", "s\\"),
+    ("ð", "D"),
+    ("ɗ", "d_<"),
+    ("ᶑ", "d`_<"),
+    ("ɖ", "d`"),
+    ("ɟ", "J\\"),
+    ("ʄ", "J\\_<"),
+    ("ɠ", "g_<"),
+    ("ɢ", "G\\"),
+    ("ʛ", "G\\_<"),
+    ("ɣ", "G"),
+    ("ɦ", "h\\"),
+    ("ħ", "X\\"),
+    ("ɧ", "x\\"),
+    ("ʜ", "H\\"),
+    ("ɥ", "H"),
+    ("ʝ", "j\\"),
+    ("ɭ", "l`"),
+    ("ɬ", "K"),
+    ("ɮ", "K\\"),
+    ("ɺ", "l\\"),
+    ("ɫ", "5"),
+    ("ʎ", "L"),
+    ("ʟ", "L\\"),
+    ("ɱ", "F"),
+    ("ɲ", "J"),
+    ("ŋ", "N"),
+    ("ɳ", "n`"),
+    ("ɴ", "N\\"),
+    ("ɸ", "p\\"),
+    ("ɹ", "r\\"),
+    ("ɻ", "r\\`"),
+    ("ɾ", "4"),
+    ("ɽ", "r`"),
+    ("ʀ", "R\\"),
+    ("ʁ", "R"),
+    ("ʂ", "s`"),
+    ("ʃ", "S"),
+    ("ʈ", "t`"),
+    ("θ", "T"),
+    ("ʋ", "P"),
+    ("ʋ", "v\\"),
+    ("ɰ", "M\\"),
+    ("ʍ", "W"),
+    ("χ", "X"),
+    ("ʐ", "z`"),
+    ("ʑ", "z\\"),
+    ("ʒ", "Z"),
+    ("ʔ", "?"),
+    ("ʕ", "?\\"),
+    ("ʡ", ">\\"),
+    ("ʢ", "<\\"),
+    ("ʘ", "O\\"),
+    ("ǀ", "|\\"),
+    ("ǃ", "!\\"),
+    ("ǂ", "=\\"),
+    ("ǁ", "|\\|\\"),
+    // Vowels
+    ("ɑ", "A"),
+    ("ɐ", "6"),
+    ("æ", "{"),
+    ("ɶ", "&"),
+    ("ɒ", "Q"),
+    ("ʌ", "V"),
+    ("ɔ", "O"),
+    ("ə", "@"),
+    ("ɘ", "@\\"),
+    ("ɚ", "@`"),
+    ("ɛ", "E"),
+    ("ɜ", "3"),
+    ("ɞ", "3\\"),
+    ("ɤ", "7"),
+    ("ɪ", "I"),
+    ("ɨ", "1"),
+    ("ʉ", "}"),
+    ("ɯ", "M"),
+    ("ʊ", "U"),
+    ("ʏ", "Y"),
+    ("ø", "2"),
+    ("œ", "9"),
+    ("ɵ", "8"),
+    ("ä", "a_\""),
+    // Suprasegmentals
+    ("ˈ", "\""),
+    ("ˌ", "%"),
+    ("ː", ":"),
+    ("ˑ", ":\\"),
+    ("\u{306}", "_X"),
+    (".", "."),
+    ("˥", "_T"),
+    ("˦", "_H"),
+    ("˧", "_M"),
+    ("˨", "_L"),
+    ("˩", "_B"),
+    // Diacritics
+    ("ʰ", "_h"),
+    ("ʼ", "_>"),
+    ("\u{325}", "_0"),
+    ("\u{30a}", "_0"),
+    ("\u{32c}", "_v"),
+    ("\u{324}", "_t"),
+    ("\u{330}", "_k"),
+    ("ʷ", "_w"),
+    ("ʲ", "'"),
+    ("ʲ", "_j"),
+    ("ˠ", "_G"),
+    ("ˤ", "_?\\"),
+    ("\u{303}", "~"),
+    ("\u{329}", "="),
+    ("\u{30d}", "="),
+    ("\u{31a}", "_}"),
+    ("ⁿ", "_n"),
+    ("ˡ", "_l"),
+    ("\u{32a}", "_d"),
+    ("\u{320}", "_-"),
+    ("\u{31f}", "_+"),
+    ("\u{31d}", "_r"),
+    ("˔", "_r"),
+    ("\u{31e}", "_o"),
+    ("\u{308}", "_\""),
+    ("\u{33d}", "_x"),
+    ("\u{339}", "_O"),
+    // Written unambiguously, but the more common `_` is read too
+    ("\u{361}", ")"),
+    ("\u{361}", "_"),
+    ("\u{35c}", ")"),
+];
+
+/// (IPA, Kirshenbaum) pairs
+///
+/// Kirshenbaum writes affricates as plain sequences, so the tie bar is
+/// dropped when writing and never read back.
+const KIRSHENBAUM: &[(&str, &str)] = &[
+    // Symbols shared with ASCII
+    ("a", "a"),
+    ("b", "b"),
+    ("c", "c"),
+    ("d", "d"),
+    ("e", "e"),
+    ("f", "f"),
+    ("ɡ", "g"),
+    ("g", "g"),
+    ("h", "h"),
+    ("i", "i"),
+    ("j", "j"),
+    ("k", "k"),
+    ("l", "l"),
+    ("m", "m"),
+    ("n", "n"),
+    ("o", "o"),
+    ("p", "p"),
+    ("q", "q"),
+    ("s", "s"),
+    ("t", "t"),
+    ("u", "u"),
+    ("v", "v"),
+    ("w", "w"),
+    ("x", "x"),
+    ("y", "y"),
+    ("z", "z"),
+    // Consonants
+    ("ʈ", "t."),
+    ("ɖ", "d."),
+    ("ɟ", "J"),
+    ("ɢ", "G"),
+    ("ʔ", "?"),
+    ("ɱ", "M"),
+    ("ɳ", "n."),
+    ("ɲ", "n^"),
+    ("ŋ", "N"),
+    ("ɴ", "n\""),
+    ("ʙ", "b<trl>"),
+    ("r", "r<trl>"),
+    ("ʀ", "r\""),
+    ("ɾ", "*"),
+    ("ɽ", "*."),
+    ("ɸ", "P"),
+    ("β", "B"),
+    ("θ", "T"),
+    ("ð", "D"),
+    ("ʃ", "S"),
+    ("ʒ", "Z"),
+    ("ʂ", "s."),
+    ("ʐ", "z."),
+    ("ç", "C"),
+    ("ʝ", "C<vcd>"),
+    ("ɣ", "Q"),
+    ("χ", "X"),
+    ("ʁ", "g\""),
+    ("ħ", "H"),
+    ("ʕ", "H<vcd>"),
+    ("ɦ", "h<?>"),
+    ("ɬ", "s<lat>"),
+    ("ɮ", "z<lat>"),
+    ("ʋ", "r<lbd>"),
+    ("ɹ", "r"),
+    ("ɻ", "r."),
+    ("ɰ", "j<vel>"),
+    ("ɭ", "l."),
+    ("ʎ", "l^"),
+    ("ʟ", "L"),
+    ("ɓ", "b`"),
+    ("ɗ", "d`"),
+    ("ʄ", "J`"),
+    ("ɠ", "g`"),
+    ("ʛ", "G`"),
+    ("ʘ", "p!"),
+    ("ǀ", "t[!"),
+    ("ǃ", "t!"),
+    ("ǂ", "c!"),
+    ("ǁ", "l!"),
+    ("ʍ", "w<vls>"),
+    ("ɥ", "j<rnd>"),
+    // Vowels
+    ("ɪ", "I"),
+    ("ʏ", "I."),
+    ("ʊ", "U"),
+    ("ɛ", "E"),
+    ("œ", "W"),
+    ("ø", "Y"),
+    ("æ", "&"),
+    ("ɶ", "&."),
+    ("ɑ", "A"),
+    ("ɒ", "A."),
+    ("ʌ", "V"),
+    ("ɔ", "O"),
+    ("ə", "@"),
+    ("ɐ", "a\""),
+    ("ɨ", "i\""),
+    ("ʉ", "u\""),
+    ("ɯ", "u-"),
+    ("ɤ", "o-"),
+    ("ɘ", "@<umd>"),
+    ("ɵ", "@."),
+    ("ɜ", "V\""),
+    ("ɞ", "O\""),
+    ("ɚ", "R"),
+    // Suprasegmentals
+    ("ˈ", "'"),
+    ("ˌ", ","),
+    ("ː", ":"),
+    // Diacritics
+    ("ʰ", "<h>"),
+    ("ʼ", "`"),
+    ("\u{325}", "<o>"),
+    ("\u{30a}", "<o>"),
+    ("\u{32c}", "<v>"),
+    ("\u{324}", "<?>"),
+    ("ʷ", "<w>"),
+    ("ʲ", ";"),
+    ("ˠ", "<vel>"),
+    ("ˤ", "<phr>"),
+    ("\u{303}", "~"),
+    ("\u{329}", "-"),
+    ("\u{30d}", "-"),
+    ("\u{32a}", "["),
+    ("\u{361}", ""),
+    ("\u{35c}", ""),
+];
+
+struct Table {
+    /// (IPA, notation), longest IPA first
+    from_ipa: Vec<(&'static str, &'static str)>,
+    /// (notation, IPA), longest notation first
+    to_ipa: Vec<(&'static str, &'static str)>,
+}
+
+impl Table {
+    fn new(pairs: &'static [(&'static str, &'static str)]) -> Table {
+        let mut from_ipa = pairs.to_vec();
+        from_ipa.sort_by_key(|(ipa, _)| std::cmp::Reverse(ipa.chars().count()));
+
+        let mut to_ipa: Vec<_> = pairs
+            .iter()
+            .filter(|(_, notation)| !notation.is_empty())
+            .map(|(ipa, notation)| (*notation, *ipa))
+            .collect();
+        to_ipa.sort_by_key(|(notation, _)| std::cmp::Reverse(notation.len()));
+
+        Table { from_ipa, to_ipa }
+    }
+}
+
+impl Notation {
+    fn table(self) -> &'static Table {
+        static X_SAMPA_TABLE: OnceLock<Table> = OnceLock::new();
+        static KIRSHENBAUM_TABLE: OnceLock<Table> = OnceLock::new();
+
+        match self {
+            Notation::XSampa => X_SAMPA_TABLE.get_or_init(|| Table::new(X_SAMPA)),
+            Notation::Kirshenbaum => KIRSHENBAUM_TABLE.get_or_init(|| Table::new(KIRSHENBAUM)),
+        }
+    }
+
+    /// Characters that are copied through in both directions
+    fn is_separator(self, c: char) -> bool {
+        c.is_whitespace()
+            || match self {
+                Notation::XSampa => matches!(c, '/' | '[' | ']' | ','),
+                Notation::Kirshenbaum => matches!(c, '/' | '(' | ')'),
+            }
+    }
+}
+
+fn transliterate(
+    input: &str,
+    notation: Notation,
+    pairs: &[(&'static str, &'static str)],
+) -> Transliteration {
+    let mut output = String::new();
+    let mut unmapped = vec![];
+
+    let mut rest = input;
+    let mut position = 0;
+
+    while let Some(c) = rest.chars().next() {
+        let consumed = if notation.is_separator(c) {
+            output.push(c);
+            c.len_utf8()
+        } else if let Some((from, to)) = pairs.iter().find(|(from, _)| rest.starts_with(from)) {
+            output.push_str(to);
+            from.len()
+        } else {
+            output.push(c);
+            unmapped.push(UnmappedSymbol {
+                position,
+                symbol: c,
+            });
+            c.len_utf8()
+        };
+
+        position += rest[..consumed].chars().count();
+        rest = &rest[consumed..];
+    }
+
+    Transliteration { output, unmapped }
+}
+
+/// Writes IPA in the notation
+pub fn from_ipa(input: &str, notation: Notation) -> Transliteration {
+    transliterate(input, notation, &notation.table().from_ipa)
+}
+
+/// Reads the notation as IPA
+pub fn to_ipa(input: &str, notation: Notation) -> Transliteration {
+    transliterate(input, notation, &notation.table().to_ipa)
+}
+
+impl Phone {
+    /// Writes the phone in the notation, failing if any part of its IPA
+    /// cannot be written
+    pub fn to_notation(&self, notation: Notation) -> Result<String, NotationError> {
+        let transliteration = from_ipa(&self.to_string(), notation);
+
+        match transliteration.unmapped.first() {
+            Some(unmapped) => Err(NotationError::Unmappable {
+                notation,
+                symbol: unmapped.symbol,
+            }),
+            None => Ok(transliteration.output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_ipa, to_ipa, Notation, NotationError, UnmappedSymbol};
+    use crate::data::{parse_ipa, IpaDiagnosticKind, Phone};
+
+    /// Checks that every valid chart phone renders the same after writing
+    /// it in the notation and reading it back, returning the symbols that
+    /// could not be written
+    fn chart_round_trips(notation: Notation) -> Vec<char> {
+        let mut unmappable = vec![];
+
+        for phone in Phone::chart() {
+            let rendered = phone.to_string();
+            // Tie bars are lost in notations that do not have them
+            if phone.validate().is_err()
+                || (rendered.contains('\u{361}') && notation == Notation::Kirshenbaum)
+            {
+                continue;
+            }
+
+            let written = match phone.to_notation(notation) {
+                Ok(written) => written,
+                Err(NotationError::Unmappable { symbol, .. }) => {
+                    unmappable.push(symbol);
+                    continue;
+                }
+                Err(error) => panic!("{error}"),
+            };
+            let read = to_ipa(&written, notation);
+            assert!(read.unmapped.is_empty(), "{written}: {read:?}");
+
+            let parsed = parse_ipa(&read.output);
+            // Taps that share a symbol with an extra-short plosive are flagged
+            assert!(
+                parsed
+                    .diagnostics
+                    .iter()
+                    .all(|diagnostic| diagnostic.kind == IpaDiagnosticKind::AmbiguousTap),
+                "{written}: {parsed:?}"
+            );
+            assert_eq!(parsed.phones.len(), 1, "{written}: {parsed:?}");
+            assert_eq!(parsed.phones[0].to_string(), rendered, "{written}");
+        }
+
+        unmappable.sort();
+        unmappable.dedup();
+        unmappable
+    }
+
+    #[test]
+    fn chart_round_trips_through_x_sampa() {
+        assert_eq!(chart_round_trips(Notation::XSampa), ['ⱱ']);
+    }
+
+    #[test]
+    fn chart_round_trips_through_kirshenbaum() {
+        // Kirshenbaum has no symbols for many place diacritics
+        let unmappable = chart_round_trips(Notation::Kirshenbaum);
+        assert!(unmappable.contains(&'\u{31d}'), "{unmappable:?}");
+    }
+
+    #[test]
+    fn converts_transcriptions() {
+        assert_eq!(to_ipa("t_S_h a:", Notation::XSampa).output, "t͡ʃʰ aː");
+        assert_eq!(to_ipa("\"p_hA.t_w", Notation::XSampa).output, "ˈpʰɑ.tʷ");
+        assert_eq!(from_ipa("ʃɪp ŋ̍", Notation::XSampa).output, "SIp N=");
+        assert_eq!(from_ipa("t͡ʃɛ", Notation::Kirshenbaum).output, "tSE");
+        assert_eq!(to_ipa("'t.a:n^", Notation::Kirshenbaum).output, "ˈʈaːɲ");
+    }
+
+    #[test]
+    fn reports_unmappable_symbols() {
+        let written = from_ipa("ma˧˥", Notation::Kirshenbaum);
+        assert_eq!(written.output, "ma˧˥");
+        assert_eq!(
+            written.unmapped,
+            vec![
+                UnmappedSymbol {
+                    position: 2,
+                    symbol: '˧'
+                },
+                UnmappedSymbol {
+                    position: 3,
+                    symbol: '˥'
+                },
+            ]
+        );
+
+        let read = to_ipa("k_#", Notation::XSampa);
+        assert_eq!(read.output, "k͡#");
+        assert_eq!(
+            read.unmapped,
+            vec![UnmappedSymbol {
+                position: 2,
+                symbol: '#'
+            }]
+        );
+    }
+}
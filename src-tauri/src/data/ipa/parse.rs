@@ -0,0 +1,610 @@
+//! Parsing of IPA strings back into [`Phone`]s
+//!
+//! The base symbols are taken from the renderer itself, so anything that
+//! [`Phone`]'s `Display` produces for a valid phone parses back to a phone
+//! that renders the same.
+
+use std::{collections::HashSet, sync::OnceLock};
+
+use serde::Serialize;
+
+use super::Diacritics;
+use crate::data::{Length, ObstruentAttachment, Phone, Place, SecondaryArticulation};
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ParsedIpa {
+    pub phones: Vec<Phone>,
+    pub diagnostics: Vec<IpaDiagnostic>,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("character {position}: {kind}")]
+pub struct IpaDiagnostic {
+    /// Character (not byte) offset into the input
+    pub position: usize,
+    pub kind: IpaDiagnosticKind,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum IpaDiagnosticKind {
+    /// The character is not a symbol or diacritic we know of
+    #[error("unknown symbol `{0}`")]
+    UnknownSymbol(char),
+    /// The diacritic is known, but cannot apply to the phone it is attached to
+    #[error("`{0}` cannot apply to the phone it is attached to")]
+    UnsupportedDiacritic(char),
+    /// A diacritic with no phone to attach to
+    #[error("`{0}` has no phone to attach to")]
+    DanglingDiacritic(char),
+    /// A tie bar that does not join a plosive and a fricative
+    #[error("the tie bar does not join a plosive and a fricative")]
+    UnsupportedTie,
+    /// A tie bar joining a plosive and a fricative that differ in voicing
+    #[error("the tie bar joins a plosive and a fricative that differ in voicing")]
+    MismatchedTie,
+    /// A symbol that spells both a tap and an extra-short plosive, read as the tap
+    #[error("the symbol is both a tap and an extra-short plosive")]
+    AmbiguousTap,
+    /// Aspiration after a phone that cannot take it, read as preaspiration
+    /// of the following phone
+    #[error("aspiration after a phone that cannot take it is read as preaspiration")]
+    AmbiguousAspiration,
+}
+
+impl ParsedIpa {
+    /// The phones, unless anything in the input had to be skipped or guessed
+    pub fn into_phones(self) -> Result<Vec<Phone>, IpaDiagnostic> {
+        match self.diagnostics.into_iter().next() {
+            Some(diagnostic) => Err(diagnostic),
+            None => Ok(self.phones),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Modifier {
+    Attachment(ObstruentAttachment),
+    Secondary(SecondaryArticulation),
+    Voiceless,
+    Syllabic,
+    Nasalized,
+    Length(Length),
+    Tie,
+}
+
+struct Tables {
+    /// Base symbols, longest first
+    symbols: Vec<(String, Phone)>,
+    modifiers: Vec<(String, Modifier)>,
+    /// Tap symbols that an extra-short plosive renders the same as
+    ambiguous_taps: HashSet<String>,
+}
+
+impl Tables {
+    fn get() -> &'static Tables {
+        static TABLES: OnceLock<Tables> = OnceLock::new();
+
+        TABLES.get_or_init(|| {
+            let mut symbols: Vec<(String, Phone)> = Phone::chart()
+                .into_iter()
+                .filter(|phone| phone.validate().is_ok())
+                .map(|phone| (phone.to_string(), phone))
+                .collect();
+            // Common typographic substitutes
+            symbols.push((
+                "g".to_string(),
+                Phone::Plosive {
+                    place: Place::Velar,
+                    voiced: true,
+                    attachments: HashSet::new(),
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                },
+            ));
+            // Longest match first, and the first valid phone wins for shared symbols
+            symbols.sort_by_key(|(symbol, _)| std::cmp::Reverse(symbol.chars().count()));
+
+            let ambiguous_taps = Phone::chart()
+                .into_iter()
+                .filter(|phone| matches!(phone, Phone::Plosive { .. }))
+                .filter_map(|mut phone| {
+                    *phone.length_mut()? = Length::ExtraShort;
+                    phone.validate().ok()?;
+                    Some(phone.to_string())
+                })
+                .filter(|rendered| {
+                    symbols.iter().any(|(symbol, phone)| {
+                        symbol == rendered && matches!(phone, Phone::Tap { .. })
+                    })
+                })
+                .collect();
+
+            let diacritics = Diacritics::get();
+            let mut modifiers = vec![
+                (
+                    diacritics.aspirated.clone(),
+                    Modifier::Attachment(ObstruentAttachment::Aspirated),
+                ),
+                (
+                    diacritics.breathy.clone(),
+                    Modifier::Attachment(ObstruentAttachment::Breathy),
+                ),
+                (
+                    diacritics.creaky.clone(),
+                    Modifier::Attachment(ObstruentAttachment::Creaky),
+                ),
+                (
+                    diacritics.ejective.clone(),
+                    Modifier::Attachment(ObstruentAttachment::Ejective),
+                ),
+                (diacritics.tie.clone(), Modifier::Tie),
+                (
+                    "'".to_string(),
+                    Modifier::Attachment(ObstruentAttachment::Ejective),
+                ),
+                (
+                    "ⁿ".to_string(),
+                    Modifier::Attachment(ObstruentAttachment::NasalRelease),
+                ),
+                (
+                    "ˡ".to_string(),
+                    Modifier::Attachment(ObstruentAttachment::LateralRelease),
+                ),
+                (
+                    "\u{31a}".to_string(),
+                    Modifier::Attachment(ObstruentAttachment::Unreleased),
+                ),
+                (
+                    "ʷ".to_string(),
+                    Modifier::Secondary(SecondaryArticulation::Labialized),
+                ),
+                (
+                    "ʲ".to_string(),
+                    Modifier::Secondary(SecondaryArticulation::Palatalized),
+                ),
+                (
+                    "ˠ".to_string(),
+                    Modifier::Secondary(SecondaryArticulation::Velarized),
+                ),
+                (
+                    "ˤ".to_string(),
+                    Modifier::Secondary(SecondaryArticulation::Pharyngealized),
+                ),
+                ("\u{35c}".to_string(), Modifier::Tie),
+                ("\u{325}".to_string(), Modifier::Voiceless),
+                ("\u{30a}".to_string(), Modifier::Voiceless),
+                ("\u{329}".to_string(), Modifier::Syllabic),
+                ("\u{30d}".to_string(), Modifier::Syllabic),
+                ("\u{303}".to_string(), Modifier::Nasalized),
+                ("ː".to_string(), Modifier::Length(Length::Long)),
+                ("ˑ".to_string(), Modifier::Length(Length::HalfLong)),
+                ("\u{306}".to_string(), Modifier::Length(Length::ExtraShort)),
+            ];
+            modifiers.retain(|(symbol, _)| !symbol.is_empty());
+            modifiers.sort_by_key(|(symbol, _)| std::cmp::Reverse(symbol.chars().count()));
+
+            Tables {
+                symbols,
+                modifiers,
+                ambiguous_taps,
+            }
+        })
+    }
+
+    fn symbol(&self, input: &str) -> Option<(&str, &Phone)> {
+        self.symbols
+            .iter()
+            .find(|(symbol, _)| input.starts_with(symbol.as_str()))
+            .map(|(symbol, phone)| (symbol.as_str(), phone))
+    }
+
+    fn modifier(&self, input: &str) -> Option<(&str, Modifier)> {
+        self.modifiers
+            .iter()
+            .find(|(symbol, _)| input.starts_with(symbol.as_str()))
+            .map(|(symbol, modifier)| (symbol.as_str(), *modifier))
+    }
+}
+
+/// Characters that commonly surround inventories in papers
+fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, ',' | ';' | '/' | '[' | ']' | '(' | ')' | '.')
+}
+
+fn is_diacritic(c: char) -> bool {
+    matches!(c, '\u{300}'..='\u{36f}' | '\u{2b0}'..='\u{2ff}' | '\u{1d2c}'..='\u{1dbf}')
+}
+
+/// Applies a modifier to a phone, returning whether it could be applied
+///
+/// A modifier that would make the phone impossible is not applied.
+fn apply(phone: &mut Phone, modifier: Modifier) -> bool {
+    let mut modified = phone.clone();
+
+    let applied = match (&mut modified, modifier) {
+        (phone, Modifier::Attachment(attachment)) => phone
+            .attachments_mut()
+            .map(|attachments| attachments.insert(attachment))
+            .is_some(),
+        (phone, Modifier::Secondary(secondary)) => phone
+            .secondary_mut()
+            .map(|secondaries| secondaries.insert(secondary))
+            .is_some(),
+        (
+            Phone::Plosive { voiced, .. }
+            | Phone::Affricative { voiced, .. }
+            | Phone::Fricative { voiced, .. }
+            | Phone::LateralFricative { voiced, .. }
+            | Phone::Nasal { voiced, .. }
+            | Phone::Trill { voiced, .. }
+            | Phone::Tap { voiced, .. }
+            | Phone::Approximant { voiced, .. }
+            | Phone::LateralApproximant { voiced, .. }
+            | Phone::Implosive { voiced, .. },
+            Modifier::Voiceless,
+        ) => {
+            *voiced = false;
+            true
+        }
+        (phone, Modifier::Syllabic) => phone
+            .syllabic_mut()
+            .map(|syllabic| *syllabic = true)
+            .is_some(),
+        (Phone::Vowel { nasalized, .. }, Modifier::Nasalized) => {
+            *nasalized = true;
+            true
+        }
+        (phone, Modifier::Length(length)) => phone
+            .length_mut()
+            .map(|old_length| *old_length = length)
+            .is_some(),
+        _ => false,
+    };
+
+    if applied && modified.validate().is_ok() {
+        *phone = modified;
+        true
+    } else {
+        false
+    }
+}
+
+/// Joins a plosive and a fricative of the same voicing into an affricative
+fn tie(start: &Phone, end: &Phone) -> Result<Phone, IpaDiagnosticKind> {
+    match (start, end) {
+        (
+            Phone::Plosive { voiced, .. },
+            Phone::Fricative {
+                voiced: end_voiced, ..
+            },
+        ) if voiced != end_voiced => Err(IpaDiagnosticKind::MismatchedTie),
+        (
+            Phone::Plosive {
+                place: start_place,
+                voiced,
+                attachments: start_attachments,
+                secondary: start_secondary,
+                ..
+            },
+            Phone::Fricative {
+                place: end_place,
+                attachments: end_attachments,
+                secondary: end_secondary,
+                length,
+                ..
+            },
+        ) => Ok(Phone::Affricative {
+            start_place: *start_place,
+            end_place: *end_place,
+            voiced: *voiced,
+            attachments: start_attachments.union(end_attachments).copied().collect(),
+            secondary: start_secondary.union(end_secondary).copied().collect(),
+            length: *length,
+        }),
+        _ => Err(IpaDiagnosticKind::UnsupportedTie),
+    }
+}
+
+/// Parses an IPA string into phones
+///
+/// Separators (whitespace, commas, slashes, brackets) are skipped. Anything
+/// that cannot be understood is reported as a diagnostic and skipped.
+pub fn parse_ipa(input: &str) -> ParsedIpa {
+    let tables = Tables::get();
+
+    let mut phones: Vec<Phone> = vec![];
+    let mut diagnostics = vec![];
+    // A preaspiration mark waiting for its phone
+    let mut preaspirated: Option<usize> = None;
+    // Whether the last phone can still take diacritics
+    let mut attached = false;
+    // The position of a tie waiting for the second half of an affricative
+    let mut tied: Option<usize> = None;
+
+    let mut rest = input;
+    let mut position = 0;
+    let mut diagnose = |position, kind| diagnostics.push(IpaDiagnostic { position, kind });
+
+    while let Some(c) = rest.chars().next() {
+        let (consumed, kind) = if is_separator(c) {
+            attached = false;
+            (c.len_utf8(), None)
+        } else if let Some((symbol, phone)) = tables.symbol(rest) {
+            let mut phone = phone.clone();
+
+            if let Some(at) = preaspirated.take() {
+                if !apply(
+                    &mut phone,
+                    Modifier::Attachment(ObstruentAttachment::Preaspirated),
+                ) {
+                    diagnose(at, IpaDiagnosticKind::UnsupportedDiacritic('ʰ'));
+                }
+            }
+
+            match tied.take() {
+                Some(at) => match tie(phones.last().unwrap(), &phone) {
+                    Ok(affricative) => *phones.last_mut().unwrap() = affricative,
+                    Err(kind) => {
+                        diagnose(at, kind);
+                        phones.push(phone);
+                    }
+                },
+                None => phones.push(phone),
+            }
+
+            attached = true;
+            let kind = tables
+                .ambiguous_taps
+                .contains(symbol)
+                .then_some(IpaDiagnosticKind::AmbiguousTap);
+
+            (symbol.len(), kind)
+        } else if let Some((symbol, modifier)) = tables.modifier(rest) {
+            let obstruent =
+                attached && matches!(phones.last(), Some(phone) if phone.attachments().is_some());
+            let kind = match (modifier, phones.last_mut()) {
+                (Modifier::Tie, Some(_)) if attached => {
+                    tied = Some(position);
+                    None
+                }
+                (Modifier::Tie, _) => Some(IpaDiagnosticKind::UnsupportedTie),
+                // Aspiration on something that cannot be aspirated
+                // is read as preaspiration of the following phone
+                (Modifier::Attachment(ObstruentAttachment::Aspirated), _) if !obstruent => {
+                    preaspirated = Some(position);
+                    attached.then_some(IpaDiagnosticKind::AmbiguousAspiration)
+                }
+                (modifier, Some(phone)) if attached => {
+                    if apply(phone, modifier) {
+                        None
+                    } else {
+                        Some(IpaDiagnosticKind::UnsupportedDiacritic(c))
+                    }
+                }
+                _ => Some(IpaDiagnosticKind::DanglingDiacritic(c)),
+            };
+
+            (symbol.len(), kind)
+        } else if is_diacritic(c) {
+            (
+                c.len_utf8(),
+                Some(IpaDiagnosticKind::UnsupportedDiacritic(c)),
+            )
+        } else {
+            attached = false;
+            (c.len_utf8(), Some(IpaDiagnosticKind::UnknownSymbol(c)))
+        };
+
+        if let Some(kind) = kind {
+            diagnose(position, kind);
+        }
+
+        position += rest[..consumed].chars().count();
+        rest = &rest[consumed..];
+    }
+
+    if let Some(at) = preaspirated {
+        diagnose(at, IpaDiagnosticKind::DanglingDiacritic('ʰ'));
+    }
+
+    if let Some(at) = tied {
+        diagnose(at, IpaDiagnosticKind::UnsupportedTie);
+    }
+
+    ParsedIpa {
+        phones,
+        diagnostics,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_ipa, IpaDiagnostic, IpaDiagnosticKind};
+    use crate::data::{Length, ObstruentAttachment, Phone, Place};
+    use std::collections::HashSet;
+
+    #[test]
+    fn valid_phones_round_trip() {
+        let extra_short = Phone::chart().into_iter().filter_map(|mut phone| {
+            *phone.length_mut()? = Length::ExtraShort;
+            Some(phone)
+        });
+
+        for phone in Phone::chart().into_iter().chain(extra_short) {
+            if phone.validate().is_err() {
+                continue;
+            }
+
+            let rendered = phone.to_string();
+            let parsed = parse_ipa(&rendered);
+            assert_eq!(parsed.phones.len(), 1, "{rendered}: {parsed:?}");
+            assert_eq!(parsed.phones[0].to_string(), rendered);
+
+            // Taps and extra-short plosives that share a symbol read as the tap
+            if !parsed.diagnostics.is_empty() {
+                assert_eq!(
+                    parsed.diagnostics[..],
+                    [IpaDiagnostic {
+                        position: 0,
+                        kind: IpaDiagnosticKind::AmbiguousTap
+                    }],
+                    "{rendered}"
+                );
+                assert!(matches!(parsed.phones[0], Phone::Tap { .. }), "{rendered}");
+            }
+        }
+    }
+
+    #[test]
+    fn attachments_round_trip() {
+        let attachment_sets = [
+            vec![ObstruentAttachment::Aspirated],
+            vec![ObstruentAttachment::Preaspirated],
+            vec![ObstruentAttachment::Ejective],
+            vec![ObstruentAttachment::Breathy, ObstruentAttachment::Creaky],
+            vec![
+                ObstruentAttachment::Ejective,
+                ObstruentAttachment::Aspirated,
+                ObstruentAttachment::Creaky,
+            ],
+        ];
+
+        for attachments in attachment_sets {
+            let voiced = !attachments.contains(&ObstruentAttachment::Ejective);
+            let phone = Phone::Affricative {
+                start_place: Place::Alveolar,
+                end_place: Place::Postalveolar,
+                voiced,
+                attachments: attachments.into_iter().collect(),
+                secondary: HashSet::new(),
+                length: Length::Short,
+            };
+
+            let parsed = parse_ipa(&phone.to_string());
+            assert!(parsed.diagnostics.is_empty(), "{phone}: {parsed:?}");
+            assert_eq!(parsed.phones, vec![phone.clone()], "{phone}");
+        }
+    }
+
+    #[test]
+    fn secondary_articulations_round_trip() {
+        for input in [
+            "kʷʰː",
+            "tˠ",
+            "sʲ",
+            "t͡sˤʰ",
+            "p\u{31a}",
+            "dⁿ",
+            "tˡ",
+            "n\u{329}",
+            "ŋ\u{30d}ː",
+            "l\u{325}\u{329}",
+        ] {
+            let parsed = parse_ipa(input);
+            assert!(parsed.diagnostics.is_empty(), "{input}: {parsed:?}");
+            assert_eq!(parsed.phones.len(), 1, "{input}: {parsed:?}");
+            assert_eq!(parsed.phones[0].to_string(), input);
+        }
+    }
+
+    #[test]
+    fn parses_papers_notation() {
+        let parsed = parse_ipa("/tʰ ʔp' t͡ʃ/");
+
+        assert!(parsed.diagnostics.is_empty(), "{parsed:?}");
+        assert_eq!(
+            parsed.phones,
+            vec![
+                Phone::Plosive {
+                    place: Place::Alveolar,
+                    voiced: false,
+                    attachments: [ObstruentAttachment::Aspirated].into_iter().collect(),
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                },
+                Phone::Plosive {
+                    place: Place::Glottal,
+                    voiced: false,
+                    attachments: HashSet::new(),
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                },
+                Phone::Plosive {
+                    place: Place::Bilabial,
+                    voiced: false,
+                    attachments: [ObstruentAttachment::Ejective].into_iter().collect(),
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                },
+                Phone::Affricative {
+                    start_place: Place::Alveolar,
+                    end_place: Place::Postalveolar,
+                    voiced: false,
+                    attachments: HashSet::new(),
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_parses_reject_diagnostics() {
+        assert_eq!(parse_ipa("pa").into_phones().unwrap().len(), 2);
+        assert_eq!(
+            parse_ipa("pa*").into_phones().unwrap_err().to_string(),
+            "character 2: unknown symbol `*`"
+        );
+    }
+
+    #[test]
+    fn reports_unsupported_input() {
+        let kinds = |input| -> Vec<_> {
+            parse_ipa(input)
+                .diagnostics
+                .into_iter()
+                .map(|diagnostic| (diagnostic.position, diagnostic.kind))
+                .collect()
+        };
+
+        assert_eq!(
+            kinds("a*"),
+            vec![(1, IpaDiagnosticKind::UnknownSymbol('*'))]
+        );
+        assert_eq!(
+            kinds("m\u{303}"),
+            vec![(1, IpaDiagnosticKind::UnsupportedDiacritic('\u{303}'))]
+        );
+        assert_eq!(kinds("t͡a"), vec![(1, IpaDiagnosticKind::UnsupportedTie)]);
+        assert_eq!(kinds("t͡ʒ"), vec![(1, IpaDiagnosticKind::MismatchedTie)]);
+        assert_eq!(parse_ipa("t͡ʒ").phones.len(), 2);
+        assert_eq!(
+            kinds(" ʼ"),
+            vec![(1, IpaDiagnosticKind::DanglingDiacritic('ʼ'))]
+        );
+        assert_eq!(
+            kinds("ʰm"),
+            vec![(0, IpaDiagnosticKind::UnsupportedDiacritic('ʰ'))]
+        );
+        assert_eq!(
+            kinds("nʰt"),
+            vec![(1, IpaDiagnosticKind::AmbiguousAspiration)]
+        );
+        assert_eq!(kinds("ʰt"), vec![]);
+        assert_eq!(
+            kinds("ɢ\u{306}"),
+            vec![(0, IpaDiagnosticKind::AmbiguousTap)]
+        );
+        assert!(matches!(
+            parse_ipa("ɢ\u{306}").phones[..],
+            [Phone::Tap { .. }]
+        ));
+        assert_eq!(
+            kinds("s\u{31a} ɲʲ"),
+            vec![
+                (1, IpaDiagnosticKind::UnsupportedDiacritic('\u{31a}')),
+                (4, IpaDiagnosticKind::UnsupportedDiacritic('ʲ'))
+            ]
+        );
+    }
+}
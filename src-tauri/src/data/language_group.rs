@@ -4,7 +4,7 @@ use semver::Version;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{Language, Protolanguage};
+use super::{Ancestry, Language, Protolanguage, Stage};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LanguageGroup {
@@ -15,6 +15,12 @@ pub struct LanguageGroup {
     pub family_id: Uuid,
     pub protolangs: Vec<Protolanguage>,
     pub langs: Vec<Language>,
+    /// Protolanguages of past epochs, oldest first
+    #[serde(default)]
+    pub history: Vec<Stage>,
+    /// Ancestors of the protolanguages in the last stage of the history
+    #[serde(default)]
+    pub ancestry: Ancestry,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -27,6 +33,12 @@ pub enum LanguageGroupError {
     ProtolanguageEmptyName,
     #[error("the proto-language named {0} already exists")]
     ProtolanguageExists(String),
+    #[error("there is no language named {0}")]
+    UnknownLanguage(String),
+    #[error("there is no proto-language named {0}")]
+    UnknownProtolanguage(String),
+    #[error("the proto-language {0} would descend from itself")]
+    AncestryCycle(String),
 }
 
 // Use the crate version as the data version of the language group
@@ -41,6 +53,8 @@ impl Default for LanguageGroup {
             family_id: Uuid::new_v4(),
             protolangs: vec![],
             langs: vec![],
+            history: vec![],
+            ancestry: HashMap::new(),
         }
     }
 }
@@ -49,47 +63,82 @@ impl Default for LanguageGroup {
 impl LanguageGroup {
     /// Creates a new epoch.
     ///
-    /// All protolanguages are lost, and languages become protolanguages.
+    /// The protolanguages become a stage of the history, and languages become protolanguages.
     /// All protolanguage base info (phonemes and morphemes) are merged into the necessary languages
     /// before it becomes a protolanguage
     pub fn epoch(&mut self) {
-        // Create language phoneme lists
-        let phonemes: Vec<HashMap<_, _>> = self
+        // Resolve what each language inherits, with its sound changes
+        // applied, and its splits, mergers and losses recorded as
+        // correspondences
+        let effective: Vec<_> = self.langs.iter().map(|l| self.resolve(l.into())).collect();
+        let ancestry = self
             .langs
             .iter()
-            .map(|l| {
-                l.ancestors
-                    .iter()
-                    .flat_map(|anc| {
-                        self.protolanguage(anc)
-                            .unwrap()
-                            .phonemes
-                            .iter()
-                            .map(|(k, v)| (*k, v.clone()))
-                    })
-                    .collect()
-            })
+            .map(|l| (l.name.clone(), l.ancestors.clone()))
             .collect();
-        self.protolangs.clear();
+        if !self.protolangs.is_empty() {
+            self.history.push(Stage {
+                name: format!("Stage {}", self.history.len() + 1),
+                protolangs: std::mem::take(&mut self.protolangs),
+                ancestry: std::mem::replace(&mut self.ancestry, ancestry),
+            });
+        } else {
+            self.ancestry = HashMap::new();
+        }
         self.protolangs = self
             .langs
             .drain(..)
-            .zip(phonemes)
-            .map(|(l, phons)| {
+            .zip(effective)
+            .map(|(l, effective)| {
                 let mut basic: Protolanguage = l.into();
-                // Order matters: if both protolanguage and language specify
-                // an id for a phoneme, we want to use the one in the language
-                // (a language can "override" a protolanguage's phonemes)
-                // This is how we implement sound change.
-                basic.phonemes = phons
-                    .into_iter()
-                    .chain(basic.phonemes.into_iter())
-                    .collect();
+                effective.apply_to(&mut basic);
                 basic
             })
             .collect();
     }
 
+    /// Creates a new epoch for only some languages.
+    ///
+    /// The named languages become protolanguages, and the rest stay languages.
+    /// Protolanguages that the remaining languages descend from are carried
+    /// into the new epoch, so their ancestors still exist. Selecting no
+    /// languages changes nothing.
+    pub fn epoch_languages(&mut self, names: &[String]) -> Result<(), LanguageGroupError> {
+        if names.is_empty() {
+            return Ok(());
+        }
+        if let Some(name) = names.iter().find(|name| self.language(name).is_none()) {
+            return Err(LanguageGroupError::UnknownLanguage(name.clone()));
+        }
+
+        let (promoted, remaining): (Vec<_>, Vec<_>) = self
+            .langs
+            .iter()
+            .cloned()
+            .partition(|l| names.contains(&l.name));
+        let ancestors: Vec<_> = remaining
+            .iter()
+            .flat_map(|l| l.ancestors.iter().cloned())
+            .collect();
+        let carried: Vec<_> = self.lineage(&ancestors).into_iter().cloned().collect();
+        if let Some(p) = carried
+            .iter()
+            .find(|p| promoted.iter().any(|l| l.name == p.name))
+        {
+            return Err(LanguageGroupError::ProtolanguageExists(p.name.clone()));
+        }
+
+        self.langs = promoted;
+        self.epoch();
+        for p in carried {
+            self.ancestry.insert(p.name.clone(), vec![p.name.clone()]);
+            self.protolangs.push(p);
+        }
+        self.langs = remaining;
+
+        Ok(())
+    }
+
     /// Merges in languages of a language group.
     ///
     /// Loads only languages from an input language group, removing data that references
@@ -123,11 +172,15 @@ impl LanguageGroup {
 
 #[cfg(test)]
 mod tests {
-    use super::LanguageGroup;
-    use crate::data::{Language, Phone, Phoneme, Protolanguage};
+    use super::{LanguageGroup, LanguageGroupError};
+    use crate::data::{
+        fixtures::phoneme, Context, Correspondence, EnvironmentRule, Language, Length, Outcome,
+        Phone, Phoneme, PhonemeChange, PhonemeSet, Prosody, Protolanguage, SplitReflex,
+        StressPosition, StressRule, Suprasegmentals, SyllableTemplate, Target, Tone, ToneLevel,
+    };
     use insta::{assert_yaml_snapshot, with_settings};
     use std::collections::{HashMap, HashSet};
-    use uuid::uuid;
+    use uuid::{uuid, Uuid};
 
     #[test]
     fn lg_epoch_preserves_protophonemes_in_language() {
@@ -146,12 +199,16 @@ mod tests {
                                 place: crate::data::Place::Bilabial,
                                 voiced: false,
                                 attachments: HashSet::new(),
+                                secondary: HashSet::new(),
+                                length: Length::Short,
                             },
                             allo: vec![],
+                            suprasegmentals: Suprasegmentals::default(),
                         },
                     );
                     map
                 },
+                ..Default::default()
             }],
             langs: vec![Language {
                 name: "1'".to_string(),
@@ -167,12 +224,16 @@ mod tests {
                                 place: crate::data::Place::Bilabial,
                                 voiced: true,
                                 attachments: HashSet::new(),
+                                secondary: HashSet::new(),
+                                length: Length::Short,
                             },
                             allo: vec![],
+                            suprasegmentals: Suprasegmentals::default(),
                         },
                     );
                     map
                 },
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -184,6 +245,343 @@ mod tests {
         })
     }
 
+    #[test]
+    fn epoch_applies_sound_changes_to_inherited_phonemes() {
+        let mut pre = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                phonemes: HashMap::from([
+                    (uuid!("00000000-0000-0000-0000-000000000000"), phoneme("k")),
+                    (uuid!("00000000-0000-0000-0000-000000000001"), phoneme("t")),
+                ]),
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                // Overrides are the language's own, so are left alone
+                phonemes: HashMap::from([(
+                    uuid!("00000000-0000-0000-0000-000000000001"),
+                    phoneme("t"),
+                )]),
+                sound_changes: "V = a\nk > x\nt > s / V_".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        let phonemes = &pre.protolangs[0].phonemes;
+        let k = &phonemes[&uuid!("00000000-0000-0000-0000-000000000000")];
+        let t = &phonemes[&uuid!("00000000-0000-0000-0000-000000000001")];
+        assert_eq!(k.primary.to_string(), "x");
+        assert_eq!(t.primary.to_string(), "t");
+        assert!(t.allo.is_empty());
+    }
+
+    #[test]
+    fn epoch_splits_merges_and_loses_phonemes() {
+        let [k, e, i, h, c] = [0, 1, 2, 3, 4].map(Uuid::from_u128);
+        let mut pre = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                phonemes: HashMap::from([
+                    (k, phoneme("k")),
+                    (e, phoneme("e")),
+                    (i, phoneme("i")),
+                    (h, phoneme("h")),
+                ]),
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                phonemes: HashMap::from([(c, phoneme("tʃ"))]),
+                phoneme_changes: vec![
+                    PhonemeChange::Split {
+                        source: k,
+                        into: vec![
+                            SplitReflex {
+                                phoneme: c,
+                                before: vec![],
+                                after: vec![Context::Set("front".to_string())],
+                            },
+                            SplitReflex {
+                                phoneme: k,
+                                before: vec![],
+                                after: vec![],
+                            },
+                        ],
+                    },
+                    PhonemeChange::Merger {
+                        sources: vec![e, i],
+                        into: i,
+                    },
+                    PhonemeChange::Loss { source: h },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        let proto = &pre.protolangs[0];
+        let mut kept: Vec<_> = proto.phonemes.keys().copied().collect();
+        kept.sort();
+        assert_eq!(kept, [k, i, c]);
+        let reflex = |phoneme, after: &[&str]| SplitReflex {
+            phoneme,
+            before: vec![],
+            after: after
+                .iter()
+                .map(|set| Context::Set(set.to_string()))
+                .collect(),
+        };
+        assert_eq!(
+            proto.correspondences,
+            [
+                (k, vec![reflex(c, &["front"]), reflex(k, &[])]),
+                (e, vec![reflex(i, &[])]),
+                (i, vec![reflex(i, &[])]),
+                (h, vec![]),
+            ]
+            .map(|(proto, reflexes)| Correspondence {
+                ancestor: "1".to_string(),
+                proto,
+                reflexes,
+            })
+        );
+    }
+
+    #[test]
+    fn epoch_keeps_history_and_diffs_stages() {
+        let [p, t] = [0, 1].map(Uuid::from_u128);
+        let mut group = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                phonemes: HashMap::from([(p, phoneme("p")), (t, phoneme("t"))]),
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                sound_changes: "p > f".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        group.epoch();
+        group.langs.push(Language {
+            name: "1''".to_string(),
+            ancestors: vec!["1'".to_string()],
+            phonemes: HashMap::from([(t, phoneme("d"))]),
+            ..Default::default()
+        });
+        group.epoch();
+
+        let names: Vec<_> = group.history.iter().map(|stage| &stage.name).collect();
+        assert_eq!(names, ["Stage 1", "Stage 2"]);
+        assert_eq!(group.protolangs[0].name, "1''");
+
+        let diffs = group.diff_stages(0, 2).unwrap();
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].ancestors, ["1"]);
+        let changed: Vec<_> = diffs[0]
+            .phonemes
+            .iter()
+            .map(|diff| {
+                let ortho = |phoneme: &Option<Phoneme>| phoneme.as_ref().unwrap().ipa();
+                (diff.id, ortho(&diff.before), ortho(&diff.after))
+            })
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                (p, "p".to_string(), "f".to_string()),
+                (t, "t".to_string(), "d".to_string())
+            ]
+        );
+        assert!(group.diff_stages(2, 1).is_err());
+        assert!(group.diff_stages(0, 3).is_err());
+    }
+
+    #[test]
+    fn epoch_takes_shared_phonemes_from_the_first_ancestor() {
+        let p = Uuid::from_u128(0);
+        let proto = |name: &str, ipa| Protolanguage {
+            name: name.to_string(),
+            phonemes: HashMap::from([(p, phoneme(ipa))]),
+            ..Default::default()
+        };
+        let lang = |ancestors: [&str; 2]| Language {
+            name: ancestors.concat(),
+            ancestors: ancestors.map(str::to_string).to_vec(),
+            ..Default::default()
+        };
+        let mut group = LanguageGroup {
+            protolangs: vec![proto("1", "p"), proto("2", "b")],
+            langs: vec![lang(["1", "2"]), lang(["2", "1"])],
+            ..Default::default()
+        };
+
+        group.epoch();
+
+        let ipa: Vec<_> = group
+            .protolangs
+            .iter()
+            .map(|proto| proto.phonemes[&p].ipa())
+            .collect();
+        assert_eq!(ipa, ["p", "b"]);
+    }
+
+    #[test]
+    fn diff_stages_compares_with_the_first_ancestor() {
+        let p = Uuid::from_u128(0);
+        let proto = |name: &str, ipa| Protolanguage {
+            name: name.to_string(),
+            phonemes: HashMap::from([(p, phoneme(ipa))]),
+            ..Default::default()
+        };
+        let mut group = LanguageGroup {
+            protolangs: vec![proto("1", "p"), proto("2", "b")],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string(), "2".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        group.epoch();
+
+        let diffs = group.diff_stages(0, 1).unwrap();
+        assert_eq!(diffs[0].ancestors, ["1", "2"]);
+        assert!(diffs[0].phonemes.is_empty(), "{diffs:?}");
+    }
+
+    fn selective_group() -> LanguageGroup {
+        LanguageGroup {
+            family_id: uuid!("00000000-0000-0000-0000-000000000000"),
+            protolangs: vec![
+                Protolanguage {
+                    name: "1".to_string(),
+                    phonemes: HashMap::from([(Uuid::from_u128(0), phoneme("p"))]),
+                    ..Default::default()
+                },
+                Protolanguage {
+                    name: "2".to_string(),
+                    phonemes: HashMap::from([(Uuid::from_u128(1), phoneme("t"))]),
+                    ..Default::default()
+                },
+            ],
+            langs: vec![
+                Language {
+                    name: "1'".to_string(),
+                    ancestors: vec!["1".to_string()],
+                    sound_changes: "p > f".to_string(),
+                    ..Default::default()
+                },
+                Language {
+                    name: "1''".to_string(),
+                    ancestors: vec!["1".to_string()],
+                    ..Default::default()
+                },
+                Language {
+                    name: "2'".to_string(),
+                    ancestors: vec!["2".to_string()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lg_epoch_selected_keeps_contemporaries() {
+        let mut pre = selective_group();
+
+        pre.epoch_languages(&["1'".to_string()]).unwrap();
+
+        with_settings!({sort_maps => true}, {
+            assert_yaml_snapshot!(pre);
+        })
+    }
+
+    #[test]
+    fn lg_epoch_selected_branch_drops_unused_protolanguages() {
+        let mut pre = selective_group();
+
+        pre.epoch_languages(&["2'".to_string()]).unwrap();
+
+        with_settings!({sort_maps => true}, {
+            assert_yaml_snapshot!(pre);
+        })
+    }
+
+    #[test]
+    fn lg_epoch_selected_rejects_unknown_languages() {
+        let mut pre = selective_group();
+
+        assert!(matches!(
+            pre.epoch_languages(&["3'".to_string()]),
+            Err(LanguageGroupError::UnknownLanguage(name)) if name == "3'"
+        ));
+        assert_eq!(pre.langs.len(), 3);
+        assert!(pre.history.is_empty());
+    }
+
+    #[test]
+    fn lg_epoch_selected_without_languages_changes_nothing() {
+        let mut pre = selective_group();
+
+        pre.epoch_languages(&[]).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(pre).unwrap(),
+            serde_json::to_value(selective_group()).unwrap()
+        );
+    }
+
+    #[test]
+    fn epoch_inherits_through_intermediate_protolanguages() {
+        let [p, t] = [0, 1].map(Uuid::from_u128);
+        let mut pre = LanguageGroup {
+            protolangs: vec![
+                Protolanguage {
+                    name: "1".to_string(),
+                    phonemes: HashMap::from([(p, phoneme("p")), (t, phoneme("t"))]),
+                    prosody: Some(Prosody {
+                        rule: StressRule::Fixed(StressPosition::Initial),
+                        weight: Default::default(),
+                    }),
+                    ..Default::default()
+                },
+                Protolanguage {
+                    name: "1.1".to_string(),
+                    ancestors: vec!["1".to_string()],
+                    phonemes: HashMap::from([(t, phoneme("d"))]),
+                    ..Default::default()
+                },
+            ],
+            langs: vec![Language {
+                name: "1.1'".to_string(),
+                ancestors: vec!["1.1".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        let proto = &pre.protolangs[0];
+        assert_eq!(proto.phonemes[&p].ipa(), "p");
+        assert_eq!(proto.phonemes[&t].ipa(), "d");
+        assert!(proto.prosody.is_some());
+    }
+
     #[test]
     fn epoch_when_phoneme_and_protophoneme_exist_keeps_phoneme() {
         let mut pre = LanguageGroup {
@@ -201,12 +599,16 @@ mod tests {
                                 place: crate::data::Place::Bilabial,
                                 voiced: false,
                                 attachments: HashSet::new(),
+                                secondary: HashSet::new(),
+                                length: Length::Short,
                             },
                             allo: vec![],
+                            suprasegmentals: Suprasegmentals::default(),
                         },
                     );
                     map
                 },
+                ..Default::default()
             }],
             langs: vec![Language {
                 name: "1'".to_string(),
@@ -222,12 +624,16 @@ mod tests {
                                 place: crate::data::Place::Bilabial,
                                 voiced: true,
                                 attachments: HashSet::new(),
+                                secondary: HashSet::new(),
+                                length: Length::Short,
                             },
                             allo: vec![],
+                            suprasegmentals: Suprasegmentals::default(),
                         },
                     );
                     map
                 },
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -239,6 +645,107 @@ mod tests {
         })
     }
 
+    #[test]
+    fn epoch_inherits_ancestor_tones() {
+        let high = Tone::Level(ToneLevel::High);
+        let rising = Tone::from_chao("35").unwrap();
+        let mut pre = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                tones: vec![high.clone(), rising.clone()],
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                tones: vec![rising.clone()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        assert_eq!(pre.protolangs[0].tones, vec![rising, high]);
+    }
+
+    #[test]
+    fn epoch_inherits_ancestor_phoneme_sets() {
+        let ancestral = PhonemeSet {
+            members: vec!["p".to_string()],
+            ..Default::default()
+        };
+        let own = PhonemeSet {
+            members: vec!["b".to_string()],
+            ..Default::default()
+        };
+        let mut pre = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                phoneme_sets: HashMap::from([
+                    ("stops".to_string(), ancestral.clone()),
+                    ("vowels".to_string(), ancestral.clone()),
+                ]),
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                phoneme_sets: HashMap::from([("stops".to_string(), own.clone())]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        let sets = &pre.protolangs[0].phoneme_sets;
+        assert_eq!(sets["stops"], own);
+        assert_eq!(sets["vowels"], ancestral);
+    }
+
+    #[test]
+    fn epoch_inherits_ancestor_phonotactics() {
+        let template = SyllableTemplate {
+            pattern: "(C)V".to_string(),
+            sets: HashMap::from([('C', "c".to_string()), ('V', "v".to_string())]),
+            nucleus: 'V',
+            principle: Default::default(),
+        };
+        let prosody = Prosody {
+            rule: StressRule::Fixed(StressPosition::Penultimate),
+            weight: Default::default(),
+        };
+        let rules = vec![EnvironmentRule {
+            name: "devoicing".to_string(),
+            target: Target::Set("c".to_string()),
+            before: vec![],
+            after: vec![Context::Boundary],
+            outcome: Outcome::Allophone(0),
+        }];
+        let mut pre = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "1".to_string(),
+                syllable_template: Some(template.clone()),
+                prosody: Some(prosody.clone()),
+                environment_rules: rules.clone(),
+                ..Default::default()
+            }],
+            langs: vec![Language {
+                name: "1'".to_string(),
+                ancestors: vec!["1".to_string()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        pre.epoch();
+
+        assert_eq!(pre.protolangs[0].syllable_template, Some(template));
+        assert_eq!(pre.protolangs[0].prosody, Some(prosody));
+        assert_eq!(pre.protolangs[0].environment_rules, rules);
+    }
+
     #[test]
     fn merge_removes_nonexistant_ancestors_and_referents() {
         // TODO once we start referring to protolanguages,
@@ -249,12 +756,14 @@ mod tests {
                 name: "1".to_string(),
                 description: None,
                 phonemes: HashMap::new(),
+                ..Default::default()
             }],
             langs: vec![Language {
                 name: "A".to_string(),
                 description: None,
                 phonemes: HashMap::new(),
                 ancestors: vec!["1".to_string()],
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -267,6 +776,7 @@ mod tests {
                 description: None,
                 phonemes: HashMap::new(),
                 ancestors: vec!["H".to_string()],
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -279,6 +789,7 @@ mod tests {
                 description: None,
                 phonemes: HashMap::new(),
                 ancestors: vec!["1".to_string()],
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -362,12 +873,22 @@ impl LanguageGroup {
         Ok(())
     }
 
+    /// Deletes a protolanguage, and removes it from the ancestors of others
     pub fn delete_protolanguage(&mut self, name: String) {
         let index = self.protolangs.iter().position(|lang| lang.name == name);
 
         if let Some(index) = index {
             self.protolangs.remove(index);
         }
+
+        for ancestors in self
+            .langs
+            .iter_mut()
+            .map(|lang| &mut lang.ancestors)
+            .chain(self.protolangs.iter_mut().map(|lang| &mut lang.ancestors))
+        {
+            ancestors.retain(|anc| *anc != name);
+        }
     }
 
     pub fn protolanguage<S: AsRef<str>>(&self, name: S) -> Option<&Protolanguage> {
@@ -1,23 +1,55 @@
-use core::fmt;
-use std::{borrow::Cow, collections::HashSet, fmt::Formatter};
+use std::collections::HashSet;
 
-use ipa_translate::branner_to_ipa;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+use super::Suprasegmentals;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub struct Phoneme {
     pub ortho: String,
     pub primary: Phone,
     pub allo: Vec<Phone>,
+    #[serde(default)]
+    pub suprasegmentals: Suprasegmentals,
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+impl Phoneme {
+    /// The primary phone in IPA, with its tone, stress and length
+    pub fn ipa(&self) -> String {
+        self.suprasegmentals.render(&self.primary.to_string())
+    }
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
 pub enum Place {
     Bilabial,
     Labiodental,
     Dental,
     Alveolar,
     Postalveolar,
+    Retroflex,
+    Palatal,
+    Velar,
+    Uvular,
+    Pharyngeal,
+    Glottal,
+}
+
+impl Place {
+    /// Every place, from the front of the mouth to the back
+    pub const ALL: [Place; 11] = [
+        Place::Bilabial,
+        Place::Labiodental,
+        Place::Dental,
+        Place::Alveolar,
+        Place::Postalveolar,
+        Place::Retroflex,
+        Place::Palatal,
+        Place::Velar,
+        Place::Uvular,
+        Place::Pharyngeal,
+        Place::Glottal,
+    ];
 }
 
 #[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
@@ -27,157 +59,708 @@ pub enum ObstruentAttachment {
     Aspirated,
     Breathy,
     Creaky,
+    /// Only plosives can have a nasal, lateral or no audible release
+    NasalRelease,
+    LateralRelease,
+    Unreleased,
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SecondaryArticulation {
+    Labialized,
+    Palatalized,
+    Velarized,
+    Pharyngealized,
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Height {
+    Close,
+    NearClose,
+    CloseMid,
+    Mid,
+    OpenMid,
+    NearOpen,
+    Open,
+}
+
+impl Height {
+    /// Every height, from close to open
+    pub const ALL: [Height; 7] = [
+        Height::Close,
+        Height::NearClose,
+        Height::CloseMid,
+        Height::Mid,
+        Height::OpenMid,
+        Height::NearOpen,
+        Height::Open,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Backness {
+    Front,
+    Central,
+    Back,
 }
 
+impl Backness {
+    /// Every backness, from front to back
+    pub const ALL: [Backness; 3] = [Backness::Front, Backness::Central, Backness::Back];
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum Length {
+    ExtraShort,
+    #[default]
+    Short,
+    HalfLong,
+    Long,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhoneError {
+    #[error("a {place:?} {manner} is not a possible articulation")]
+    ImpossiblePlace { manner: &'static str, place: Place },
+    #[error("a voiced {place:?} {manner} is not a possible articulation")]
+    ImpossibleVoicing { manner: &'static str, place: Place },
+    #[error("a {manner} cannot be {attachment:?}")]
+    UnsupportedAttachment {
+        manner: &'static str,
+        attachment: ObstruentAttachment,
+    },
+    #[error("a {place:?} {manner} is already {secondary:?}")]
+    RedundantSecondary {
+        manner: &'static str,
+        place: Place,
+        secondary: SecondaryArticulation,
+    },
+}
+
+impl Serialize for PhoneError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_str())
+    }
+}
+
+#[derive(Clone, Copy)]
 enum PhoneType {
     Plosive,
     Fricative,
-    Vowel,
+    LateralFricative,
+    Nasal,
+    Trill,
+    Tap,
+    Approximant,
+    LateralApproximant,
+    Implosive,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+impl PhoneType {
+    fn name(self) -> &'static str {
+        match self {
+            PhoneType::Plosive => "plosive",
+            PhoneType::Fricative => "fricative",
+            PhoneType::LateralFricative => "lateral fricative",
+            PhoneType::Nasal => "nasal",
+            PhoneType::Trill => "trill",
+            PhoneType::Tap => "tap",
+            PhoneType::Approximant => "approximant",
+            PhoneType::LateralApproximant => "lateral approximant",
+            PhoneType::Implosive => "implosive",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
 pub enum Phone {
     Plosive {
         place: Place,
         voiced: bool,
         attachments: HashSet<ObstruentAttachment>,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
     },
     Affricative {
         start_place: Place,
         end_place: Place,
         voiced: bool,
         attachments: HashSet<ObstruentAttachment>,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
     },
     Fricative {
         place: Place,
         voiced: bool,
         attachments: HashSet<ObstruentAttachment>,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+    },
+    LateralFricative {
+        place: Place,
+        voiced: bool,
+        attachments: HashSet<ObstruentAttachment>,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+    },
+    Nasal {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+        #[serde(default)]
+        syllabic: bool,
+    },
+    Trill {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+        #[serde(default)]
+        syllabic: bool,
+    },
+    Tap {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+    },
+    Approximant {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+        #[serde(default)]
+        syllabic: bool,
+    },
+    LateralApproximant {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+        #[serde(default)]
+        syllabic: bool,
+    },
+    Implosive {
+        place: Place,
+        voiced: bool,
+        #[serde(default)]
+        secondary: HashSet<SecondaryArticulation>,
+        #[serde(default)]
+        length: Length,
+    },
+    Click {
+        place: Place,
+        lateral: bool,
+    },
+    Vowel {
+        height: Height,
+        backness: Backness,
+        rounded: bool,
+        #[serde(default)]
+        length: Length,
+        #[serde(default)]
+        nasalized: bool,
     },
-    Vowel {},
 }
 
 impl Phone {
-    fn get_base(
-        pt: PhoneType,
-        place: &Place,
-        voiced: bool,
-        attachments: &HashSet<ObstruentAttachment>,
-    ) -> String {
-        branner_to_ipa(match pt {
-            PhoneType::Plosive => match place {
-                Place::Bilabial if attachments.contains(&ObstruentAttachment::Ejective) => "p",
-                Place::Bilabial if voiced => "b",
-                Place::Bilabial => "p",
-                Place::Labiodental if attachments.contains(&ObstruentAttachment::Ejective) => "p[",
-                Place::Labiodental if voiced => "b[",
-                Place::Labiodental => "p[",
-                Place::Dental if attachments.contains(&ObstruentAttachment::Ejective) => "t[",
-                Place::Dental if voiced => "d[",
-                Place::Dental => "t[",
-                Place::Alveolar if attachments.contains(&ObstruentAttachment::Ejective) => "t",
-                Place::Alveolar if voiced => "d",
-                Place::Alveolar => "t",
-                Place::Postalveolar if attachments.contains(&ObstruentAttachment::Ejective) => "t",
-                Place::Postalveolar if voiced => "d",
-                Place::Postalveolar => "t",
-            },
-            PhoneType::Fricative => match place {
-                Place::Bilabial if attachments.contains(&ObstruentAttachment::Ejective) => "P\"",
-                Place::Bilabial if voiced => "B\"",
-                Place::Bilabial => "P\"",
-                Place::Labiodental if attachments.contains(&ObstruentAttachment::Ejective) => "f",
-                Place::Labiodental if voiced => "v",
-                Place::Labiodental => "f",
-                Place::Dental if attachments.contains(&ObstruentAttachment::Ejective) => "O-",
-                Place::Dental if voiced => "d-",
-                Place::Dental => "O-",
-                Place::Alveolar if attachments.contains(&ObstruentAttachment::Ejective) => "s",
-                Place::Alveolar if voiced => "z",
-                Place::Alveolar => "s",
-                Place::Postalveolar if attachments.contains(&ObstruentAttachment::Ejective) => "S",
-                Place::Postalveolar if voiced => "3\"",
-                Place::Postalveolar => "S",
-            },
-            PhoneType::Vowel => todo!(),
-        })
+    /// Every phone of the IPA chart without attachments, including
+    /// impossible articulations, in the order their symbols are preferred
+    pub fn chart() -> Vec<Phone> {
+        let mut phones = vec![];
+
+        for place in Place::ALL {
+            for voiced in [false, true] {
+                let attachments = HashSet::new;
+                let secondary = HashSet::new;
+                let length = Length::Short;
+                phones.push(Phone::Plosive {
+                    place,
+                    voiced,
+                    attachments: attachments(),
+                    secondary: secondary(),
+                    length,
+                });
+                phones.push(Phone::Fricative {
+                    place,
+                    voiced,
+                    attachments: attachments(),
+                    secondary: secondary(),
+                    length,
+                });
+                phones.push(Phone::LateralFricative {
+                    place,
+                    voiced,
+                    attachments: attachments(),
+                    secondary: secondary(),
+                    length,
+                });
+            }
+
+            let voiced = true;
+            let secondary = HashSet::new;
+            let length = Length::Short;
+            let syllabic = false;
+            phones.extend([
+                Phone::Nasal {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                    syllabic,
+                },
+                Phone::Trill {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                    syllabic,
+                },
+                Phone::Tap {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                },
+                Phone::Approximant {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                    syllabic,
+                },
+                Phone::LateralApproximant {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                    syllabic,
+                },
+                Phone::Implosive {
+                    place,
+                    voiced,
+                    secondary: secondary(),
+                    length,
+                },
+                Phone::Click {
+                    place,
+                    lateral: false,
+                },
+                Phone::Click {
+                    place,
+                    lateral: true,
+                },
+            ]);
+        }
+
+        for height in Height::ALL {
+            for backness in Backness::ALL {
+                for rounded in [false, true] {
+                    phones.push(Phone::Vowel {
+                        height,
+                        backness,
+                        rounded,
+                        length: Length::Short,
+                        nasalized: false,
+                    });
+                }
+            }
+        }
+
+        phones
     }
 
-    fn aspiration<'a>(phone: &'a str, attachments: &HashSet<ObstruentAttachment>) -> Cow<'a, str> {
-        if attachments.contains(&ObstruentAttachment::Preaspirated) {
-            Cow::Owned(branner_to_ipa("h^") + phone)
-        } else if attachments.contains(&ObstruentAttachment::Aspirated) {
-            Cow::Owned(phone.to_string() + &branner_to_ipa("h^"))
-        } else {
-            Cow::Borrowed(phone)
+    pub fn attachments(&self) -> Option<&HashSet<ObstruentAttachment>> {
+        match self {
+            Phone::Plosive { attachments, .. }
+            | Phone::Affricative { attachments, .. }
+            | Phone::Fricative { attachments, .. }
+            | Phone::LateralFricative { attachments, .. } => Some(attachments),
+            _ => None,
         }
     }
-}
 
-impl fmt::Display for Phone {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let phone = match self {
-            Phone::Plosive {
-                place,
-                voiced,
-                attachments,
-            } => {
-                let mut phone = Self::get_base(PhoneType::Plosive, place, *voiced, attachments);
+    pub fn attachments_mut(&mut self) -> Option<&mut HashSet<ObstruentAttachment>> {
+        match self {
+            Phone::Plosive { attachments, .. }
+            | Phone::Affricative { attachments, .. }
+            | Phone::Fricative { attachments, .. }
+            | Phone::LateralFricative { attachments, .. } => Some(attachments),
+            _ => None,
+        }
+    }
 
-                if attachments.contains(&ObstruentAttachment::Breathy) {
-                    phone += &branner_to_ipa(r#"h")"#);
-                }
+    pub fn secondary(&self) -> Option<&HashSet<SecondaryArticulation>> {
+        match self {
+            Phone::Plosive { secondary, .. }
+            | Phone::Affricative { secondary, .. }
+            | Phone::Fricative { secondary, .. }
+            | Phone::LateralFricative { secondary, .. }
+            | Phone::Nasal { secondary, .. }
+            | Phone::Trill { secondary, .. }
+            | Phone::Tap { secondary, .. }
+            | Phone::Approximant { secondary, .. }
+            | Phone::LateralApproximant { secondary, .. }
+            | Phone::Implosive { secondary, .. } => Some(secondary),
+            Phone::Click { .. } | Phone::Vowel { .. } => None,
+        }
+    }
 
-                if attachments.contains(&ObstruentAttachment::Creaky) {
-                    phone += &branner_to_ipa("~");
-                }
+    pub fn secondary_mut(&mut self) -> Option<&mut HashSet<SecondaryArticulation>> {
+        match self {
+            Phone::Plosive { secondary, .. }
+            | Phone::Affricative { secondary, .. }
+            | Phone::Fricative { secondary, .. }
+            | Phone::LateralFricative { secondary, .. }
+            | Phone::Nasal { secondary, .. }
+            | Phone::Trill { secondary, .. }
+            | Phone::Tap { secondary, .. }
+            | Phone::Approximant { secondary, .. }
+            | Phone::LateralApproximant { secondary, .. }
+            | Phone::Implosive { secondary, .. } => Some(secondary),
+            Phone::Click { .. } | Phone::Vowel { .. } => None,
+        }
+    }
 
-                if attachments.contains(&ObstruentAttachment::Ejective) {
-                    phone += &branner_to_ipa("`");
-                }
+    pub fn length(&self) -> Length {
+        match self {
+            Phone::Plosive { length, .. }
+            | Phone::Affricative { length, .. }
+            | Phone::Fricative { length, .. }
+            | Phone::LateralFricative { length, .. }
+            | Phone::Nasal { length, .. }
+            | Phone::Trill { length, .. }
+            | Phone::Tap { length, .. }
+            | Phone::Approximant { length, .. }
+            | Phone::LateralApproximant { length, .. }
+            | Phone::Implosive { length, .. }
+            | Phone::Vowel { length, .. } => *length,
+            Phone::Click { .. } => Length::Short,
+        }
+    }
 
-                phone = Self::aspiration(&phone, attachments).to_string();
+    pub fn length_mut(&mut self) -> Option<&mut Length> {
+        match self {
+            Phone::Plosive { length, .. }
+            | Phone::Affricative { length, .. }
+            | Phone::Fricative { length, .. }
+            | Phone::LateralFricative { length, .. }
+            | Phone::Nasal { length, .. }
+            | Phone::Trill { length, .. }
+            | Phone::Tap { length, .. }
+            | Phone::Approximant { length, .. }
+            | Phone::LateralApproximant { length, .. }
+            | Phone::Implosive { length, .. }
+            | Phone::Vowel { length, .. } => Some(length),
+            Phone::Click { .. } => None,
+        }
+    }
 
-                phone
-            }
+    /// Whether the phone is a syllabic consonant
+    pub fn syllabic(&self) -> bool {
+        match self {
+            Phone::Nasal { syllabic, .. }
+            | Phone::Trill { syllabic, .. }
+            | Phone::Approximant { syllabic, .. }
+            | Phone::LateralApproximant { syllabic, .. } => *syllabic,
+            _ => false,
+        }
+    }
+
+    pub fn syllabic_mut(&mut self) -> Option<&mut bool> {
+        match self {
+            Phone::Nasal { syllabic, .. }
+            | Phone::Trill { syllabic, .. }
+            | Phone::Approximant { syllabic, .. }
+            | Phone::LateralApproximant { syllabic, .. } => Some(syllabic),
+            _ => None,
+        }
+    }
+
+    /// Checks that the phone is articulable, i.e. that it is not
+    /// one of the shaded cells of the IPA chart.
+    pub fn validate(&self) -> Result<(), PhoneError> {
+        match self {
+            Phone::Plosive { place, voiced, .. } => Self::check(PhoneType::Plosive, place, *voiced),
             Phone::Affricative {
                 start_place,
                 end_place,
                 voiced,
-                attachments,
+                ..
             } => {
-                let mut start_phone =
-                    Self::get_base(PhoneType::Plosive, start_place, *voiced, attachments);
-                let mut end_phone =
-                    Self::get_base(PhoneType::Fricative, end_place, *voiced, attachments);
-
-                if attachments.contains(&ObstruentAttachment::Breathy) {
-                    start_phone += &branner_to_ipa(r#"h")"#);
-                    end_phone += &branner_to_ipa(r#"h")"#);
-                }
+                Self::check(PhoneType::Plosive, start_place, *voiced)?;
+                Self::check(PhoneType::Fricative, end_place, *voiced)
+            }
+            Phone::Fricative { place, voiced, .. } => {
+                Self::check(PhoneType::Fricative, place, *voiced)
+            }
+            Phone::LateralFricative { place, voiced, .. } => {
+                Self::check(PhoneType::LateralFricative, place, *voiced)
+            }
+            Phone::Nasal { place, voiced, .. } => Self::check(PhoneType::Nasal, place, *voiced),
+            Phone::Trill { place, voiced, .. } => Self::check(PhoneType::Trill, place, *voiced),
+            Phone::Tap { place, voiced, .. } => Self::check(PhoneType::Tap, place, *voiced),
+            Phone::Approximant { place, voiced, .. } => {
+                Self::check(PhoneType::Approximant, place, *voiced)
+            }
+            Phone::LateralApproximant { place, voiced, .. } => {
+                Self::check(PhoneType::LateralApproximant, place, *voiced)
+            }
+            Phone::Implosive { place, voiced, .. } => {
+                Self::check(PhoneType::Implosive, place, *voiced)
+            }
+            Phone::Click { place, lateral } => match (place, lateral) {
+                (
+                    Place::Bilabial
+                    | Place::Dental
+                    | Place::Alveolar
+                    | Place::Postalveolar
+                    | Place::Palatal,
+                    false,
+                )
+                | (Place::Alveolar, true) => Ok(()),
+                _ => Err(PhoneError::ImpossiblePlace {
+                    manner: if *lateral { "lateral click" } else { "click" },
+                    place: *place,
+                }),
+            },
+            Phone::Vowel { .. } => Ok(()),
+        }?;
 
-                if attachments.contains(&ObstruentAttachment::Creaky) {
-                    start_phone += &branner_to_ipa("~");
-                    end_phone += &branner_to_ipa("~");
-                }
+        self.check_attachments()?;
+        self.check_secondary()
+    }
 
-                let mut phone = branner_to_ipa(&format!("{start_phone})){end_phone}"));
+    /// Releases other than aspiration and ejection need a full closure
+    fn check_attachments(&self) -> Result<(), PhoneError> {
+        let manner = match self {
+            Phone::Affricative { .. } => "affricative",
+            Phone::Fricative { .. } => "fricative",
+            Phone::LateralFricative { .. } => "lateral fricative",
+            _ => return Ok(()),
+        };
 
-                if attachments.contains(&ObstruentAttachment::Ejective) {
-                    phone += &branner_to_ipa("`");
-                }
+        let release = self.attachments().into_iter().flatten().find(|attachment| {
+            matches!(
+                attachment,
+                ObstruentAttachment::NasalRelease
+                    | ObstruentAttachment::LateralRelease
+                    | ObstruentAttachment::Unreleased
+            )
+        });
+
+        match release {
+            Some(attachment) => Err(PhoneError::UnsupportedAttachment {
+                manner,
+                attachment: *attachment,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// A secondary articulation cannot be at the primary place
+    fn check_secondary(&self) -> Result<(), PhoneError> {
+        let (manner, place) = match self {
+            Phone::Plosive { place, .. } => (PhoneType::Plosive.name(), place),
+            Phone::Affricative { end_place, .. } => ("affricative", end_place),
+            Phone::Fricative { place, .. } => (PhoneType::Fricative.name(), place),
+            Phone::LateralFricative { place, .. } => (PhoneType::LateralFricative.name(), place),
+            Phone::Nasal { place, .. } => (PhoneType::Nasal.name(), place),
+            Phone::Trill { place, .. } => (PhoneType::Trill.name(), place),
+            Phone::Tap { place, .. } => (PhoneType::Tap.name(), place),
+            Phone::Approximant { place, .. } => (PhoneType::Approximant.name(), place),
+            Phone::LateralApproximant { place, .. } => {
+                (PhoneType::LateralApproximant.name(), place)
+            }
+            Phone::Implosive { place, .. } => (PhoneType::Implosive.name(), place),
+            Phone::Click { .. } | Phone::Vowel { .. } => return Ok(()),
+        };
 
-                phone = Self::aspiration(&phone, attachments).to_string();
+        let redundant = self.secondary().into_iter().flatten().find(|secondary| {
+            matches!(
+                (secondary, place),
+                (SecondaryArticulation::Palatalized, Place::Palatal)
+                    | (SecondaryArticulation::Velarized, Place::Velar)
+                    | (SecondaryArticulation::Pharyngealized, Place::Pharyngeal)
+            )
+        });
 
-                phone
+        match redundant {
+            Some(secondary) => Err(PhoneError::RedundantSecondary {
+                manner,
+                place: *place,
+                secondary: *secondary,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn check(pt: PhoneType, place: &Place, voiced: bool) -> Result<(), PhoneError> {
+        let impossible = match pt {
+            PhoneType::Plosive => matches!(place, Place::Pharyngeal),
+            PhoneType::Nasal | PhoneType::Implosive => {
+                matches!(place, Place::Pharyngeal | Place::Glottal)
             }
+            PhoneType::Trill => matches!(place, Place::Palatal | Place::Velar | Place::Glottal),
+            PhoneType::Tap => matches!(place, Place::Velar | Place::Glottal),
+            PhoneType::Fricative | PhoneType::Approximant => false,
+            PhoneType::LateralFricative | PhoneType::LateralApproximant => matches!(
+                place,
+                Place::Bilabial | Place::Labiodental | Place::Pharyngeal | Place::Glottal
+            ),
+        };
+
+        if impossible {
+            Err(PhoneError::ImpossiblePlace {
+                manner: pt.name(),
+                place: *place,
+            })
+        } else if voiced && matches!((pt, place), (PhoneType::Plosive, Place::Glottal)) {
+            Err(PhoneError::ImpossibleVoicing {
+                manner: pt.name(),
+                place: *place,
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Backness, Height, Length, ObstruentAttachment, Phone, PhoneError, Phoneme, Place,
+        SecondaryArticulation, Suprasegmentals,
+    };
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashSet;
+
+    #[test]
+    fn impossible_articulations_are_rejected() {
+        assert!(matches!(
+            Phone::Nasal {
+                place: Place::Pharyngeal,
+                voiced: true,
+                secondary: HashSet::new(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .validate(),
+            Err(PhoneError::ImpossiblePlace { .. })
+        ));
+        assert!(matches!(
+            Phone::LateralApproximant {
+                place: Place::Bilabial,
+                voiced: true,
+                secondary: HashSet::new(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .validate(),
+            Err(PhoneError::ImpossiblePlace { .. })
+        ));
+        assert!(matches!(
+            Phone::Plosive {
+                place: Place::Glottal,
+                voiced: true,
+                attachments: HashSet::new(),
+                secondary: HashSet::new(),
+                length: Length::Short,
+            }
+            .validate(),
+            Err(PhoneError::ImpossibleVoicing { .. })
+        ));
+        assert!(matches!(
+            Phone::Click {
+                place: Place::Velar,
+                lateral: false
+            }
+            .validate(),
+            Err(PhoneError::ImpossiblePlace { .. })
+        ));
+        assert!(Phone::Trill {
+            place: Place::Uvular,
+            voiced: true,
+            secondary: HashSet::new(),
+            length: Length::Short,
+            syllabic: false,
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn impossible_modifications_are_rejected() {
+        assert!(matches!(
             Phone::Fricative {
-                place: _,
-                voiced: _,
-                attachments: _,
-            } => todo!(),
-            Phone::Vowel {} => todo!(),
+                place: Place::Alveolar,
+                voiced: false,
+                attachments: [ObstruentAttachment::Unreleased].into(),
+                secondary: HashSet::new(),
+                length: Length::Short,
+            }
+            .validate(),
+            Err(PhoneError::UnsupportedAttachment { .. })
+        ));
+        assert!(matches!(
+            Phone::Nasal {
+                place: Place::Palatal,
+                voiced: true,
+                secondary: [SecondaryArticulation::Palatalized].into(),
+                length: Length::Short,
+                syllabic: false,
+            }
+            .validate(),
+            Err(PhoneError::RedundantSecondary { .. })
+        ));
+        assert!(Phone::Plosive {
+            place: Place::Velar,
+            voiced: false,
+            attachments: [ObstruentAttachment::Unreleased].into(),
+            secondary: [SecondaryArticulation::Labialized].into(),
+            length: Length::Long,
+        }
+        .validate()
+        .is_ok());
+    }
+
+    #[test]
+    fn vowel_phoneme_serializes() {
+        let phoneme = Phoneme {
+            ortho: "ö".to_string(),
+            primary: Phone::Vowel {
+                height: Height::CloseMid,
+                backness: Backness::Front,
+                rounded: true,
+                length: Length::HalfLong,
+                nasalized: false,
+            },
+            allo: vec![],
+            suprasegmentals: Suprasegmentals::default(),
         };
 
-        write!(f, "{}", phone)
+        assert_yaml_snapshot!(phoneme);
     }
 }
@@ -0,0 +1,208 @@
+//! Splits, mergers and loss of inherited phonemes
+//!
+//! Inherited phonemes are keyed by id, so a language can only override one
+//! proto phoneme with one of its own. Phoneme changes say what becomes of
+//! proto phonemes that don't survive one-to-one, and the epoch records each
+//! of them as a correspondence.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Context, Phoneme, PhonemeSet};
+
+/// A daughter phoneme, and the environment it appears in
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SplitReflex {
+    pub phoneme: Uuid,
+    /// Empty on both sides for the elsewhere case
+    #[serde(default)]
+    pub before: Vec<Context>,
+    #[serde(default)]
+    pub after: Vec<Context>,
+}
+
+/// What becomes of proto phonemes in a language
+///
+/// Proto phonemes are given by id. Daughter phonemes are the language's own,
+/// or a changed proto phoneme that lives on, like the elsewhere case of a
+/// split.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum PhonemeChange {
+    Split {
+        source: Uuid,
+        into: Vec<SplitReflex>,
+    },
+    Merger {
+        sources: Vec<Uuid>,
+        into: Uuid,
+    },
+    Loss {
+        source: Uuid,
+    },
+}
+
+impl PhonemeChange {
+    pub fn sources(&self) -> Vec<Uuid> {
+        match self {
+            PhonemeChange::Split { source, .. } | PhonemeChange::Loss { source } => vec![*source],
+            PhonemeChange::Merger { sources, .. } => sources.clone(),
+        }
+    }
+
+    /// Daughter phonemes with their environments, which are empty for a
+    /// merger
+    pub fn reflexes(&self) -> Vec<SplitReflex> {
+        match self {
+            PhonemeChange::Split { into, .. } => into.clone(),
+            PhonemeChange::Merger { into, .. } => vec![SplitReflex {
+                phoneme: *into,
+                before: vec![],
+                after: vec![],
+            }],
+            PhonemeChange::Loss { .. } => vec![],
+        }
+    }
+}
+
+/// A proto phoneme that did not survive one-to-one, and what it became
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Correspondence {
+    /// The ancestor the proto phoneme came from
+    pub ancestor: String,
+    pub proto: Uuid,
+    /// Daughter phonemes and where they appear, none if it was lost
+    pub reflexes: Vec<SplitReflex>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhonemeChangeError {
+    #[error("no ancestor has the phoneme {0}")]
+    UnknownSource(Uuid),
+    #[error("the phoneme {0} is neither the language's nor changed")]
+    UnknownReflex(Uuid),
+    #[error("the phoneme {0} is changed more than once")]
+    ChangedTwice(Uuid),
+    #[error("a split needs at least two phonemes")]
+    SplitIntoOne,
+    #[error("there is no phoneme set named {0}")]
+    UnknownSet(String),
+}
+
+/// Checks that phoneme changes refer to inherited and own phonemes and to
+/// existing phoneme sets, and change each proto phoneme at most once
+pub fn validate_phoneme_changes(
+    changes: &[PhonemeChange],
+    inherited: &HashMap<Uuid, Phoneme>,
+    own: &HashMap<Uuid, Phoneme>,
+    sets: &HashMap<String, PhonemeSet>,
+) -> Result<(), PhonemeChangeError> {
+    let mut changed = HashSet::new();
+
+    for change in changes {
+        if let PhonemeChange::Split { into, .. } = change {
+            if into.len() < 2 {
+                return Err(PhonemeChangeError::SplitIntoOne);
+            }
+        }
+
+        let sources = change.sources();
+        for source in &sources {
+            if !inherited.contains_key(source) {
+                return Err(PhonemeChangeError::UnknownSource(*source));
+            }
+            if !changed.insert(*source) {
+                return Err(PhonemeChangeError::ChangedTwice(*source));
+            }
+        }
+
+        for reflex in change.reflexes() {
+            if !own.contains_key(&reflex.phoneme) && !sources.contains(&reflex.phoneme) {
+                return Err(PhonemeChangeError::UnknownReflex(reflex.phoneme));
+            }
+
+            for context in reflex.before.iter().chain(&reflex.after) {
+                match context {
+                    Context::Set(name) if !sets.contains_key(name) => {
+                        return Err(PhonemeChangeError::UnknownSet(name.clone()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes changed proto phonemes from the inherited ones, and returns the
+/// correspondences
+///
+/// Proto phonemes that are among their own reflexes are kept.
+pub fn apply_phoneme_changes(
+    changes: &[PhonemeChange],
+    inherited: &mut HashMap<Uuid, Phoneme>,
+    ancestor_of: impl Fn(Uuid) -> Option<String>,
+) -> Vec<Correspondence> {
+    let mut correspondences = vec![];
+
+    for change in changes {
+        let reflexes = change.reflexes();
+
+        for source in change.sources() {
+            if !reflexes.iter().any(|reflex| reflex.phoneme == source) {
+                inherited.remove(&source);
+            }
+
+            if let Some(ancestor) = ancestor_of(source) {
+                correspondences.push(Correspondence {
+                    ancestor,
+                    proto: source,
+                    reflexes: reflexes.clone(),
+                });
+            }
+        }
+    }
+
+    correspondences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_phoneme_changes, PhonemeChange, PhonemeChangeError, SplitReflex};
+    use crate::data::{fixtures::phoneme, Context, PhonemeSet};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn split_environments_need_known_sets() {
+        let [k, c] = [0, 1].map(Uuid::from_u128);
+        let inherited = HashMap::from([(k, phoneme("k"))]);
+        let own = HashMap::from([(c, phoneme("tʃ"))]);
+        let split = |set: &str| {
+            vec![PhonemeChange::Split {
+                source: k,
+                into: vec![
+                    SplitReflex {
+                        phoneme: c,
+                        before: vec![],
+                        after: vec![Context::Set(set.to_string())],
+                    },
+                    SplitReflex {
+                        phoneme: k,
+                        before: vec![],
+                        after: vec![],
+                    },
+                ],
+            }]
+        };
+        let sets = HashMap::from([("front".to_string(), PhonemeSet::default())]);
+
+        assert!(validate_phoneme_changes(&split("front"), &inherited, &own, &sets).is_ok());
+        assert!(matches!(
+            validate_phoneme_changes(&split("back"), &inherited, &own, &sets),
+            Err(PhonemeChangeError::UnknownSet(name)) if name == "back"
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Phone, Phoneme};
+
+/// What a member phoneme produces for a property
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum CellValue {
+    /// The phoneme is not produced at all
+    Null,
+    /// Index into the phoneme's allophones
+    Allophone(usize),
+}
+
+/// Member Allophone Property Mapping, from (property name, phoneme
+/// orthography) to what the phoneme produces
+pub type Mapm = HashMap<(String, String), CellValue>;
+
+/// A set of phonemes (consonants, vowels, nasals, ...) used by phonotactics
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct PhonemeSet {
+    /// Orthographies of the member phonemes
+    pub members: Vec<String>,
+    pub properties: Vec<String>,
+    #[serde(with = "mapm")]
+    pub mapm: Mapm,
+    /// Properties whose production is the same phone for every member
+    #[serde(default)]
+    pub additional: HashMap<String, Phone>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhonemeSetError {
+    #[error("cannot create a phoneme set with a blank name")]
+    EmptyName,
+    #[error("the phoneme set named {0} already exists")]
+    Exists(String),
+}
+
+/// Adds an empty phoneme set named `name`
+pub fn create_phoneme_set(
+    sets: &mut HashMap<String, PhonemeSet>,
+    name: String,
+) -> Result<(), PhonemeSetError> {
+    if name.trim().is_empty() {
+        return Err(PhonemeSetError::EmptyName);
+    }
+
+    if sets.contains_key(&name) {
+        return Err(PhonemeSetError::Exists(name));
+    }
+
+    sets.insert(name, PhonemeSet::default());
+
+    Ok(())
+}
+
+impl PhonemeSet {
+    /// The full production set of a member phoneme, from property name to
+    /// the phone produced, or `None` for null production
+    ///
+    /// Properties without a cell produce the primary phone. Invalid
+    /// allophone indices are logged, and also produce the primary phone.
+    /// Returns `None` if `member` is not a member of the set, or has no
+    /// phoneme in `phonemes`.
+    pub fn productions(
+        &self,
+        phonemes: &HashMap<Uuid, Phoneme>,
+        member: &str,
+    ) -> Option<HashMap<String, Option<Phone>>> {
+        if !self.members.iter().any(|m| m == member) {
+            return None;
+        }
+
+        let phoneme = phonemes.values().find(|phoneme| phoneme.ortho == member)?;
+
+        let mapped = self.properties.iter().map(|property| {
+            let production = match self.mapm.get(&(property.clone(), member.to_string())) {
+                Some(CellValue::Null) => None,
+                Some(CellValue::Allophone(index)) => match phoneme.allo.get(*index) {
+                    Some(allophone) => Some(allophone.clone()),
+                    None => {
+                        log::warn!(
+                            "phoneme {member} has no allophone {index} for property {property}, using its primary phone"
+                        );
+                        Some(phoneme.primary.clone())
+                    }
+                },
+                None => Some(phoneme.primary.clone()),
+            };
+
+            (property.clone(), production)
+        });
+        let additional = self
+            .additional
+            .iter()
+            .map(|(property, phone)| (property.clone(), Some(phone.clone())));
+
+        Some(mapped.chain(additional).collect())
+    }
+}
+
+/// JSON maps need string keys, so the MAPM is stored as a list of cells
+mod mapm {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{CellValue, Mapm};
+
+    #[derive(Serialize, Deserialize)]
+    struct Cell {
+        property: String,
+        phoneme: String,
+        value: CellValue,
+    }
+
+    pub fn serialize<S>(mapm: &Mapm, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut cells: Vec<_> = mapm
+            .iter()
+            .map(|((property, phoneme), value)| Cell {
+                property: property.clone(),
+                phoneme: phoneme.clone(),
+                value: *value,
+            })
+            .collect();
+        cells.sort_by(|a, b| (&a.property, &a.phoneme).cmp(&(&b.property, &b.phoneme)));
+
+        cells.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Mapm, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let cells = Vec::<Cell>::deserialize(deserializer)?;
+
+        Ok(cells
+            .into_iter()
+            .map(|cell| ((cell.property, cell.phoneme), cell.value))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_phoneme_set, CellValue, PhonemeSet, PhonemeSetError};
+    use crate::data::fixtures::{self, phone};
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn plosives() -> PhonemeSet {
+        PhonemeSet {
+            members: vec!["p".to_string(), "t".to_string()],
+            properties: vec!["onset".to_string(), "coda".to_string()],
+            mapm: HashMap::from([
+                (
+                    ("onset".to_string(), "t".to_string()),
+                    CellValue::Allophone(0),
+                ),
+                (
+                    ("coda".to_string(), "t".to_string()),
+                    CellValue::Allophone(5),
+                ),
+                (("coda".to_string(), "p".to_string()), CellValue::Null),
+            ]),
+            additional: HashMap::from([("pause".to_string(), phone("ʔ"))]),
+        }
+    }
+
+    #[test]
+    fn resolves_productions() {
+        let phonemes = fixtures::phonemes(&[("p", "p", &[]), ("t", "t", &["tʰ"])]);
+        let set = plosives();
+
+        let p = set.productions(&phonemes, "p").unwrap();
+        assert_eq!(
+            p["onset"],
+            Some(phonemes[&Uuid::from_u128(0)].primary.clone())
+        );
+        assert_eq!(p["coda"], None);
+        assert_eq!(p["pause"].as_ref().unwrap().to_string(), "ʔ");
+
+        let t = set.productions(&phonemes, "t").unwrap();
+        assert_eq!(t["onset"].as_ref().unwrap().to_string(), "tʰ");
+        // Invalid allophone indices fall back to the primary phone
+        assert_eq!(t["coda"].as_ref().unwrap().to_string(), "t");
+
+        assert_eq!(set.productions(&phonemes, "k"), None);
+    }
+
+    #[test]
+    fn phoneme_set_serializes() {
+        let set = plosives();
+        let json = serde_json::to_string(&set).unwrap();
+
+        assert_yaml_snapshot!(set);
+        assert_eq!(serde_json::from_str::<PhonemeSet>(&json).unwrap(), set);
+    }
+
+    #[test]
+    fn phoneme_sets_need_unique_names() {
+        let mut sets = HashMap::new();
+
+        assert!(create_phoneme_set(&mut sets, "stops".to_string()).is_ok());
+        assert!(matches!(
+            create_phoneme_set(&mut sets, "stops".to_string()),
+            Err(PhonemeSetError::Exists(_))
+        ));
+        assert!(matches!(
+            create_phoneme_set(&mut sets, " ".to_string()),
+            Err(PhonemeSetError::EmptyName)
+        ));
+    }
+}
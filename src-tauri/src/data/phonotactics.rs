@@ -0,0 +1,380 @@
+//! Phonotactic validation of candidate words
+//!
+//! A word is checked against the syllable template, the clusters allowed in
+//! onsets and codas, and restrictions on where phonemes may appear. Every
+//! problem is reported as a [`Violation`] spanning phoneme positions.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{
+    ParsedSyllable, Phoneme, PhonemeSet, SyllabificationError, SyllableTemplate,
+    SyllableTemplateError, Target,
+};
+
+/// Where in a word a phoneme appears
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum WordPosition {
+    WordInitial,
+    WordFinal,
+    Onset,
+    Nucleus,
+    Coda,
+}
+
+/// A phoneme, or member of a phoneme set, that cannot appear at a position,
+/// e.g. no /ŋ/ word-initially
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Restriction {
+    pub target: Target,
+    pub position: WordPosition,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct Phonotactics {
+    /// Clusters of phonemes allowed in onsets, or any cluster if `None`
+    #[serde(default)]
+    pub onset_clusters: Option<Vec<Vec<String>>>,
+    /// Clusters of phonemes allowed in codas, or any cluster if `None`
+    #[serde(default)]
+    pub coda_clusters: Option<Vec<Vec<String>>>,
+    #[serde(default)]
+    pub restrictions: Vec<Restriction>,
+}
+
+/// A word to validate
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum CandidateWord {
+    /// Written text, split into the longest phoneme orthographies
+    Orthography(String),
+    /// Phoneme orthographies
+    Phonemes(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum ViolationKind {
+    UnknownPhoneme(String),
+    /// The syllable template does not fit the rest of the word
+    Unsyllabifiable,
+    ForbiddenOnset(Vec<String>),
+    ForbiddenCoda(Vec<String>),
+    Restricted {
+        phoneme: String,
+        position: WordPosition,
+    },
+}
+
+/// A violation of the phonemes from `start` up to `end`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Violation {
+    pub start: usize,
+    pub end: usize,
+    pub kind: ViolationKind,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Validation {
+    /// The word as phoneme orthographies, which violations index into
+    pub phonemes: Vec<String>,
+    /// The syllables of the word, if it could be syllabified
+    pub syllables: Option<Vec<ParsedSyllable>>,
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PhonotacticsError {
+    #[error(transparent)]
+    Template(#[from] SyllableTemplateError),
+    #[error("there is no phoneme set named {0}")]
+    UnknownSet(String),
+}
+
+/// Splits text into phoneme orthographies, longest first
+///
+/// Text that is not a phoneme becomes one phoneme per character, so it can
+/// be reported. Whitespace is skipped.
+pub fn split_orthography(phonemes: &HashMap<Uuid, Phoneme>, text: &str) -> Vec<String> {
+    let mut orthos: Vec<&str> = phonemes
+        .values()
+        .map(|phoneme| phoneme.ortho.as_str())
+        .filter(|ortho| !ortho.is_empty())
+        .collect();
+    orthos.sort_by_key(|ortho| std::cmp::Reverse(ortho.len()));
+
+    let mut split = vec![];
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+
+        let ortho = orthos
+            .iter()
+            .find(|ortho| rest.starts_with(**ortho))
+            .map_or(&rest[..c.len_utf8()], |ortho| *ortho);
+        split.push(ortho.to_string());
+        rest = &rest[ortho.len()..];
+    }
+
+    split
+}
+
+impl Phonotactics {
+    /// Checks that every phoneme set the restrictions use exists
+    pub fn validate(&self, sets: &HashMap<String, PhonemeSet>) -> Result<(), PhonotacticsError> {
+        for restriction in &self.restrictions {
+            if let Target::Set(name) = &restriction.target {
+                if !sets.contains_key(name) {
+                    return Err(PhonotacticsError::UnknownSet(name.clone()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a cluster is allowed, where single phonemes always are
+    fn allows<S: AsRef<str>>(clusters: &Option<Vec<Vec<String>>>, cluster: &[S]) -> bool {
+        match clusters {
+            Some(clusters) if cluster.len() > 1 => clusters.iter().any(|allowed| {
+                allowed.len() == cluster.len()
+                    && allowed.iter().zip(cluster).all(|(a, b)| a == b.as_ref())
+            }),
+            _ => true,
+        }
+    }
+
+    /// Validates a word against the phonotactics and the syllable template
+    ///
+    /// Without a template, only word-initial and word-final restrictions are
+    /// checked.
+    pub fn check(
+        &self,
+        phonemes: &HashMap<Uuid, Phoneme>,
+        sets: &HashMap<String, PhonemeSet>,
+        template: Option<&SyllableTemplate>,
+        word: &CandidateWord,
+    ) -> Result<Validation, PhonotacticsError> {
+        self.validate(sets)?;
+
+        let word = match word {
+            CandidateWord::Orthography(text) => split_orthography(phonemes, text),
+            CandidateWord::Phonemes(word) => word.clone(),
+        };
+        let mut violations: Vec<Violation> = word
+            .iter()
+            .enumerate()
+            .filter(|(_, ortho)| !phonemes.values().any(|phoneme| phoneme.ortho == **ortho))
+            .map(|(i, ortho)| Violation {
+                start: i,
+                end: i + 1,
+                kind: ViolationKind::UnknownPhoneme(ortho.clone()),
+            })
+            .collect();
+
+        let syllables = match template {
+            // Syllabify around forbidden clusters where possible, so they
+            // are only reported if they cannot be avoided
+            Some(template) if violations.is_empty() => match template
+                .syllabify_where(sets, &word, |onset, coda| {
+                    Self::allows(&self.onset_clusters, onset)
+                        && Self::allows(&self.coda_clusters, coda)
+                })
+                .or_else(|_| template.syllabify(sets, &word))
+            {
+                Ok(syllables) => Some(syllables),
+                Err(SyllabificationError::Unsyllabifiable { position, .. }) => {
+                    violations.push(Violation {
+                        start: position,
+                        end: word.len(),
+                        kind: ViolationKind::Unsyllabifiable,
+                    });
+                    None
+                }
+                Err(SyllabificationError::Template(e)) => return Err(e.into()),
+                Err(SyllabificationError::NoTemplate) => None,
+            },
+            _ => None,
+        };
+
+        // Every position of every phoneme, by index
+        let mut positions: Vec<(usize, WordPosition)> = vec![];
+        if let Some(last) = word.len().checked_sub(1) {
+            positions.push((0, WordPosition::WordInitial));
+            positions.push((last, WordPosition::WordFinal));
+        }
+
+        let mut start = 0;
+        for syllable in syllables.iter().flatten() {
+            let nucleus = start + syllable.onset.len();
+            let end = nucleus + 1 + syllable.coda.len();
+
+            if !Self::allows(&self.onset_clusters, &syllable.onset) {
+                violations.push(Violation {
+                    start,
+                    end: nucleus,
+                    kind: ViolationKind::ForbiddenOnset(syllable.onset.clone()),
+                });
+            }
+            if !Self::allows(&self.coda_clusters, &syllable.coda) {
+                violations.push(Violation {
+                    start: nucleus + 1,
+                    end,
+                    kind: ViolationKind::ForbiddenCoda(syllable.coda.clone()),
+                });
+            }
+
+            positions.extend((start..nucleus).map(|i| (i, WordPosition::Onset)));
+            positions.push((nucleus, WordPosition::Nucleus));
+            positions.extend((nucleus + 1..end).map(|i| (i, WordPosition::Coda)));
+            start = end;
+        }
+
+        for (i, position) in positions {
+            let ortho = &word[i];
+            let restricted = self.restrictions.iter().any(|restriction| {
+                restriction.position == position
+                    && match &restriction.target {
+                        Target::Phoneme(target) => target == ortho,
+                        Target::Set(name) => sets[name].members.contains(ortho),
+                    }
+            });
+
+            if restricted {
+                violations.push(Violation {
+                    start: i,
+                    end: i + 1,
+                    kind: ViolationKind::Restricted {
+                        phoneme: ortho.clone(),
+                        position,
+                    },
+                });
+            }
+        }
+
+        violations.sort_by_key(|violation| (violation.start, violation.end));
+
+        Ok(Validation {
+            phonemes: word,
+            syllables,
+            violations,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        split_orthography, CandidateWord, Phonotactics, Restriction, ViolationKind, WordPosition,
+    };
+    use crate::data::{fixtures, Phoneme, PhonemeSet, SyllableTemplate, Target};
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn phonemes() -> HashMap<Uuid, Phoneme> {
+        fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("t", "t", &[]),
+            ("s", "s", &[]),
+            ("ng", "ŋ", &[]),
+            ("a", "a", &[]),
+            ("i", "i", &[]),
+        ])
+    }
+
+    fn sets() -> HashMap<String, PhonemeSet> {
+        let set = |members: &[&str]| PhonemeSet {
+            members: members.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+
+        HashMap::from([
+            ("c".to_string(), set(&["p", "t", "s", "ng"])),
+            ("v".to_string(), set(&["a", "i"])),
+        ])
+    }
+
+    fn template() -> SyllableTemplate {
+        SyllableTemplate {
+            pattern: "(C)(C)V(C)".to_string(),
+            sets: HashMap::from([('C', "c".to_string()), ('V', "v".to_string())]),
+            nucleus: 'V',
+            principle: Default::default(),
+        }
+    }
+
+    fn phonotactics() -> Phonotactics {
+        Phonotactics {
+            onset_clusters: Some(vec![vec!["s".to_string(), "t".to_string()]]),
+            coda_clusters: None,
+            restrictions: vec![Restriction {
+                target: Target::Phoneme("ng".to_string()),
+                position: WordPosition::WordInitial,
+            }],
+        }
+    }
+
+    fn check(word: &str) -> Vec<ViolationKind> {
+        phonotactics()
+            .check(
+                &phonemes(),
+                &sets(),
+                Some(&template()),
+                &CandidateWord::Orthography(word.to_string()),
+            )
+            .unwrap()
+            .violations
+            .into_iter()
+            .map(|violation| violation.kind)
+            .collect()
+    }
+
+    #[test]
+    fn splits_longest_orthographies() {
+        assert_eq!(
+            split_orthography(&phonemes(), "sanga"),
+            ["s", "a", "ng", "a"]
+        );
+        assert_eq!(
+            split_orthography(&phonemes(), "pa xa"),
+            ["p", "a", "x", "a"]
+        );
+    }
+
+    #[test]
+    fn accepts_valid_words() {
+        assert_eq!(check("stapa"), []);
+        assert_eq!(check("sangit"), []);
+    }
+
+    #[test]
+    fn reports_violations() {
+        assert_eq!(
+            check("ngapta"),
+            [ViolationKind::Restricted {
+                phoneme: "ng".to_string(),
+                position: WordPosition::WordInitial,
+            }]
+        );
+        assert_eq!(
+            check("tsapa"),
+            [ViolationKind::ForbiddenOnset(vec![
+                "t".to_string(),
+                "s".to_string()
+            ])]
+        );
+        assert_eq!(check("patttta"), [ViolationKind::Unsyllabifiable]);
+        assert_yaml_snapshot!(phonotactics()
+            .check(
+                &phonemes(),
+                &sets(),
+                Some(&template()),
+                &CandidateWord::Phonemes(vec!["ng".to_string(), "x".to_string()]),
+            )
+            .unwrap());
+    }
+}
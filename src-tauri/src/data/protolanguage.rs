@@ -3,13 +3,41 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use super::{Language, Phoneme};
+use super::{
+    create_phoneme_set, natural_class, realize, CandidateWord, Correspondence, EnvironmentError,
+    EnvironmentRule, FeatureMatrix, Generated, GeneratorError, GeneratorSettings, InventoryChart,
+    Language, ParsedSyllable, Phoneme, PhonemeSet, PhonemeSetError, Phonotactics,
+    PhonotacticsError, Prosody, Realization, StressError, StressedWord, SyllabificationError,
+    SyllableTemplate, Tone, Validation,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Protolanguage {
     pub name: String,
     pub phonemes: HashMap<Uuid, Phoneme>,
     pub description: Option<serde_json::Value>,
+    /// Protolanguage names that this protolanguage is descended from
+    #[serde(default)]
+    pub ancestors: Vec<String>,
+    #[serde(default)]
+    pub tones: Vec<Tone>,
+    /// Phoneme sets by name, such as consonants or vowels
+    #[serde(default)]
+    pub phoneme_sets: HashMap<String, PhonemeSet>,
+    #[serde(default)]
+    pub syllable_template: Option<SyllableTemplate>,
+    /// Stress rules and syllable weight
+    #[serde(default)]
+    pub prosody: Option<Prosody>,
+    /// Ordered rules that choose allophones from their environment
+    #[serde(default)]
+    pub environment_rules: Vec<EnvironmentRule>,
+    /// Allowed clusters and positional restrictions
+    #[serde(default)]
+    pub phonotactics: Phonotactics,
+    /// Phonemes of the ancestors that split, merged or were lost
+    #[serde(default)]
+    pub correspondences: Vec<Correspondence>,
 }
 
 impl Default for Protolanguage {
@@ -18,6 +46,14 @@ impl Default for Protolanguage {
             name: "unnamed".to_string(),
             phonemes: HashMap::new(),
             description: None,
+            ancestors: vec![],
+            tones: vec![],
+            phoneme_sets: HashMap::new(),
+            syllable_template: None,
+            prosody: None,
+            environment_rules: vec![],
+            phonotactics: Phonotactics::default(),
+            correspondences: vec![],
         }
     }
 }
@@ -26,6 +62,74 @@ impl Protolanguage {
     pub fn description(&self) -> Option<&serde_json::Value> {
         self.description.as_ref()
     }
+
+    /// Phonemes whose primary phone belongs to the natural class
+    pub fn natural_class(&self, class: &FeatureMatrix) -> HashMap<Uuid, Phoneme> {
+        natural_class(&self.phonemes, class)
+            .map(|(id, phoneme)| (*id, phoneme.clone()))
+            .collect()
+    }
+
+    pub fn create_phoneme_set(&mut self, name: String) -> Result<(), PhonemeSetError> {
+        create_phoneme_set(&mut self.phoneme_sets, name)
+    }
+
+    /// Splits phonemes, by orthography, into syllables of the syllable template
+    pub fn syllabify<S: AsRef<str>>(
+        &self,
+        phonemes: &[S],
+    ) -> Result<Vec<ParsedSyllable>, SyllabificationError> {
+        self.syllable_template
+            .as_ref()
+            .ok_or(SyllabificationError::NoTemplate)?
+            .syllabify(&self.phoneme_sets, phonemes)
+    }
+
+    /// Stresses syllables by the protolanguage's stress rules
+    pub fn stress(&self, syllables: &[ParsedSyllable]) -> Result<StressedWord, StressError> {
+        self.prosody
+            .as_ref()
+            .ok_or(StressError::NoProsody)?
+            .stress(&self.phonemes, syllables)
+    }
+
+    /// Realizes phonemes, by orthography, with the environment rules
+    pub fn realize<S: AsRef<str>>(&self, phonemes: &[S]) -> Result<Realization, EnvironmentError> {
+        realize(
+            &self.environment_rules,
+            &self.phonemes,
+            &self.phoneme_sets,
+            phonemes,
+        )
+    }
+
+    /// Checks a word against the phonotactics and syllable template
+    pub fn validate_word(&self, word: &CandidateWord) -> Result<Validation, PhonotacticsError> {
+        self.phonotactics.check(
+            &self.phonemes,
+            &self.phoneme_sets,
+            self.syllable_template.as_ref(),
+            word,
+        )
+    }
+
+    /// Generates random words from the phonotactics
+    pub fn generate_words(
+        &self,
+        settings: &GeneratorSettings,
+    ) -> Result<Generated, GeneratorError> {
+        settings.generate(
+            &self.phonemes,
+            &self.phoneme_sets,
+            self.syllable_template.as_ref(),
+            &self.phonotactics,
+        )
+    }
+
+    /// The phoneme inventory laid out as IPA charts
+    pub fn chart(&self) -> InventoryChart {
+        InventoryChart::new(&self.phonemes)
+    }
 }
 
 /// Important for "epochs", as languages can only have an "ancestry"
@@ -36,6 +140,14 @@ impl From<Language> for Protolanguage {
             name: value.name,
             phonemes: value.phonemes,
             description: value.description,
+            ancestors: vec![],
+            tones: value.tones,
+            phoneme_sets: value.phoneme_sets,
+            syllable_template: value.syllable_template,
+            prosody: value.prosody,
+            environment_rules: value.environment_rules,
+            phonotactics: value.phonotactics,
+            correspondences: vec![],
         }
     }
 }
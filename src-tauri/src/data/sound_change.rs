@@ -0,0 +1,582 @@
+//! Sound changes, written in the style of a sound change applier
+//!
+//! Every line is a category, a rule, a comment starting with `;`, or blank:
+//!
+//! ```text
+//! V = a e i o u
+//! p > f / V_V
+//! [-voice, -continuant] > [+voice] / V_(C)#
+//! ə > ∅ / _#
+//! ```
+//!
+//! Categories are single uppercase letters standing for a list of phones.
+//! Rules rewrite a target to a replacement in an environment, where `_` is
+//! the target, `#` a word boundary, and parentheses are optional. Targets
+//! and replacements are phones, categories or feature matrices, and `∅` (or
+//! `0`) is nothing, which deletes or inserts phones. A category in the
+//! replacement picks the member at the same index as the target category's,
+//! and a feature matrix changes the features of the target phone.
+
+use std::collections::HashMap;
+
+use super::{parse_ipa, Feature, FeatureMatrix, Phone, Phoneme};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Element {
+    Phone(Phone),
+    Category(char),
+    Features(FeatureMatrix),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Pattern {
+    Element(Element),
+    Boundary,
+    Optional(Vec<Pattern>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct SoundChange {
+    /// The rule as written
+    source: String,
+    target: Vec<Element>,
+    replacement: Vec<Element>,
+    /// The environment before the target, from the target outwards
+    before: Vec<Pattern>,
+    after: Vec<Pattern>,
+}
+
+/// Parsed sound changes, applied in order
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub struct SoundChanges {
+    categories: HashMap<char, Vec<Phone>>,
+    rules: Vec<SoundChange>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum SoundChangeErrorKind {
+    #[error("expected a rule like `a > b / c_d` or a category like `V = a e i`")]
+    Malformed,
+    #[error("the environment needs exactly one `_`")]
+    Underscore,
+    #[error("unbalanced {0}")]
+    Unbalanced(char),
+    #[error("there is no category {0}")]
+    UnknownCategory(char),
+    #[error("there is no feature named {0}")]
+    UnknownFeature(String),
+    #[error("cannot read {0} as IPA")]
+    InvalidIpa(String),
+    #[error("{0} can only appear in environments")]
+    OnlyInEnvironment(char),
+    #[error(
+        "categories and feature matrices in the replacement need a target at the same position"
+    )]
+    NoTargetToChange,
+    #[error("inserting phones needs an environment")]
+    InsertionWithoutEnvironment,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("line {line}: {kind}")]
+pub struct SoundChangeError {
+    /// Counting from 1
+    pub line: usize,
+    pub kind: SoundChangeErrorKind,
+}
+
+fn is_null(text: &str) -> bool {
+    matches!(text.trim(), "∅" | "0")
+}
+
+/// Whether a character ends a run of IPA
+fn is_special(c: char) -> bool {
+    c.is_whitespace() || c.is_ascii_uppercase() || "#_()[]∅".contains(c)
+}
+
+fn parse_matrix(text: &str) -> Result<FeatureMatrix, SoundChangeErrorKind> {
+    let mut matrix = FeatureMatrix::default();
+    let mut current: Option<(bool, String)> = None;
+
+    let mut finish = |current: Option<(bool, String)>| match current {
+        Some((value, name)) => {
+            let feature = Feature::from_name(&name)
+                .ok_or_else(|| SoundChangeErrorKind::UnknownFeature(name.trim().to_string()))?;
+            matrix.set(feature, value);
+            Ok(())
+        }
+        None => Ok(()),
+    };
+
+    for c in text.chars() {
+        match (c, &mut current) {
+            ('+' | '-', _) => finish(current.replace((c == '+', String::new())))?,
+            (',', _) => finish(current.take())?,
+            (c, Some((_, name))) => name.push(c),
+            (c, None) if c.is_whitespace() => {}
+            (c, None) => return Err(SoundChangeErrorKind::UnknownFeature(c.to_string())),
+        }
+    }
+    finish(current)?;
+
+    Ok(matrix)
+}
+
+impl SoundChanges {
+    /// Parses sound changes, one per line
+    pub fn parse(source: &str) -> Result<SoundChanges, SoundChangeError> {
+        let mut changes = SoundChanges::default();
+
+        for (i, line) in source.lines().enumerate() {
+            let error = |kind| SoundChangeError { line: i + 1, kind };
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some((name, members)) = line.split_once('=') {
+                let name = match name.trim().chars().collect::<Vec<_>>()[..] {
+                    [c] if c.is_ascii_uppercase() => c,
+                    _ => return Err(error(SoundChangeErrorKind::Malformed)),
+                };
+                let members = members
+                    .split_whitespace()
+                    .map(|member| {
+                        parse_one(member)
+                            .ok_or_else(|| SoundChangeErrorKind::InvalidIpa(member.to_string()))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map_err(error)?;
+
+                changes.categories.insert(name, members);
+            } else {
+                let rule = changes.parse_rule(line).map_err(error)?;
+                changes.rules.push(rule);
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn parse_rule(&self, line: &str) -> Result<SoundChange, SoundChangeErrorKind> {
+        let (target, rest) = line
+            .split_once('>')
+            .ok_or(SoundChangeErrorKind::Malformed)?;
+        let (replacement, environment) = match rest.split_once('/') {
+            Some((replacement, environment)) => (replacement, Some(environment)),
+            None => (rest, None),
+        };
+
+        let elements = |text: &str| -> Result<Vec<Element>, SoundChangeErrorKind> {
+            if is_null(text) {
+                return Ok(vec![]);
+            }
+
+            self.parse_patterns(text)?
+                .into_iter()
+                .map(|pattern| match pattern {
+                    Pattern::Element(element) => Ok(element),
+                    Pattern::Boundary => Err(SoundChangeErrorKind::OnlyInEnvironment('#')),
+                    Pattern::Optional(_) => Err(SoundChangeErrorKind::OnlyInEnvironment('(')),
+                })
+                .collect()
+        };
+        let target = elements(target)?;
+        let replacement = elements(replacement)?;
+
+        let changes_nothing = |(i, element): (usize, &Element)| match element {
+            Element::Phone(_) => false,
+            Element::Category(_) => !matches!(target.get(i), Some(Element::Category(_))),
+            Element::Features(_) => target.get(i).is_none(),
+        };
+        if replacement.iter().enumerate().any(changes_nothing) {
+            return Err(SoundChangeErrorKind::NoTargetToChange);
+        }
+
+        let (before, after) = match environment {
+            Some(environment) => {
+                let (before, after) = environment
+                    .split_once('_')
+                    .ok_or(SoundChangeErrorKind::Underscore)?;
+                if after.contains('_') {
+                    return Err(SoundChangeErrorKind::Underscore);
+                }
+
+                (
+                    reversed(self.parse_patterns(before)?),
+                    self.parse_patterns(after)?,
+                )
+            }
+            None => (vec![], vec![]),
+        };
+
+        if target.is_empty() && before.is_empty() && after.is_empty() {
+            return Err(SoundChangeErrorKind::InsertionWithoutEnvironment);
+        }
+
+        Ok(SoundChange {
+            source: line.to_string(),
+            target,
+            replacement,
+            before,
+            after,
+        })
+    }
+
+    fn parse_patterns(&self, text: &str) -> Result<Vec<Pattern>, SoundChangeErrorKind> {
+        // Patterns of every open group, innermost last
+        let mut groups: Vec<Vec<Pattern>> = vec![vec![]];
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            let mut consumed = c.len_utf8();
+            let pattern = match c {
+                c if c.is_whitespace() => None,
+                '#' => Some(Pattern::Boundary),
+                '(' => {
+                    groups.push(vec![]);
+                    None
+                }
+                ')' => {
+                    let group = groups.pop().filter(|_| !groups.is_empty());
+                    Some(Pattern::Optional(
+                        group.ok_or(SoundChangeErrorKind::Unbalanced(')'))?,
+                    ))
+                }
+                '[' => {
+                    let end = rest
+                        .find(']')
+                        .ok_or(SoundChangeErrorKind::Unbalanced('['))?;
+                    consumed = end + 1;
+                    Some(Pattern::Element(Element::Features(parse_matrix(
+                        &rest[1..end],
+                    )?)))
+                }
+                ']' => return Err(SoundChangeErrorKind::Unbalanced(']')),
+                '_' | '∅' => return Err(SoundChangeErrorKind::Malformed),
+                c if c.is_ascii_uppercase() => {
+                    if !self.categories.contains_key(&c) {
+                        return Err(SoundChangeErrorKind::UnknownCategory(c));
+                    }
+                    Some(Pattern::Element(Element::Category(c)))
+                }
+                _ => {
+                    let run = rest.find(is_special).unwrap_or(rest.len());
+                    consumed = run;
+
+                    let parsed = parse_ipa(&rest[..run]);
+                    if !parsed.diagnostics.is_empty() || parsed.phones.is_empty() {
+                        return Err(SoundChangeErrorKind::InvalidIpa(rest[..run].to_string()));
+                    }
+                    groups.last_mut().unwrap().extend(
+                        parsed
+                            .phones
+                            .into_iter()
+                            .map(|phone| Pattern::Element(Element::Phone(phone))),
+                    );
+                    None
+                }
+            };
+
+            if let Some(pattern) = pattern {
+                groups.last_mut().unwrap().push(pattern);
+            }
+            rest = &rest[consumed..];
+        }
+
+        match &groups[..] {
+            [patterns] => Ok(patterns.clone()),
+            _ => Err(SoundChangeErrorKind::Unbalanced('(')),
+        }
+    }
+}
+
+impl SoundChanges {
+    fn is(&self, element: &Element, phone: &Phone) -> bool {
+        match element {
+            Element::Phone(target) => target == phone,
+            Element::Category(c) => self.categories[c].contains(phone),
+            Element::Features(matrix) => phone.features().matches(matrix),
+        }
+    }
+
+    /// Whether patterns match the word from `position` on, moving by `step`
+    ///
+    /// Boundaries are just outside of the word, at `-1` and its length.
+    fn walk(&self, patterns: &[Pattern], word: &[Phone], position: isize, step: isize) -> bool {
+        let phone = usize::try_from(position).ok().and_then(|i| word.get(i));
+
+        match patterns.split_first() {
+            None => true,
+            Some((Pattern::Boundary, rest)) => {
+                let edge = position == -1 || position == word.len() as isize;
+                edge && self.walk(rest, word, position + step, step)
+            }
+            Some((Pattern::Element(element), rest)) => {
+                phone.is_some_and(|phone| self.is(element, phone))
+                    && self.walk(rest, word, position + step, step)
+            }
+            Some((Pattern::Optional(group), rest)) => {
+                let taken: Vec<Pattern> = group.iter().chain(rest).cloned().collect();
+                self.walk(&taken, word, position, step) || self.walk(rest, word, position, step)
+            }
+        }
+    }
+
+    /// Whether the rule's environment holds around `word[start..end]`
+    fn in_environment(&self, rule: &SoundChange, word: &[Phone], start: usize, end: usize) -> bool {
+        self.walk(&rule.before, word, start as isize - 1, -1)
+            && self.walk(&rule.after, word, end as isize, 1)
+    }
+
+    fn replace(&self, rule: &SoundChange, matched: &[Phone]) -> Vec<Phone> {
+        rule.replacement
+            .iter()
+            .enumerate()
+            .map(|(i, element)| match element {
+                Element::Phone(phone) => phone.clone(),
+                Element::Category(c) => {
+                    let Some(Element::Category(from)) = rule.target.get(i) else {
+                        unreachable!("parsed replacement categories have target categories")
+                    };
+                    let index = self.categories[from]
+                        .iter()
+                        .position(|phone| *phone == matched[i]);
+
+                    index
+                        .and_then(|index| self.categories[c].get(index))
+                        .unwrap_or(&matched[i])
+                        .clone()
+                }
+                Element::Features(matrix) => {
+                    let mut features = matched[i].features();
+                    for (feature, value) in &matrix.0 {
+                        features.set(*feature, *value);
+                    }
+
+                    Phone::from_features(&features).unwrap_or_else(|| {
+                        log::warn!(
+                            "no phone is {matched} with {matrix}, so `{}` keeps it",
+                            rule.source,
+                            matched = matched[i]
+                        );
+                        matched[i].clone()
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Applies a rule everywhere in a word at once, from left to right
+    fn apply_rule(&self, rule: &SoundChange, word: &[Phone]) -> Vec<Phone> {
+        let mut changed = vec![];
+
+        if rule.target.is_empty() {
+            for gap in 0..=word.len() {
+                if self.in_environment(rule, word, gap, gap) {
+                    changed.extend(self.replace(rule, &[]));
+                }
+                changed.extend(word.get(gap).cloned());
+            }
+
+            return changed;
+        }
+
+        let mut i = 0;
+        while i < word.len() {
+            let end = i + rule.target.len();
+            let matches = end <= word.len()
+                && rule
+                    .target
+                    .iter()
+                    .zip(&word[i..end])
+                    .all(|(element, phone)| self.is(element, phone))
+                && self.in_environment(rule, word, i, end);
+
+            if matches {
+                changed.extend(self.replace(rule, &word[i..end]));
+                i = end;
+            } else {
+                changed.push(word[i].clone());
+                i += 1;
+            }
+        }
+
+        changed
+    }
+
+    /// The rules as written, in order
+    pub fn sources(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.source.as_str()).collect()
+    }
+
+    /// Applies the rule at `index` to a form
+    pub fn apply_nth(&self, index: usize, word: &[Phone]) -> Vec<Phone> {
+        self.apply_rule(&self.rules[index], word)
+    }
+
+    /// Applies every rule in order to a form
+    pub fn apply(&self, word: &[Phone]) -> Vec<Phone> {
+        self.rules
+            .iter()
+            .fold(word.to_vec(), |word, rule| self.apply_rule(rule, &word))
+    }
+
+    /// Applies the rules to a phoneme of the inventory
+    ///
+    /// Rules without an environment change the phoneme's phones. Rules with
+    /// one only apply in some words, so what they change the primary phone
+    /// into becomes an allophone instead. Phones the rules would delete or
+    /// split into several are kept as they are.
+    pub fn apply_to_phoneme(&self, phoneme: &mut Phoneme) {
+        let single = |phones: Vec<Phone>| match <[Phone; 1]>::try_from(phones) {
+            Ok([phone]) => Some(phone),
+            Err(_) => None,
+        };
+
+        for rule in &self.rules {
+            if rule.before.is_empty() && rule.after.is_empty() {
+                for phone in std::iter::once(&mut phoneme.primary).chain(&mut phoneme.allo) {
+                    if let Some(changed) =
+                        single(self.apply_rule(rule, std::slice::from_ref(phone)))
+                    {
+                        *phone = changed;
+                    }
+                }
+            } else {
+                let unconditioned = SoundChange {
+                    before: vec![],
+                    after: vec![],
+                    ..rule.clone()
+                };
+                let changed =
+                    single(self.apply_rule(&unconditioned, std::slice::from_ref(&phoneme.primary)));
+
+                if let Some(changed) = changed {
+                    if changed != phoneme.primary && !phoneme.allo.contains(&changed) {
+                        phoneme.allo.push(changed);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Exactly one phone, from IPA
+fn parse_one(text: &str) -> Option<Phone> {
+    let mut parsed = parse_ipa(text);
+
+    match parsed.phones.len() {
+        1 if parsed.diagnostics.is_empty() => parsed.phones.pop(),
+        _ => None,
+    }
+}
+
+/// Reverses patterns, and the insides of their groups
+fn reversed(patterns: Vec<Pattern>) -> Vec<Pattern> {
+    patterns
+        .into_iter()
+        .rev()
+        .map(|pattern| match pattern {
+            Pattern::Optional(group) => Pattern::Optional(reversed(group)),
+            pattern => pattern,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SoundChangeErrorKind, SoundChanges};
+    use crate::data::{
+        fixtures::{phone, phoneme},
+        parse_ipa, Phone, Phoneme,
+    };
+
+    fn apply(changes: &str, word: &str) -> String {
+        let changes = SoundChanges::parse(changes).unwrap();
+
+        changes
+            .apply(&parse_ipa(word).phones)
+            .iter()
+            .map(Phone::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn applies_conditioned_changes() {
+        let lenition = "V = a e i o u\np > f / V_V";
+
+        assert_eq!(apply(lenition, "papapa"), "pafafa");
+        assert_eq!(apply(lenition, "appa"), "appa");
+        // Simultaneous application: the environment is the original word
+        assert_eq!(apply("a > e / _a", "aaa"), "eea");
+    }
+
+    #[test]
+    fn applies_categories_and_features() {
+        let categories = "S = p t k\nZ = b d g\nS > Z / #_";
+        assert_eq!(apply(categories, "tapa"), "dapa");
+
+        let voicing = "V = a i\n[-voice, -continuant] > [+voice] / V_V";
+        assert_eq!(apply(voicing, "atika"), "adiɡa");
+    }
+
+    #[test]
+    fn handles_boundaries_optionals_insertion_and_deletion() {
+        let changes = "; final devoicing, with or without a final consonant\n\
+                       C = t n\n\
+                       b > p / _(C)#\n\
+                       ə > ∅ / _#\n\
+                       ∅ > ə / #_s";
+
+        assert_eq!(apply(changes, "stabə"), "əstab");
+        assert_eq!(apply(changes, "tab"), "tap");
+        assert_eq!(apply(changes, "tabt"), "tapt");
+        assert_eq!(apply(changes, "tabtt"), "tabtt");
+    }
+
+    #[test]
+    fn changes_inherited_phonemes() {
+        let changes = SoundChanges::parse("V = a\nk > x\np > f / V_V").unwrap();
+        let mut k = Phoneme {
+            allo: vec![phone("kʰ")],
+            ..phoneme("k")
+        };
+        let mut p = phoneme("p");
+
+        changes.apply_to_phoneme(&mut k);
+        changes.apply_to_phoneme(&mut p);
+
+        assert_eq!(k.primary.to_string(), "x");
+        assert_eq!(k.allo[0].to_string(), "kʰ");
+        // Conditioned changes only add allophones
+        assert_eq!(p.primary.to_string(), "p");
+        assert_eq!(p.allo[0].to_string(), "f");
+    }
+
+    #[test]
+    fn reports_errors_by_line() {
+        let error = |changes: &str| SoundChanges::parse(changes).unwrap_err();
+
+        let unknown = error("V = a\n\np > f / V_N");
+        assert_eq!(unknown.line, 3);
+        assert_eq!(unknown.kind, SoundChangeErrorKind::UnknownCategory('N'));
+        assert_eq!(error("p > f / a_a_").kind, SoundChangeErrorKind::Underscore);
+        assert_eq!(
+            error("[+voice, +loud] > p").kind,
+            SoundChangeErrorKind::UnknownFeature("loud".to_string())
+        );
+        assert_eq!(
+            error("p > f / (a_").kind,
+            SoundChangeErrorKind::Unbalanced('(')
+        );
+        assert_eq!(
+            error("p > t [+voice]").kind,
+            SoundChangeErrorKind::NoTargetToChange
+        );
+        assert_eq!(
+            error("∅ > ə").kind,
+            SoundChangeErrorKind::InsertionWithoutEnvironment
+        );
+    }
+}
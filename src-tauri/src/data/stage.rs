@@ -0,0 +1,133 @@
+//! The history of a language group, one stage per epoch
+//!
+//! Stages are numbered from the oldest, and the group's current
+//! protolanguages come after the last stage of its history.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{LanguageGroup, Phoneme, Protolanguage};
+
+/// Ancestors of protolanguages, by name
+pub type Ancestry = HashMap<String, Vec<String>>;
+
+/// The protolanguages of a past epoch
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Stage {
+    pub name: String,
+    pub protolangs: Vec<Protolanguage>,
+    /// Ancestors of each protolanguage in the previous stage, by name
+    #[serde(default)]
+    pub ancestry: Ancestry,
+}
+
+/// A phoneme that was added, removed or changed between two stages
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct PhonemeDiff {
+    pub id: Uuid,
+    pub before: Option<Phoneme>,
+    pub after: Option<Phoneme>,
+}
+
+/// How a protolanguage differs from its ancestors in an earlier stage
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct LanguageDiff {
+    pub name: String,
+    pub ancestors: Vec<String>,
+    pub phonemes: Vec<PhonemeDiff>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StageError {
+    #[error("there is no stage {0}")]
+    UnknownStage(usize),
+    #[error("stage {from} does not come before stage {to}")]
+    Order { from: usize, to: usize },
+}
+
+impl LanguageGroup {
+    /// The protolanguages of a stage, and their ancestry in the one before
+    ///
+    /// The stage after the last one of the history is the current one.
+    pub fn stage(&self, index: usize) -> Option<(&[Protolanguage], &Ancestry)> {
+        if index == self.history.len() {
+            Some((&self.protolangs, &self.ancestry))
+        } else {
+            self.history
+                .get(index)
+                .map(|stage| (stage.protolangs.as_slice(), &stage.ancestry))
+        }
+    }
+
+    pub fn rename_stage(&mut self, index: usize, name: String) -> Result<(), StageError> {
+        let stage = self
+            .history
+            .get_mut(index)
+            .ok_or(StageError::UnknownStage(index))?;
+        stage.name = name;
+
+        Ok(())
+    }
+
+    /// Compares every protolanguage of stage `to` with its ancestors in the
+    /// earlier stage `from`
+    pub fn diff_stages(&self, from: usize, to: usize) -> Result<Vec<LanguageDiff>, StageError> {
+        if from >= to {
+            return Err(StageError::Order { from, to });
+        }
+        let (old, _) = self.stage(from).ok_or(StageError::UnknownStage(from))?;
+        let (new, _) = self.stage(to).ok_or(StageError::UnknownStage(to))?;
+
+        let diffs = new
+            .iter()
+            .map(|lang| {
+                // Walk the ancestry back to `from`
+                let mut ancestors = vec![lang.name.clone()];
+                for index in (from + 1..=to).rev() {
+                    let (_, ancestry) = self.stage(index).unwrap();
+                    let mut seen = HashSet::new();
+
+                    ancestors = ancestors
+                        .iter()
+                        .flat_map(|name| ancestry.get(name).into_iter().flatten())
+                        .filter(|name| seen.insert(*name))
+                        .cloned()
+                        .collect();
+                }
+
+                // The first ancestor with a phoneme wins, as in an epoch
+                let before: HashMap<_, _> = ancestors
+                    .iter()
+                    .rev()
+                    .filter_map(|name| old.iter().find(|proto| &proto.name == name))
+                    .flat_map(|proto| &proto.phonemes)
+                    .map(|(id, phoneme)| (*id, phoneme))
+                    .collect();
+                let ids: BTreeMap<_, _> = before
+                    .keys()
+                    .chain(lang.phonemes.keys())
+                    .map(|id| (*id, (before.get(id).copied(), lang.phonemes.get(id))))
+                    .collect();
+                let phonemes = ids
+                    .into_iter()
+                    .filter(|(_, (before, after))| before != after)
+                    .map(|(id, (before, after))| PhonemeDiff {
+                        id,
+                        before: before.cloned(),
+                        after: after.cloned(),
+                    })
+                    .collect();
+
+                LanguageDiff {
+                    name: lang.name.clone(),
+                    ancestors,
+                    phonemes,
+                }
+            })
+            .collect();
+
+        Ok(diffs)
+    }
+}
@@ -0,0 +1,475 @@
+//! Stress assignment on syllabified words
+//!
+//! Stress is placed by a [`StressRule`] on syllables produced by the
+//! syllabifier. Weight-sensitive and foot-based rules count moras as set by
+//! [`SyllableWeight`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Length, ParsedSyllable, Phoneme, Stress, Suprasegmentals, Syllable};
+
+/// A syllable counted from one edge of the word
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum StressPosition {
+    Initial,
+    Peninitial,
+    Antepenultimate,
+    Penultimate,
+    Final,
+}
+
+impl StressPosition {
+    /// The syllable index in a word of `count` syllables, or the nearest
+    /// syllable if the word is too short
+    fn index(self, count: usize) -> usize {
+        let last = count.saturating_sub(1);
+
+        match self {
+            StressPosition::Initial => 0,
+            StressPosition::Peninitial => 1.min(last),
+            StressPosition::Antepenultimate => count.saturating_sub(3),
+            StressPosition::Penultimate => count.saturating_sub(2),
+            StressPosition::Final => last,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Edge {
+    Left,
+    Right,
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Foot {
+    /// Stressed then unstressed
+    Trochee,
+    /// Unstressed then stressed
+    Iamb,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum StressRule {
+    /// Always the same syllable
+    Fixed(StressPosition),
+    /// The heavy syllable nearest to an edge, e.g. Latin is a window of one
+    /// syllable after skipping one from the right, falling back to the
+    /// antepenultimate
+    WeightSensitive {
+        edge: Edge,
+        /// Syllables at the edge that never take stress (extrametricality)
+        skip: usize,
+        /// How many syllables after the skipped ones are considered, or
+        /// every one if `None`
+        window: Option<usize>,
+        /// Where stress goes without a heavy syllable in the window
+        fallback: StressPosition,
+    },
+    /// Binary feet built from one edge, with primary stress on the head of
+    /// the foot nearest the `main` edge and secondary stress on the others
+    Feet {
+        foot: Foot,
+        direction: Edge,
+        main: Edge,
+        /// Heavy syllables form feet on their own
+        quantity_sensitive: bool,
+    },
+    /// Stress marked on the phonemes of the word, e.g. a phoneme `á` with
+    /// primary stress, falling back to a fixed position
+    Lexical { fallback: StressPosition },
+}
+
+/// How moras are counted, and how many make a syllable heavy
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SyllableWeight {
+    /// Long and half-long nuclei count two moras
+    pub long_nucleus: bool,
+    /// Every coda consonant counts a mora (weight by position)
+    pub coda: bool,
+    /// Syllables with at least this many moras are heavy
+    pub heavy: usize,
+}
+
+impl Default for SyllableWeight {
+    fn default() -> Self {
+        Self {
+            long_nucleus: true,
+            coda: true,
+            heavy: 2,
+        }
+    }
+}
+
+/// Stress settings of a language's phonotactics
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Prosody {
+    pub rule: StressRule,
+    #[serde(default)]
+    pub weight: SyllableWeight,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StressError {
+    #[error("there are no stress rules")]
+    NoProsody,
+    #[error("there is no phoneme written {0}")]
+    UnknownPhoneme(String),
+}
+
+/// A stressed word, as syllables and as IPA
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct StressedWord {
+    pub syllables: Vec<Syllable>,
+    pub ipa: String,
+}
+
+/// Writes syllables with stress marks, separated by `.` where there is no
+/// stress mark
+pub fn render_syllables(syllables: &[Syllable]) -> String {
+    syllables
+        .iter()
+        .enumerate()
+        .map(|(i, syllable)| {
+            if i > 0 && syllable.suprasegmentals.stress == Stress::Unstressed {
+                format!(".{syllable}")
+            } else {
+                syllable.to_string()
+            }
+        })
+        .collect()
+}
+
+fn is_long(phoneme: &Phoneme) -> bool {
+    let long = |length| matches!(length, Length::HalfLong | Length::Long);
+
+    long(phoneme.primary.length()) || long(phoneme.suprasegmentals.length)
+}
+
+impl SyllableWeight {
+    pub fn moras(&self, syllable: &[&Phoneme], nucleus: usize) -> usize {
+        let long = usize::from(self.long_nucleus && is_long(syllable[nucleus]));
+        let coda = if self.coda {
+            syllable.len() - nucleus - 1
+        } else {
+            0
+        };
+
+        1 + long + coda
+    }
+}
+
+impl Prosody {
+    /// The stress of every syllable, from their weights and the phonemes'
+    /// own stress marks
+    fn assign(&self, heavy: &[bool], marked: &[Stress]) -> Vec<Stress> {
+        let count = heavy.len();
+        let mut stress = vec![Stress::Unstressed; count];
+        if count == 0 {
+            return stress;
+        }
+
+        let primary = match &self.rule {
+            StressRule::Fixed(position) => position.index(count),
+            StressRule::WeightSensitive {
+                edge,
+                skip,
+                window,
+                fallback,
+            } => {
+                let from_edge: Vec<usize> = match edge {
+                    Edge::Left => (0..count).collect(),
+                    Edge::Right => (0..count).rev().collect(),
+                };
+
+                from_edge
+                    .into_iter()
+                    .skip(*skip)
+                    .take(window.unwrap_or(count))
+                    .find(|i| heavy[*i])
+                    .unwrap_or_else(|| fallback.index(count))
+            }
+            StressRule::Feet {
+                foot,
+                direction,
+                main,
+                quantity_sensitive,
+            } => {
+                let heads = feet(heavy, *foot, *direction, *quantity_sensitive);
+                for head in &heads {
+                    stress[*head] = Stress::Secondary;
+                }
+
+                let nearest = match main {
+                    Edge::Left => heads.iter().min(),
+                    Edge::Right => heads.iter().max(),
+                };
+
+                match (nearest, main) {
+                    (Some(head), _) => *head,
+                    // Too short for a foot
+                    (None, Edge::Left) => 0,
+                    (None, Edge::Right) => count - 1,
+                }
+            }
+            StressRule::Lexical { fallback } => {
+                for (syllable, mark) in stress.iter_mut().zip(marked) {
+                    *syllable = *mark;
+                }
+
+                marked
+                    .iter()
+                    .position(|mark| *mark == Stress::Primary)
+                    .unwrap_or_else(|| fallback.index(count))
+            }
+        };
+
+        stress[primary] = Stress::Primary;
+        stress
+    }
+
+    /// Stresses syllables of phonemes, written by their orthography
+    pub fn stress(
+        &self,
+        phonemes: &HashMap<Uuid, Phoneme>,
+        syllables: &[ParsedSyllable],
+    ) -> Result<StressedWord, StressError> {
+        let lookup = |ortho: &String| {
+            phonemes
+                .values()
+                .find(|phoneme| phoneme.ortho == *ortho)
+                .ok_or_else(|| StressError::UnknownPhoneme(ortho.clone()))
+        };
+
+        let mut parts = vec![];
+        for syllable in syllables {
+            let onset = syllable
+                .onset
+                .iter()
+                .map(lookup)
+                .collect::<Result<Vec<_>, _>>()?;
+            let nucleus = lookup(&syllable.nucleus)?;
+            let coda = syllable
+                .coda
+                .iter()
+                .map(lookup)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            parts.push((onset, nucleus, coda));
+        }
+
+        let heavy: Vec<bool> = parts
+            .iter()
+            .map(|(onset, nucleus, coda)| {
+                let phonemes: Vec<&Phoneme> = onset
+                    .iter()
+                    .copied()
+                    .chain(std::iter::once(*nucleus))
+                    .chain(coda.iter().copied())
+                    .collect();
+
+                self.weight.moras(&phonemes, onset.len()) >= self.weight.heavy
+            })
+            .collect();
+        let marked: Vec<Stress> = parts
+            .iter()
+            .map(|(onset, nucleus, coda)| {
+                onset
+                    .iter()
+                    .chain(std::iter::once(nucleus))
+                    .chain(coda)
+                    .map(|phoneme| phoneme.suprasegmentals.stress)
+                    .max_by_key(|stress| *stress as u8)
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let syllables: Vec<Syllable> = parts
+            .iter()
+            .zip(self.assign(&heavy, &marked))
+            .map(|((onset, nucleus, coda), stress)| {
+                // The nucleus' length goes on its phone, not after the coda
+                let mut vowel = nucleus.primary.clone();
+                if let Some(length) = vowel.length_mut() {
+                    if nucleus.suprasegmentals.length != Length::Short {
+                        *length = nucleus.suprasegmentals.length;
+                    }
+                }
+
+                Syllable {
+                    phones: onset
+                        .iter()
+                        .map(|phoneme| phoneme.primary.clone())
+                        .chain(std::iter::once(vowel))
+                        .chain(coda.iter().map(|phoneme| phoneme.primary.clone()))
+                        .collect(),
+                    suprasegmentals: Suprasegmentals {
+                        tone: nucleus.suprasegmentals.tone.clone(),
+                        stress,
+                        length: Length::Short,
+                    },
+                }
+            })
+            .collect();
+
+        Ok(StressedWord {
+            ipa: render_syllables(&syllables),
+            syllables,
+        })
+    }
+}
+
+/// The heads of binary feet built from `direction`
+fn feet(heavy: &[bool], foot: Foot, direction: Edge, quantity_sensitive: bool) -> Vec<usize> {
+    let order: Vec<usize> = match direction {
+        Edge::Left => (0..heavy.len()).collect(),
+        Edge::Right => (0..heavy.len()).rev().collect(),
+    };
+    let heavy = |i: usize| quantity_sensitive && heavy[order[i]];
+
+    let mut heads = vec![];
+    let mut i = 0;
+    while i < order.len() {
+        if heavy(i) {
+            heads.push(order[i]);
+            i += 1;
+        } else if i + 1 < order.len() && !heavy(i + 1) {
+            let (left, right) = (order[i].min(order[i + 1]), order[i].max(order[i + 1]));
+            heads.push(match foot {
+                Foot::Trochee => left,
+                Foot::Iamb => right,
+            });
+            i += 2;
+        } else {
+            // Degenerate feet are not allowed, so the syllable is unfooted
+            i += 1;
+        }
+    }
+
+    heads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Edge, Foot, Prosody, StressPosition, StressRule, SyllableWeight};
+    use crate::data::{fixtures, ParsedSyllable, Phoneme, PhonemeSet, Stress, SyllableTemplate};
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    fn phonemes() -> HashMap<Uuid, Phoneme> {
+        let mut phonemes = fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("t", "t", &[]),
+            ("n", "n", &[]),
+            ("a", "a", &[]),
+            ("aː", "aː", &[]),
+            ("i", "i", &[]),
+            ("á", "a", &[]),
+        ]);
+        phonemes
+            .get_mut(&Uuid::from_u128(6))
+            .unwrap()
+            .suprasegmentals
+            .stress = Stress::Primary;
+
+        phonemes
+    }
+
+    /// Syllabifies a word of phonemes separated by spaces
+    fn syllables(word: &str) -> Vec<ParsedSyllable> {
+        let set = |members: &[&str]| PhonemeSet {
+            members: members.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+        let sets = HashMap::from([
+            ("c".to_string(), set(&["p", "t", "n"])),
+            ("v".to_string(), set(&["a", "aː", "i", "á"])),
+        ]);
+        let template = SyllableTemplate {
+            pattern: "(C)V(C)".to_string(),
+            sets: HashMap::from([('C', "c".to_string()), ('V', "v".to_string())]),
+            nucleus: 'V',
+            principle: Default::default(),
+        };
+        let phonemes: Vec<_> = word.split(' ').collect();
+
+        template.syllabify(&sets, &phonemes).unwrap()
+    }
+
+    fn stress(rule: StressRule, word: &str) -> String {
+        let prosody = Prosody {
+            rule,
+            weight: SyllableWeight::default(),
+        };
+
+        prosody.stress(&phonemes(), &syllables(word)).unwrap().ipa
+    }
+
+    #[test]
+    fn fixed_stress() {
+        let word = "p a t a n a";
+
+        assert_eq!(
+            stress(StressRule::Fixed(StressPosition::Initial), word),
+            "ˈpa.ta.na"
+        );
+        assert_eq!(
+            stress(StressRule::Fixed(StressPosition::Penultimate), word),
+            "paˈta.na"
+        );
+        assert_eq!(
+            stress(StressRule::Fixed(StressPosition::Final), word),
+            "pa.taˈna"
+        );
+        assert_eq!(
+            stress(StressRule::Fixed(StressPosition::Antepenultimate), "p a"),
+            "ˈpa"
+        );
+    }
+
+    #[test]
+    fn weight_sensitive_stress() {
+        // Latin: a heavy penult is stressed, otherwise the antepenult
+        let latin = || StressRule::WeightSensitive {
+            edge: Edge::Right,
+            skip: 1,
+            window: Some(1),
+            fallback: StressPosition::Antepenultimate,
+        };
+
+        assert_eq!(stress(latin(), "p a t a n a"), "ˈpa.ta.na");
+        assert_eq!(stress(latin(), "p a t a n t a"), "paˈtan.ta");
+        assert_eq!(stress(latin(), "p a t aː n a"), "paˈtaː.na");
+    }
+
+    #[test]
+    fn foot_based_stress() {
+        let trochees = StressRule::Feet {
+            foot: Foot::Trochee,
+            direction: Edge::Left,
+            main: Edge::Left,
+            quantity_sensitive: false,
+        };
+        let iambs = StressRule::Feet {
+            foot: Foot::Iamb,
+            direction: Edge::Right,
+            main: Edge::Right,
+            quantity_sensitive: true,
+        };
+
+        assert_eq!(stress(trochees, "p a t a n a p a t a"), "ˈpa.taˌna.pa.ta");
+        assert_eq!(stress(iambs, "p a t aː n a"), "paˈtaː.na");
+    }
+
+    #[test]
+    fn lexical_stress() {
+        let rule = || StressRule::Lexical {
+            fallback: StressPosition::Initial,
+        };
+
+        assert_eq!(stress(rule(), "p a t á n a"), "paˈta.na");
+        assert_eq!(stress(rule(), "p a t a"), "ˈpa.ta");
+    }
+}
@@ -0,0 +1,218 @@
+use core::fmt;
+use std::fmt::Formatter;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Length, Phone};
+
+/// Pitch levels, numbered 1 (lowest) to 5 (highest) as in Chao's notation
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ToneLevel {
+    ExtraLow,
+    Low,
+    Mid,
+    High,
+    ExtraHigh,
+}
+
+impl ToneLevel {
+    /// Every level, from lowest to highest
+    pub const ALL: [ToneLevel; 5] = [
+        ToneLevel::ExtraLow,
+        ToneLevel::Low,
+        ToneLevel::Mid,
+        ToneLevel::High,
+        ToneLevel::ExtraHigh,
+    ];
+
+    /// Reads a Chao tone number, from 1 to 5
+    pub fn from_chao(number: u8) -> Option<ToneLevel> {
+        ToneLevel::ALL
+            .get(usize::from(number).checked_sub(1)?)
+            .copied()
+    }
+
+    fn letter(self) -> char {
+        match self {
+            ToneLevel::ExtraLow => '˩',
+            ToneLevel::Low => '˨',
+            ToneLevel::Mid => '˧',
+            ToneLevel::High => '˦',
+            ToneLevel::ExtraHigh => '˥',
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Debug)]
+pub enum Tone {
+    Level(ToneLevel),
+    /// The levels the pitch moves through, e.g. mid then high for a rise
+    Contour(Vec<ToneLevel>),
+}
+
+impl Tone {
+    pub fn levels(&self) -> &[ToneLevel] {
+        match self {
+            Tone::Level(level) => std::slice::from_ref(level),
+            Tone::Contour(levels) => levels,
+        }
+    }
+
+    /// Reads a tone from Chao numerals, e.g. `3`, `35` or `214`
+    pub fn from_chao(numerals: &str) -> Option<Tone> {
+        let levels = numerals
+            .chars()
+            .map(|c| ToneLevel::from_chao(c.to_digit(10)? as u8))
+            .collect::<Option<Vec<_>>>()?;
+
+        match levels.as_slice() {
+            [] => None,
+            [level] => Some(Tone::Level(*level)),
+            _ => Some(Tone::Contour(levels)),
+        }
+    }
+}
+
+/// Tone letters, e.g. `˧˥` for a mid-high rise
+impl fmt::Display for Tone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let letters: String = self.levels().iter().map(|level| level.letter()).collect();
+
+        write!(f, "{letters}")
+    }
+}
+
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum Stress {
+    #[default]
+    Unstressed,
+    Secondary,
+    Primary,
+}
+
+impl Stress {
+    fn mark(self) -> &'static str {
+        match self {
+            Stress::Unstressed => "",
+            Stress::Secondary => "ˌ",
+            Stress::Primary => "ˈ",
+        }
+    }
+}
+
+/// Tone, stress and length of a phoneme or syllable
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct Suprasegmentals {
+    #[serde(default)]
+    pub tone: Option<Tone>,
+    #[serde(default)]
+    pub stress: Stress,
+    #[serde(default)]
+    pub length: Length,
+}
+
+impl Suprasegmentals {
+    /// Renders segments with the suprasegmentals in IPA order: stress before,
+    /// length then tone after
+    ///
+    /// Length is only added if the segments are not already marked long.
+    pub fn render(&self, segments: &str) -> String {
+        let length = if segments.ends_with(['ː', 'ˑ']) {
+            String::new()
+        } else {
+            self.length.to_string()
+        };
+        let tone = self.tone.as_ref().map(Tone::to_string).unwrap_or_default();
+
+        format!("{}{segments}{length}{tone}", self.stress.mark())
+    }
+}
+
+/// A syllable of phones with its suprasegmentals
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Syllable {
+    pub phones: Vec<Phone>,
+    #[serde(default)]
+    pub suprasegmentals: Suprasegmentals,
+}
+
+impl fmt::Display for Syllable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let segments: String = self.phones.iter().map(Phone::to_string).collect();
+
+        write!(f, "{}", self.suprasegmentals.render(&segments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stress, Suprasegmentals, Syllable, Tone, ToneLevel};
+    use crate::data::{Backness, Height, Length, Phone, Phoneme, Place};
+    use insta::assert_yaml_snapshot;
+    use std::collections::HashSet;
+
+    #[test]
+    fn tones_render_as_chao_letters() {
+        let rendered: Vec<_> = ["5", "1", "35", "214", "51"]
+            .into_iter()
+            .map(|numerals| format!("{numerals}: {}", Tone::from_chao(numerals).unwrap()))
+            .collect();
+
+        assert_yaml_snapshot!(rendered);
+        assert_eq!(Tone::from_chao("3"), Some(Tone::Level(ToneLevel::Mid)));
+        assert_eq!(Tone::from_chao(""), None);
+        assert_eq!(Tone::from_chao("36"), None);
+    }
+
+    #[test]
+    fn syllables_render_suprasegmentals_in_order() {
+        let syllable = Syllable {
+            phones: vec![
+                Phone::Nasal {
+                    place: Place::Bilabial,
+                    voiced: true,
+                    secondary: HashSet::new(),
+                    length: Length::Short,
+                    syllabic: false,
+                },
+                Phone::Vowel {
+                    height: Height::Open,
+                    backness: Backness::Front,
+                    rounded: false,
+                    length: Length::Short,
+                    nasalized: false,
+                },
+            ],
+            suprasegmentals: Suprasegmentals {
+                tone: Some(Tone::Contour(vec![ToneLevel::Mid, ToneLevel::ExtraHigh])),
+                stress: Stress::Primary,
+                length: Length::Long,
+            },
+        };
+
+        assert_eq!(syllable.to_string(), "ˈmaː˧˥");
+    }
+
+    #[test]
+    fn tonal_phoneme_serializes() {
+        let phoneme = Phoneme {
+            ortho: "á".to_string(),
+            primary: Phone::Vowel {
+                height: Height::Open,
+                backness: Backness::Front,
+                rounded: false,
+                length: Length::Long,
+                nasalized: false,
+            },
+            allo: vec![],
+            suprasegmentals: Suprasegmentals {
+                tone: Some(Tone::Level(ToneLevel::High)),
+                length: Length::Long,
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(phoneme.ipa(), "aː˦");
+        assert_yaml_snapshot!(phoneme);
+    }
+}
@@ -0,0 +1,445 @@
+//! Syllable structure templates and syllabification
+//!
+//! A template such as `(C)(C)V(C)` describes the syllables of a language.
+//! Every letter stands for a phoneme set, and parenthesized letters are
+//! optional as a group. Phoneme sequences, given as phoneme orthographies,
+//! are split into syllables that each match the template.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::PhonemeSet;
+
+/// Which syllabification wins when a sequence can be split several ways
+#[derive(Serialize, Deserialize, Hash, PartialEq, Eq, Copy, Clone, Debug, Default)]
+pub enum SyllabificationPrinciple {
+    /// Consonants between nuclei go to the following onset where possible
+    #[default]
+    MaximalOnset,
+    /// Consonants between nuclei go to the preceding coda where possible
+    MaximalCoda,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SyllableTemplate {
+    /// e.g. `(C)(C)V(C)`
+    pub pattern: String,
+    /// The phoneme set each letter of the pattern stands for
+    pub sets: HashMap<char, String>,
+    /// The letter of the syllable nucleus, which must appear exactly once
+    #[serde(default = "default_nucleus")]
+    pub nucleus: char,
+    #[serde(default)]
+    pub principle: SyllabificationPrinciple,
+}
+
+fn default_nucleus() -> char {
+    'V'
+}
+
+/// One or more letters of a template, optional as a whole
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Slot {
+    letters: Vec<char>,
+    optional: bool,
+}
+
+/// A template split around its nucleus
+struct Structure<'a> {
+    onset: Vec<Slot>,
+    nucleus: &'a PhonemeSet,
+    coda: Vec<Slot>,
+    sets: HashMap<char, &'a PhonemeSet>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyllableTemplateError {
+    #[error("unbalanced parenthesis at position {0}")]
+    UnbalancedParenthesis(usize),
+    #[error("empty optional group at position {0}")]
+    EmptyGroup(usize),
+    #[error("the nucleus {nucleus} appears {count} times outside of optional groups, not once")]
+    NucleusCount { nucleus: char, count: usize },
+    #[error("the letter {0} does not stand for a phoneme set")]
+    UnknownLetter(char),
+    #[error("there is no phoneme set named {0}")]
+    MissingSet(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SyllabificationError {
+    #[error("there is no syllable template")]
+    NoTemplate,
+    #[error(transparent)]
+    Template(#[from] SyllableTemplateError),
+    #[error("cannot syllabify {} from position {position}", .rest.join(" "))]
+    Unsyllabifiable { position: usize, rest: Vec<String> },
+}
+
+/// A syllable of phoneme orthographies
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ParsedSyllable {
+    pub onset: Vec<String>,
+    pub nucleus: String,
+    pub coda: Vec<String>,
+}
+
+impl SyllableTemplate {
+    fn slots(&self) -> Result<Vec<Slot>, SyllableTemplateError> {
+        let mut slots = vec![];
+        let mut group: Option<(usize, Vec<char>)> = None;
+
+        for (position, c) in self.pattern.chars().enumerate() {
+            match (c, &mut group) {
+                (c, _) if c.is_whitespace() => {}
+                ('(', None) => group = Some((position, vec![])),
+                ('(', Some(_)) | (')', None) => {
+                    return Err(SyllableTemplateError::UnbalancedParenthesis(position))
+                }
+                (')', Some((start, letters))) => {
+                    if letters.is_empty() {
+                        return Err(SyllableTemplateError::EmptyGroup(*start));
+                    }
+                    slots.push(Slot {
+                        letters: std::mem::take(letters),
+                        optional: true,
+                    });
+                    group = None;
+                }
+                (c, Some((_, letters))) => letters.push(c),
+                (c, None) => slots.push(Slot {
+                    letters: vec![c],
+                    optional: false,
+                }),
+            }
+        }
+
+        if let Some((start, _)) = group {
+            return Err(SyllableTemplateError::UnbalancedParenthesis(start));
+        }
+
+        Ok(slots)
+    }
+
+    fn structure<'a>(
+        &self,
+        phoneme_sets: &'a HashMap<String, PhonemeSet>,
+    ) -> Result<Structure<'a>, SyllableTemplateError> {
+        let mut slots = self.slots()?;
+
+        let mut sets = HashMap::new();
+        for letter in slots.iter().flat_map(|slot| &slot.letters) {
+            let name = self
+                .sets
+                .get(letter)
+                .ok_or(SyllableTemplateError::UnknownLetter(*letter))?;
+            let set = phoneme_sets
+                .get(name)
+                .ok_or_else(|| SyllableTemplateError::MissingSet(name.clone()))?;
+            sets.insert(*letter, set);
+        }
+
+        let nucleus = Slot {
+            letters: vec![self.nucleus],
+            optional: false,
+        };
+        let nuclei: Vec<_> = slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| **slot == nucleus)
+            .map(|(i, _)| i)
+            .collect();
+        let [index] = nuclei[..] else {
+            return Err(SyllableTemplateError::NucleusCount {
+                nucleus: self.nucleus,
+                count: nuclei.len(),
+            });
+        };
+
+        let coda = slots.split_off(index + 1);
+        slots.pop();
+
+        Ok(Structure {
+            onset: slots,
+            nucleus: sets[&self.nucleus],
+            coda,
+            sets,
+        })
+    }
+
+    /// Checks that the pattern is well-formed, and that all of its letters
+    /// stand for existing phoneme sets
+    pub fn validate(
+        &self,
+        phoneme_sets: &HashMap<String, PhonemeSet>,
+    ) -> Result<(), SyllableTemplateError> {
+        self.structure(phoneme_sets).map(|_| ())
+    }
+
+    /// Every sequence of letters the pattern allows, taking or leaving each
+    /// optional group
+    pub fn shapes(&self) -> Result<Vec<Vec<char>>, SyllableTemplateError> {
+        let mut shapes = vec![vec![]];
+
+        for slot in self.slots()? {
+            let with: Vec<Vec<char>> = shapes
+                .iter()
+                .map(|shape| shape.iter().chain(&slot.letters).copied().collect())
+                .collect();
+
+            if slot.optional {
+                shapes.extend(with);
+            } else {
+                shapes = with;
+            }
+        }
+
+        Ok(shapes)
+    }
+
+    /// Splits a sequence of phoneme orthographies into syllables
+    pub fn syllabify<S: AsRef<str>>(
+        &self,
+        phoneme_sets: &HashMap<String, PhonemeSet>,
+        phonemes: &[S],
+    ) -> Result<Vec<ParsedSyllable>, SyllabificationError> {
+        self.syllabify_where(phoneme_sets, phonemes, |_, _| true)
+    }
+
+    /// Splits phonemes into syllables whose onset and coda are `allowed`
+    pub fn syllabify_where<S: AsRef<str>>(
+        &self,
+        phoneme_sets: &HashMap<String, PhonemeSet>,
+        phonemes: &[S],
+        allowed: impl Fn(&[&str], &[&str]) -> bool,
+    ) -> Result<Vec<ParsedSyllable>, SyllabificationError> {
+        let structure = self.structure(phoneme_sets)?;
+        let phonemes: Vec<&str> = phonemes.iter().map(AsRef::as_ref).collect();
+
+        // The best split of every prefix, as the start of each syllable
+        // and the position of its nucleus
+        let mut best: Vec<Option<Vec<(usize, usize)>>> = vec![None; phonemes.len() + 1];
+        best[0] = Some(vec![]);
+
+        for end in 1..=phonemes.len() {
+            for start in 0..end {
+                let Some(before) = &best[start] else {
+                    continue;
+                };
+                let Some(nucleus) = structure.matches(&phonemes[start..end]) else {
+                    continue;
+                };
+                let syllable = &phonemes[start..end];
+                if !allowed(&syllable[..nucleus], &syllable[nucleus + 1..]) {
+                    continue;
+                }
+
+                let mut candidate = before.clone();
+                candidate.push((start, start + nucleus));
+
+                let better = match &best[end] {
+                    Some(current) => self.prefers(&candidate, current),
+                    None => true,
+                };
+                if better {
+                    best[end] = Some(candidate);
+                }
+            }
+        }
+
+        let Some(split) = best[phonemes.len()].take() else {
+            let position = (0..phonemes.len())
+                .rev()
+                .find(|i| best[*i].is_some())
+                .unwrap_or(0);
+
+            return Err(SyllabificationError::Unsyllabifiable {
+                position,
+                rest: phonemes[position..].iter().map(|p| p.to_string()).collect(),
+            });
+        };
+
+        let ends = split
+            .iter()
+            .skip(1)
+            .map(|(start, _)| *start)
+            .chain(std::iter::once(phonemes.len()));
+
+        Ok(split
+            .iter()
+            .zip(ends)
+            .map(|((start, nucleus), end)| {
+                let owned = |range: &[&str]| range.iter().map(|p| p.to_string()).collect();
+
+                ParsedSyllable {
+                    onset: owned(&phonemes[*start..*nucleus]),
+                    nucleus: phonemes[*nucleus].to_string(),
+                    coda: owned(&phonemes[nucleus + 1..end]),
+                }
+            })
+            .collect())
+    }
+
+    /// Whether a split is better than another of the same phonemes
+    ///
+    /// Fewer syllables win, then earlier syllable boundaries for maximal
+    /// onsets, or later ones for maximal codas.
+    fn prefers(&self, candidate: &[(usize, usize)], current: &[(usize, usize)]) -> bool {
+        if candidate.len() != current.len() {
+            return candidate.len() < current.len();
+        }
+
+        let boundaries = |split: &[(usize, usize)]| -> Vec<usize> {
+            split.iter().map(|(start, _)| *start).collect()
+        };
+
+        match self.principle {
+            SyllabificationPrinciple::MaximalOnset => boundaries(candidate) < boundaries(current),
+            SyllabificationPrinciple::MaximalCoda => boundaries(candidate) > boundaries(current),
+        }
+    }
+}
+
+impl Structure<'_> {
+    fn contains(set: &PhonemeSet, phoneme: &str) -> bool {
+        set.members.iter().any(|member| member == phoneme)
+    }
+
+    /// Whether the slots match the phonemes exactly
+    fn fill(&self, slots: &[Slot], phonemes: &[&str]) -> bool {
+        let Some((slot, rest)) = slots.split_first() else {
+            return phonemes.is_empty();
+        };
+
+        let filled = phonemes.len() >= slot.letters.len()
+            && slot
+                .letters
+                .iter()
+                .zip(phonemes)
+                .all(|(letter, phoneme)| Structure::contains(self.sets[letter], phoneme))
+            && self.fill(rest, &phonemes[slot.letters.len()..]);
+
+        filled || (slot.optional && self.fill(rest, phonemes))
+    }
+
+    /// The position of the nucleus if the phonemes form a syllable
+    fn matches(&self, phonemes: &[&str]) -> Option<usize> {
+        (0..phonemes.len()).find(|nucleus| {
+            Structure::contains(self.nucleus, phonemes[*nucleus])
+                && self.fill(&self.onset, &phonemes[..*nucleus])
+                && self.fill(&self.coda, &phonemes[nucleus + 1..])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SyllabificationError, SyllabificationPrinciple, SyllableTemplate, SyllableTemplateError,
+    };
+    use crate::data::PhonemeSet;
+    use std::collections::HashMap;
+
+    fn sets() -> HashMap<String, PhonemeSet> {
+        let set = |members: &[&str]| PhonemeSet {
+            members: members.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        };
+
+        HashMap::from([
+            (
+                "consonants".to_string(),
+                set(&["p", "t", "k", "s", "l", "r"]),
+            ),
+            ("vowels".to_string(), set(&["a", "i", "u"])),
+        ])
+    }
+
+    fn template(pattern: &str, principle: SyllabificationPrinciple) -> SyllableTemplate {
+        SyllableTemplate {
+            pattern: pattern.to_string(),
+            sets: HashMap::from([('C', "consonants".to_string()), ('V', "vowels".to_string())]),
+            nucleus: 'V',
+            principle,
+        }
+    }
+
+    fn syllabify(template: &SyllableTemplate, word: &str) -> Result<String, SyllabificationError> {
+        let phonemes: Vec<String> = word.chars().map(String::from).collect();
+        let syllables = template.syllabify(&sets(), &phonemes)?;
+
+        Ok(syllables
+            .iter()
+            .map(|syllable| {
+                format!(
+                    "{}{}{}",
+                    syllable.onset.concat(),
+                    syllable.nucleus,
+                    syllable.coda.concat()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("."))
+    }
+
+    #[test]
+    fn syllabifies_by_principle() {
+        let onset = template("(C)(C)V(C)", SyllabificationPrinciple::MaximalOnset);
+        let coda = template("(C)(C)V(C)", SyllabificationPrinciple::MaximalCoda);
+
+        assert_eq!(syllabify(&onset, "pata").unwrap(), "pa.ta");
+        assert_eq!(syllabify(&coda, "pata").unwrap(), "pat.a");
+        assert_eq!(syllabify(&onset, "atla").unwrap(), "a.tla");
+        assert_eq!(syllabify(&coda, "atla").unwrap(), "at.la");
+        // Only two onset consonants are allowed
+        assert_eq!(syllabify(&onset, "astra").unwrap(), "as.tra");
+        assert_eq!(syllabify(&onset, "iu").unwrap(), "i.u");
+    }
+
+    #[test]
+    fn optional_groups_are_all_or_nothing() {
+        let template = template("(CC)V", SyllabificationPrinciple::MaximalOnset);
+
+        assert_eq!(syllabify(&template, "tra").unwrap(), "tra");
+        assert!(syllabify(&template, "ta").is_err());
+    }
+
+    #[test]
+    fn reports_unsyllabifiable_sequences() {
+        let template = template("(C)V(C)", SyllabificationPrinciple::MaximalOnset);
+
+        assert_eq!(
+            syllabify(&template, "pastka").unwrap_err().to_string(),
+            "cannot syllabify t k a from position 3"
+        );
+        assert_eq!(
+            syllabify(&template, "pst").unwrap_err().to_string(),
+            "cannot syllabify p s t from position 0"
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        let errors: Vec<_> = ["(C(C)V", "C)V", "()V", "CVV", "CC", "XV"]
+            .into_iter()
+            .map(|pattern| {
+                template(pattern, Default::default())
+                    .validate(&sets())
+                    .unwrap_err()
+            })
+            .collect();
+
+        assert!(matches!(
+            errors[..],
+            [
+                SyllableTemplateError::UnbalancedParenthesis(2),
+                SyllableTemplateError::UnbalancedParenthesis(1),
+                SyllableTemplateError::EmptyGroup(0),
+                SyllableTemplateError::NucleusCount { count: 2, .. },
+                SyllableTemplateError::NucleusCount { count: 0, .. },
+                SyllableTemplateError::UnknownLetter('X'),
+            ]
+        ));
+    }
+}
@@ -1,4 +1,12 @@
-use crate::data::{Language, LanguageGroup, Protolanguage};
+mod chart;
+mod derivation;
+mod tree;
+
+pub use chart::*;
+pub use derivation::*;
+pub use tree::*;
+
+use crate::data::{DerivationReport, InventoryChart, Language, LanguageGroup, Protolanguage};
 use crate::file::Project;
 use askama::Template;
 use serde::{Serialize, Serializer};
@@ -29,21 +37,29 @@ impl<'a> From<&'a LanguageGroup> for LanguageGroupExport<'a> {
 
 struct ProtolanguageExport<'a> {
     raw: &'a Protolanguage,
+    chart: TypstChart,
 }
 
 impl<'a> From<&'a Protolanguage> for ProtolanguageExport<'a> {
     fn from(value: &'a Protolanguage) -> Self {
-        ProtolanguageExport { raw: value }
+        ProtolanguageExport {
+            raw: value,
+            chart: (&value.chart()).into(),
+        }
     }
 }
 
 struct LanguageExport<'a> {
     raw: &'a Language,
+    chart: TypstChart,
 }
 
 impl<'a> From<&'a Language> for LanguageExport<'a> {
     fn from(value: &'a Language) -> Self {
-        LanguageExport { raw: value }
+        LanguageExport {
+            raw: value,
+            chart: (&value.chart()).into(),
+        }
     }
 }
 
@@ -120,3 +136,114 @@ pub async fn export_language_group(project: State<'_, Project>) -> Result<(), Er
 
     Ok(())
 }
+
+#[command]
+pub fn render_chart(chart: InventoryChart, format: ChartFormat) -> Result<String, Error> {
+    let rendered = match format {
+        ChartFormat::Svg => SvgChart::from(&chart).render()?,
+        ChartFormat::Typst => TypstChart::from(&chart).render()?,
+    };
+
+    Ok(rendered.trim().to_string())
+}
+
+#[command]
+pub async fn export_chart(chart: InventoryChart, format: ChartFormat) -> Result<(), Error> {
+    use std::io::Write;
+
+    let (tx, rx) = oneshot::channel();
+
+    FileDialogBuilder::new()
+        .add_filter("Chart", &[format.extension()])
+        .save_file(|file_path| {
+            tx.send(file_path).unwrap();
+        });
+
+    if let Some(file_path) = rx.await.unwrap() {
+        let rendered = render_chart(chart, format)?;
+
+        let mut file = File::create(file_path)?;
+
+        write!(file, "{rendered}")?;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn render_derivation(report: DerivationReport, format: TableFormat) -> Result<String, Error> {
+    let rendered = match format {
+        TableFormat::Csv => derivation_csv(&report),
+        TableFormat::Typst => TypstDerivation::from(&report).render()?,
+    };
+
+    Ok(rendered.trim().to_string())
+}
+
+#[command]
+pub async fn export_derivation(report: DerivationReport, format: TableFormat) -> Result<(), Error> {
+    use std::io::Write;
+
+    let (tx, rx) = oneshot::channel();
+
+    FileDialogBuilder::new()
+        .add_filter("Derivation", &[format.extension()])
+        .save_file(|file_path| {
+            tx.send(file_path).unwrap();
+        });
+
+    if let Some(file_path) = rx.await.unwrap() {
+        let rendered = render_derivation(report, format)?;
+
+        let mut file = File::create(file_path)?;
+
+        write!(file, "{rendered}")?;
+    }
+
+    Ok(())
+}
+
+/// Renders the family tree of the project, with the sound changes of each
+/// language on its branch if `annotate` is set
+#[command]
+pub fn render_family_tree(
+    project: State<Project>,
+    format: TreeFormat,
+    annotate: bool,
+) -> Result<String, Error> {
+    let tree = FamilyTree::new(&project.inner().0.lock().unwrap().1, annotate);
+    let rendered = match format {
+        TreeFormat::Newick => tree.newick(),
+        TreeFormat::Dot => tree.dot(),
+        TreeFormat::Svg => SvgTree::from(&tree).render()?,
+    };
+
+    Ok(rendered.trim().to_string())
+}
+
+#[command]
+pub async fn export_family_tree(
+    project: State<'_, Project>,
+    format: TreeFormat,
+    annotate: bool,
+) -> Result<(), Error> {
+    use std::io::Write;
+
+    let (tx, rx) = oneshot::channel();
+
+    FileDialogBuilder::new()
+        .add_filter("Family tree", &[format.extension()])
+        .save_file(|file_path| {
+            tx.send(file_path).unwrap();
+        });
+
+    if let Some(file_path) = rx.await.unwrap() {
+        let rendered = render_family_tree(project, format, annotate)?;
+
+        let mut file = File::create(file_path)?;
+
+        write!(file, "{rendered}")?;
+    }
+
+    Ok(())
+}
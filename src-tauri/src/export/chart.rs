@@ -0,0 +1,464 @@
+use crate::data::{Backness, ChartEntry, ChartSlot, Height, InventoryChart, Place};
+use askama::Template;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum ChartFormat {
+    Svg,
+    Typst,
+}
+
+impl ChartFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ChartFormat::Svg => "svg",
+            ChartFormat::Typst => "typ",
+        }
+    }
+}
+
+fn place_name(place: Place) -> &'static str {
+    match place {
+        Place::Bilabial => "Bilabial",
+        Place::Labiodental => "Labiodental",
+        Place::Dental => "Dental",
+        Place::Alveolar => "Alveolar",
+        Place::Postalveolar => "Postalveolar",
+        Place::Retroflex => "Retroflex",
+        Place::Palatal => "Palatal",
+        Place::Velar => "Velar",
+        Place::Uvular => "Uvular",
+        Place::Pharyngeal => "Pharyngeal",
+        Place::Glottal => "Glottal",
+    }
+}
+
+fn height_name(height: Height) -> &'static str {
+    match height {
+        Height::Close => "Close",
+        Height::NearClose => "Near-close",
+        Height::CloseMid => "Close-mid",
+        Height::Mid => "Mid",
+        Height::OpenMid => "Open-mid",
+        Height::NearOpen => "Near-open",
+        Height::Open => "Open",
+    }
+}
+
+fn backness_name(backness: Backness) -> &'static str {
+    match backness {
+        Backness::Front => "Front",
+        Backness::Central => "Central",
+        Backness::Back => "Back",
+    }
+}
+
+/// Allophones are parenthesized, as is usual in inventory charts
+fn entry_label(entry: &ChartEntry) -> String {
+    if entry.allophone {
+        format!("({})", entry.ipa)
+    } else {
+        entry.ipa.clone()
+    }
+}
+
+fn slot_label(slot: &ChartSlot) -> String {
+    slot.entries
+        .iter()
+        .map(entry_label)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub(super) fn typst_escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            if "\\*_#$@<>[]`~\"'=-/+".contains(c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+}
+
+struct TypstRow {
+    label: &'static str,
+    /// Table cells in Typst code mode
+    slots: Vec<String>,
+}
+
+struct TypstTable {
+    caption: &'static str,
+    headers: Vec<&'static str>,
+    rows: Vec<TypstRow>,
+}
+
+impl TypstTable {
+    fn new<'a>(
+        caption: &'static str,
+        headers: Vec<&'static str>,
+        rows: impl Iterator<Item = (&'static str, Vec<&'a ChartSlot>)>,
+    ) -> TypstTable {
+        let rows = rows
+            .map(|(label, slots)| TypstRow {
+                label,
+                slots: slots
+                    .into_iter()
+                    .map(|slot| {
+                        if slot.impossible {
+                            "table.cell(fill: luma(200))[]".to_string()
+                        } else {
+                            format!("[{}]", typst_escape(&slot_label(slot)))
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        TypstTable {
+            caption,
+            headers,
+            rows,
+        }
+    }
+
+    fn columns(&self) -> String {
+        format!("(auto,) + (1fr,) * {}", self.headers.len() * 2)
+    }
+}
+
+/// An inventory chart as Typst tables, one for consonants and one for vowels
+#[derive(Template)]
+#[template(path = "chart.typ.askama", escape = "none")]
+pub struct TypstChart {
+    tables: Vec<TypstTable>,
+    other: String,
+}
+
+impl From<&InventoryChart> for TypstChart {
+    fn from(chart: &InventoryChart) -> Self {
+        let consonants = TypstTable::new(
+            "Consonants",
+            Place::ALL.into_iter().map(place_name).collect(),
+            chart.consonants.iter().map(|row| {
+                let slots = row
+                    .cells
+                    .iter()
+                    .flat_map(|cell| [&cell.left, &cell.right])
+                    .collect();
+
+                (row.manner.name(), slots)
+            }),
+        );
+        let vowels = TypstTable::new(
+            "Vowels",
+            Backness::ALL.into_iter().map(backness_name).collect(),
+            chart.vowels.iter().map(|row| {
+                let slots = row
+                    .cells
+                    .iter()
+                    .flat_map(|cell| [&cell.left, &cell.right])
+                    .collect();
+
+                (height_name(row.height), slots)
+            }),
+        );
+        let other = chart
+            .other
+            .iter()
+            .map(|entry| typst_escape(&entry_label(entry)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        TypstChart {
+            tables: vec![consonants, vowels],
+            other,
+        }
+    }
+}
+
+const LABEL_WIDTH: u32 = 150;
+const SLOT_WIDTH: u32 = 40;
+const ROW_HEIGHT: u32 = 32;
+/// Distance from the top of a row to the baseline of its text
+const BASELINE: u32 = 21;
+const TRAPEZOID_WIDTH: u32 = 420;
+const TRAPEZOID_STEP: u32 = 45;
+/// How far the front edge moves back per height step
+const TRAPEZOID_SLANT: u32 = 35;
+
+struct SvgRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    class: &'static str,
+}
+
+struct SvgLine {
+    x1: u32,
+    y1: u32,
+    x2: u32,
+    y2: u32,
+}
+
+struct SvgText {
+    x: u32,
+    y: u32,
+    anchor: &'static str,
+    /// Pieces of text with their class
+    spans: Vec<(&'static str, String)>,
+}
+
+impl SvgText {
+    fn label(x: u32, y: u32, anchor: &'static str, text: &str) -> SvgText {
+        SvgText {
+            x,
+            y,
+            anchor,
+            spans: vec![("label", text.to_string())],
+        }
+    }
+}
+
+/// An inventory chart as an SVG image, with a consonant table above a
+/// vowel trapezoid
+///
+/// Impossible slots are shaded and have the `impossible` class, empty slots
+/// have the `gap` class, and allophones the `allophone` class.
+#[derive(Template)]
+#[template(path = "chart.svg.askama", escape = "html")]
+pub struct SvgChart {
+    width: u32,
+    height: u32,
+    rects: Vec<SvgRect>,
+    lines: Vec<SvgLine>,
+    dots: Vec<(u32, u32)>,
+    texts: Vec<SvgText>,
+}
+
+impl SvgChart {
+    /// Writes the entries of a slot at `x`
+    fn slot_text(&mut self, slot: &ChartSlot, x: u32, y: u32, anchor: &'static str) {
+        if slot.entries.is_empty() {
+            return;
+        }
+
+        let spans = slot
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let class = if entry.allophone {
+                    "allophone"
+                } else {
+                    "phoneme"
+                };
+                let separator = if i == 0 { "" } else { " " };
+
+                (class, format!("{separator}{}", entry_label(entry)))
+            })
+            .collect();
+
+        self.texts.push(SvgText {
+            x,
+            y,
+            anchor,
+            spans,
+        });
+    }
+
+    fn consonants(&mut self, chart: &InventoryChart) {
+        for (i, place) in Place::ALL.into_iter().enumerate() {
+            self.texts.push(SvgText::label(
+                LABEL_WIDTH + (2 * i as u32 + 1) * SLOT_WIDTH,
+                BASELINE,
+                "middle",
+                place_name(place),
+            ));
+        }
+
+        for (r, row) in chart.consonants.iter().enumerate() {
+            let y = (r as u32 + 1) * ROW_HEIGHT;
+
+            self.texts
+                .push(SvgText::label(8, y + BASELINE, "start", row.manner.name()));
+
+            for (i, cell) in row.cells.iter().enumerate() {
+                let x = LABEL_WIDTH + 2 * i as u32 * SLOT_WIDTH;
+
+                for (slot, slot_x) in [(&cell.left, x), (&cell.right, x + SLOT_WIDTH)] {
+                    let class = if slot.impossible {
+                        "impossible"
+                    } else if slot.gap {
+                        "gap"
+                    } else {
+                        "slot"
+                    };
+
+                    self.rects.push(SvgRect {
+                        x: slot_x,
+                        y,
+                        width: SLOT_WIDTH,
+                        height: ROW_HEIGHT,
+                        class,
+                    });
+                    self.slot_text(slot, slot_x + SLOT_WIDTH / 2, y + BASELINE, "middle");
+                }
+
+                self.rects.push(SvgRect {
+                    x,
+                    y,
+                    width: 2 * SLOT_WIDTH,
+                    height: ROW_HEIGHT,
+                    class: "cell",
+                });
+            }
+        }
+    }
+
+    fn vowels(&mut self, chart: &InventoryChart, top: u32) {
+        let left = LABEL_WIDTH;
+        let steps = Height::ALL.len() as u32 - 1;
+        let x = |height: usize, backness: Backness| {
+            let front = left + height as u32 * TRAPEZOID_SLANT;
+            let back = left + TRAPEZOID_WIDTH;
+
+            match backness {
+                Backness::Front => front,
+                Backness::Central => (front + back) / 2,
+                Backness::Back => back,
+            }
+        };
+        let y = |height: usize| top + height as u32 * TRAPEZOID_STEP;
+
+        for backness in Backness::ALL {
+            self.texts.push(SvgText::label(
+                x(0, backness),
+                top - 16,
+                "middle",
+                backness_name(backness),
+            ));
+        }
+
+        // The outline, and the lines through close-mid and open-mid vowels
+        for (from, to) in [(0, 0), (steps as usize, steps as usize)] {
+            self.lines.push(SvgLine {
+                x1: x(from, Backness::Front),
+                y1: y(from),
+                x2: x(to, Backness::Back),
+                y2: y(to),
+            });
+        }
+        for backness in [Backness::Front, Backness::Central, Backness::Back] {
+            self.lines.push(SvgLine {
+                x1: x(0, backness),
+                y1: y(0),
+                x2: x(steps as usize, backness),
+                y2: y(steps as usize),
+            });
+        }
+        for height in [Height::CloseMid, Height::OpenMid] {
+            let h = height as usize;
+
+            self.lines.push(SvgLine {
+                x1: x(h, Backness::Front),
+                y1: y(h),
+                x2: x(h, Backness::Back),
+                y2: y(h),
+            });
+        }
+
+        for (h, row) in chart.vowels.iter().enumerate() {
+            self.texts.push(SvgText::label(
+                8,
+                y(h) + 5,
+                "start",
+                height_name(row.height),
+            ));
+
+            for (cell, backness) in row.cells.iter().zip(Backness::ALL) {
+                if cell.left.entries.is_empty() && cell.right.entries.is_empty() {
+                    continue;
+                }
+
+                let (x, y) = (x(h, backness), y(h));
+
+                self.dots.push((x, y));
+                self.slot_text(&cell.left, x - 8, y + 5, "end");
+                self.slot_text(&cell.right, x + 8, y + 5, "start");
+            }
+        }
+    }
+}
+
+impl From<&InventoryChart> for SvgChart {
+    fn from(chart: &InventoryChart) -> Self {
+        let consonants_height = (chart.consonants.len() as u32 + 1) * ROW_HEIGHT;
+        let vowels_top = consonants_height + 2 * ROW_HEIGHT;
+        let vowels_height = (Height::ALL.len() as u32 - 1) * TRAPEZOID_STEP;
+        let mut height = vowels_top + vowels_height + ROW_HEIGHT;
+
+        let mut svg = SvgChart {
+            width: LABEL_WIDTH + 2 * SLOT_WIDTH * Place::ALL.len() as u32,
+            height,
+            rects: vec![],
+            lines: vec![],
+            dots: vec![],
+            texts: vec![],
+        };
+
+        svg.consonants(chart);
+        svg.vowels(chart, vowels_top);
+
+        if !chart.other.is_empty() {
+            let slot = ChartSlot {
+                entries: chart.other.clone(),
+                impossible: false,
+                gap: false,
+            };
+
+            svg.texts
+                .push(SvgText::label(8, height + BASELINE, "start", "Other"));
+            svg.slot_text(&slot, LABEL_WIDTH, height + BASELINE, "start");
+            height += ROW_HEIGHT;
+        }
+
+        svg.height = height;
+
+        svg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SvgChart, TypstChart};
+    use crate::data::{fixtures, InventoryChart};
+    use askama::Template;
+    use insta::assert_snapshot;
+
+    fn chart() -> InventoryChart {
+        let phonemes = fixtures::phonemes(&[
+            ("p", "p", &[]),
+            ("b", "b", &[]),
+            ("t", "t", &["ɾ"]),
+            ("m", "m", &[]),
+            ("s", "s", &[]),
+            ("ǀ", "ǀ", &[]),
+            ("a", "a", &["ə"]),
+            ("u", "u", &[]),
+        ]);
+
+        InventoryChart::new(&phonemes)
+    }
+
+    #[test]
+    fn renders_typst_chart() {
+        assert_snapshot!(TypstChart::from(&chart()).render().unwrap());
+    }
+
+    #[test]
+    fn renders_svg_chart() {
+        assert_snapshot!(SvgChart::from(&chart()).render().unwrap());
+    }
+}
@@ -0,0 +1,154 @@
+use super::chart::typst_escape;
+use crate::data::{DerivationReport, DerivationWarning, Interaction};
+use askama::Template;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum TableFormat {
+    Csv,
+    Typst,
+}
+
+impl TableFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TableFormat::Csv => "csv",
+            TableFormat::Typst => "typ",
+        }
+    }
+}
+
+/// Marks a rule that left a form unchanged
+const UNCHANGED: &str = "—";
+
+/// A derivation table, with a column per form and a row per rule
+///
+/// The first row holds the inputs and the last the outputs. A rule's row
+/// holds the form it produced, or a dash if it did not change it.
+fn table(report: &DerivationReport) -> Vec<Vec<String>> {
+    let mut header = vec!["Input".to_string()];
+    header.extend(report.derivations.iter().map(|d| d.input.clone()));
+
+    let rules = report.rules.iter().enumerate().map(|(i, source)| {
+        let mut row = vec![source.clone()];
+        row.extend(report.derivations.iter().map(|d| {
+            d.steps
+                .iter()
+                .find(|step| step.rule == i)
+                .map_or(UNCHANGED.to_string(), |step| step.after.clone())
+        }));
+        row
+    });
+
+    let mut output = vec!["Output".to_string()];
+    output.extend(report.derivations.iter().map(|d| d.output.clone()));
+
+    std::iter::once(header)
+        .chain(rules)
+        .chain(std::iter::once(output))
+        .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn warning_text(report: &DerivationReport, warning: &DerivationWarning) -> String {
+    match warning {
+        DerivationWarning::NeverFires { rule } => {
+            format!("{} never fires", report.rules[*rule])
+        }
+        DerivationWarning::Interaction {
+            interaction,
+            rule,
+            other,
+            example,
+        } => {
+            let verb = match interaction {
+                Interaction::Feeds => "feeds",
+                Interaction::Bleeds => "bleeds",
+                Interaction::Counterfeeds => "counterfeeds",
+                Interaction::Counterbleeds => "counterbleeds",
+            };
+
+            format!(
+                "{} {verb} {} (in {example})",
+                report.rules[*rule], report.rules[*other]
+            )
+        }
+    }
+}
+
+/// A derivation table as CSV, followed by a row per warning
+pub fn derivation_csv(report: &DerivationReport) -> String {
+    let rows = table(report).into_iter().chain(
+        report
+            .warnings
+            .iter()
+            .map(|warning| vec!["Warning".to_string(), warning_text(report, warning)]),
+    );
+
+    rows.map(|row| {
+        row.iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",")
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// A derivation table as a Typst table
+#[derive(Template)]
+#[template(path = "derivation.typ.askama", escape = "none")]
+pub struct TypstDerivation {
+    columns: usize,
+    rows: Vec<Vec<String>>,
+}
+
+impl From<&DerivationReport> for TypstDerivation {
+    fn from(report: &DerivationReport) -> Self {
+        let rows: Vec<Vec<String>> = table(report)
+            .into_iter()
+            .map(|row| row.iter().map(|cell| typst_escape(cell)).collect())
+            .collect();
+
+        TypstDerivation {
+            columns: report.derivations.len() + 1,
+            rows,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{derivation_csv, TypstDerivation};
+    use crate::data::{parse_ipa, DerivationReport, SoundChanges};
+    use askama::Template;
+    use insta::assert_snapshot;
+
+    fn report() -> DerivationReport {
+        let words: Vec<_> = ["apa", "aki"]
+            .iter()
+            .map(|word| parse_ipa(word).phones)
+            .collect();
+
+        SoundChanges::parse("p > b / a_a\nk > tʃ / _i\nq > ɢ")
+            .unwrap()
+            .derive(&words)
+    }
+
+    #[test]
+    fn renders_csv_derivation() {
+        assert_snapshot!(derivation_csv(&report()));
+    }
+
+    #[test]
+    fn renders_typst_derivation() {
+        assert_snapshot!(TypstDerivation::from(&report()).render().unwrap());
+    }
+}
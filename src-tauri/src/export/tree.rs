@@ -0,0 +1,517 @@
+use crate::data::{LanguageGroup, SoundChanges};
+use askama::Template;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub enum TreeFormat {
+    Newick,
+    Dot,
+    Svg,
+}
+
+impl TreeFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            TreeFormat::Newick => "nwk",
+            TreeFormat::Dot => "dot",
+            TreeFormat::Svg => "svg",
+        }
+    }
+}
+
+struct TreeNode {
+    name: String,
+    proto: bool,
+    /// Annotations of the branch leading to the node
+    changes: Vec<String>,
+    children: Vec<usize>,
+}
+
+/// The protolanguages and languages of a group as a tree
+///
+/// The protolanguages of past stages come first, each descending from its
+/// ancestors in the stage before. Each node hangs from its first ancestor.
+/// Further ancestors, which a tree can't show, are kept as secondary edges.
+pub struct FamilyTree {
+    nodes: Vec<TreeNode>,
+    roots: Vec<usize>,
+    /// Pairs of ancestor and descendant
+    secondary: Vec<(usize, usize)>,
+}
+
+impl FamilyTree {
+    /// Builds the tree, with a language's sound changes on the branch above
+    /// it if `annotate` is set
+    ///
+    /// Sound changes that don't parse are left out.
+    pub fn new(group: &LanguageGroup, annotate: bool) -> FamilyTree {
+        let mut nodes = vec![];
+        // Ancestors of each node, the first one being its parent
+        let mut ancestors: Vec<Vec<usize>> = vec![];
+        // Nodes of the protolanguages of the last stage, by name
+        let mut previous: HashMap<&str, usize> = HashMap::new();
+
+        for index in 0..=group.history.len() {
+            let (protolangs, ancestry) = group.stage(index).unwrap();
+            let mut current = HashMap::new();
+            let mut added = vec![];
+
+            for proto in protolangs {
+                let earlier = ancestry.get(&proto.name).map_or(&[][..], Vec::as_slice);
+
+                // A protolanguage carried over by a selective epoch is
+                // still the same language
+                if let ([name], Some(node)) = (earlier, previous.get(proto.name.as_str())) {
+                    if name == &proto.name {
+                        current.insert(proto.name.as_str(), *node);
+                        continue;
+                    }
+                }
+
+                current.insert(proto.name.as_str(), nodes.len());
+                added.push((nodes.len(), proto));
+                nodes.push(TreeNode {
+                    name: proto.name.clone(),
+                    proto: true,
+                    changes: vec![],
+                    children: vec![],
+                });
+                ancestors.push(
+                    earlier
+                        .iter()
+                        .filter_map(|name| previous.get(name.as_str()).copied())
+                        .collect(),
+                );
+            }
+
+            // Ancestors in the same stage come before those in the last one
+            for (node, proto) in added {
+                let mut own: Vec<_> = proto
+                    .ancestors
+                    .iter()
+                    .filter_map(|name| current.get(name.as_str()).copied())
+                    .collect();
+                own.append(&mut ancestors[node]);
+                ancestors[node] = own;
+            }
+            previous = current;
+        }
+
+        for lang in &group.langs {
+            nodes.push(TreeNode {
+                name: lang.name.clone(),
+                proto: false,
+                changes: Some(&lang.sound_changes)
+                    .filter(|_| annotate)
+                    .and_then(|changes| SoundChanges::parse(changes).ok())
+                    .map(|changes| changes.sources().into_iter().map(str::to_string).collect())
+                    .unwrap_or_default(),
+                children: vec![],
+            });
+            ancestors.push(
+                lang.ancestors
+                    .iter()
+                    .filter_map(|name| previous.get(name.as_str()).copied())
+                    .collect(),
+            );
+        }
+
+        let mut parents = vec![None; nodes.len()];
+        let mut secondary = vec![];
+        for (i, ancestors) in ancestors.iter().enumerate() {
+            let mut ancestors = ancestors.iter().copied();
+
+            parents[i] = ancestors.next();
+            secondary.extend(ancestors.map(|anc| (anc, i)));
+        }
+
+        // A cycle would be unreachable from the roots, so one of its edges
+        // is cut
+        for i in 0..nodes.len() {
+            let mut path = vec![i];
+            let mut top = i;
+            while let Some(parent) = parents[top] {
+                if path.contains(&parent) {
+                    parents[top] = None;
+                    break;
+                }
+                path.push(parent);
+                top = parent;
+            }
+        }
+        let mut roots = vec![];
+        for (i, parent) in parents.iter().enumerate() {
+            match parent {
+                Some(parent) => nodes[*parent].children.push(i),
+                None => roots.push(i),
+            }
+        }
+
+        FamilyTree {
+            nodes,
+            roots,
+            secondary,
+        }
+    }
+
+    fn newick_node(&self, index: usize, out: &mut String) {
+        let node = &self.nodes[index];
+
+        if !node.children.is_empty() {
+            out.push('(');
+            for (i, child) in node.children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                self.newick_node(*child, out);
+            }
+            out.push(')');
+        }
+        out.push_str(&newick_label(&node.name));
+        if !node.changes.is_empty() {
+            // Brackets would end the comment early
+            let changes = node.changes.join("; ").replace('[', "{").replace(']', "}");
+            out.push_str(&format!("[&changes=\"{}\"]", changes.replace('"', "'")));
+        }
+    }
+
+    /// The tree in Newick format, with several roots joined under a nameless
+    /// one
+    pub fn newick(&self) -> String {
+        let mut out = String::new();
+
+        match self.roots.as_slice() {
+            [root] => self.newick_node(*root, &mut out),
+            roots => {
+                out.push('(');
+                for (i, root) in roots.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    self.newick_node(*root, &mut out);
+                }
+                out.push(')');
+            }
+        }
+        out.push(';');
+
+        out
+    }
+
+    /// The tree as a Graphviz digraph, with protolanguages as ellipses and
+    /// languages as boxes
+    ///
+    /// Nodes are named by index, since a name can come back in a later stage.
+    pub fn dot(&self) -> String {
+        let mut out = String::from("digraph family {\n");
+
+        for (i, node) in self.nodes.iter().enumerate() {
+            let shape = if node.proto { "ellipse" } else { "box" };
+            out.push_str(&format!(
+                "    n{i} [label=\"{}\", shape={shape}];\n",
+                dot_escape(&node.name)
+            ));
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            for child in &node.children {
+                let changes = &self.nodes[*child].changes;
+                let label = if changes.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [label=\"{}\"]", dot_escape(&changes.join("\n")))
+                };
+
+                out.push_str(&format!("    n{i} -> n{child}{label};\n"));
+            }
+        }
+        for (ancestor, descendant) in &self.secondary {
+            out.push_str(&format!(
+                "    n{ancestor} -> n{descendant} [style=dashed];\n"
+            ));
+        }
+        out.push('}');
+
+        out
+    }
+}
+
+fn newick_label(name: &str) -> String {
+    if name.contains(|c: char| "()[]':;,".contains(c) || c.is_whitespace()) {
+        format!("'{}'", name.replace('\'', "''"))
+    } else {
+        name.to_string()
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+const NODE_WIDTH: u32 = 120;
+/// Narrower than a node, to leave a gap between neighbours
+const BOX_WIDTH: u32 = 112;
+const NODE_HEIGHT: u32 = 32;
+/// Distance from the top of a node to the baseline of its name
+const BASELINE: u32 = 21;
+const LEVEL_GAP: u32 = 48;
+const CHANGE_HEIGHT: u32 = 14;
+const MARGIN: u32 = 16;
+
+struct SvgNode {
+    /// Center of the top edge
+    x: u32,
+    y: u32,
+    class: &'static str,
+    name: String,
+}
+
+impl SvgNode {
+    fn left(&self) -> u32 {
+        self.x - BOX_WIDTH / 2
+    }
+}
+
+struct SvgEdge {
+    /// Points of the path, from the ancestor down to the descendant
+    points: Vec<(u32, u32)>,
+    class: &'static str,
+}
+
+struct SvgChanges {
+    x: u32,
+    y: u32,
+    lines: Vec<String>,
+}
+
+/// A family tree as an SVG image, with ancestors above their descendants
+///
+/// Protolanguages have the `proto` class and languages the `lang` class.
+/// Secondary ancestors are joined by `secondary` edges.
+#[derive(Template)]
+#[template(path = "family_tree.svg.askama", escape = "html")]
+pub struct SvgTree {
+    width: u32,
+    height: u32,
+    nodes: Vec<SvgNode>,
+    edges: Vec<SvgEdge>,
+    changes: Vec<SvgChanges>,
+    box_width: u32,
+    node_height: u32,
+    baseline: u32,
+    change_height: u32,
+}
+
+impl From<&FamilyTree> for SvgTree {
+    fn from(tree: &FamilyTree) -> Self {
+        let most_changes = tree
+            .nodes
+            .iter()
+            .map(|node| node.changes.len() as u32)
+            .max()
+            .unwrap_or(0);
+        let level_height = NODE_HEIGHT + LEVEL_GAP + most_changes * CHANGE_HEIGHT;
+
+        // Leaves go left to right, and parents centered over their children
+        fn place(
+            tree: &FamilyTree,
+            index: usize,
+            depth: u32,
+            next_leaf: &mut u32,
+            positions: &mut [(u32, u32)],
+        ) {
+            let node = &tree.nodes[index];
+            let x = if node.children.is_empty() {
+                *next_leaf += 1;
+                (*next_leaf - 1) * NODE_WIDTH
+            } else {
+                for child in &node.children {
+                    place(tree, *child, depth + 1, next_leaf, positions);
+                }
+                let first = positions[node.children[0]].0;
+                let last = positions[*node.children.last().unwrap()].0;
+                (first + last) / 2
+            };
+
+            positions[index] = (x, depth);
+        }
+
+        let mut positions = vec![(0, 0); tree.nodes.len()];
+        let mut leaves = 0;
+        for root in &tree.roots {
+            place(tree, *root, 0, &mut leaves, &mut positions);
+        }
+        let center = |index: usize| {
+            let (x, depth) = positions[index];
+            (MARGIN + x + NODE_WIDTH / 2, MARGIN + depth * level_height)
+        };
+
+        let nodes = tree
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| {
+                let (x, y) = center(i);
+
+                SvgNode {
+                    x,
+                    y,
+                    class: if node.proto { "proto" } else { "lang" },
+                    name: node.name.clone(),
+                }
+            })
+            .collect();
+
+        let mut edges = vec![];
+        let mut changes = vec![];
+        for (i, node) in tree.nodes.iter().enumerate() {
+            let (x, y) = center(i);
+
+            for child in &node.children {
+                let (child_x, child_y) = center(*child);
+                let middle = y + NODE_HEIGHT + LEVEL_GAP / 4;
+
+                edges.push(SvgEdge {
+                    points: vec![
+                        (x, y + NODE_HEIGHT),
+                        (x, middle),
+                        (child_x, middle),
+                        (child_x, child_y),
+                    ],
+                    class: "branch",
+                });
+
+                let child_changes = &tree.nodes[*child].changes;
+                if !child_changes.is_empty() {
+                    changes.push(SvgChanges {
+                        x: child_x + 4,
+                        y: middle + CHANGE_HEIGHT,
+                        lines: child_changes.clone(),
+                    });
+                }
+            }
+        }
+        for (ancestor, descendant) in &tree.secondary {
+            let (x, y) = center(*ancestor);
+            let (child_x, child_y) = center(*descendant);
+
+            edges.push(SvgEdge {
+                points: vec![(x, y + NODE_HEIGHT), (child_x, child_y)],
+                class: "secondary",
+            });
+        }
+
+        let depth = positions.iter().map(|(_, depth)| *depth).max().unwrap_or(0);
+
+        SvgTree {
+            width: 2 * MARGIN + leaves.max(1) * NODE_WIDTH,
+            height: 2 * MARGIN + depth * level_height + NODE_HEIGHT,
+            nodes,
+            edges,
+            changes,
+            box_width: BOX_WIDTH,
+            node_height: NODE_HEIGHT,
+            baseline: BASELINE,
+            change_height: CHANGE_HEIGHT,
+        }
+    }
+}
+
+impl SvgEdge {
+    fn path(&self) -> String {
+        self.points
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FamilyTree, SvgTree};
+    use crate::data::{Language, LanguageGroup, Protolanguage};
+    use askama::Template;
+    use insta::assert_snapshot;
+
+    fn group() -> LanguageGroup {
+        let proto = |name: &str, ancestors: &[&str]| Protolanguage {
+            name: name.to_string(),
+            ancestors: ancestors.iter().map(|anc| anc.to_string()).collect(),
+            ..Default::default()
+        };
+        let lang = |name: &str, ancestors: &[&str], changes: &str| Language {
+            name: name.to_string(),
+            ancestors: ancestors.iter().map(|anc| anc.to_string()).collect(),
+            sound_changes: changes.to_string(),
+            ..Default::default()
+        };
+
+        LanguageGroup {
+            protolangs: vec![
+                proto("Proto-Nordic", &[]),
+                proto("West Nordic", &["Proto-Nordic"]),
+                proto("Substrate", &[]),
+            ],
+            langs: vec![
+                lang("Icelandic", &["West Nordic"], "p > f / _t"),
+                lang("Faroese", &["West Nordic", "Substrate"], ""),
+                lang("Swedish", &["Proto-Nordic"], "k > tʃ / _[-back]"),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_newick_tree() {
+        assert_eq!(
+            FamilyTree::new(&group(), false).newick(),
+            "(((Icelandic,Faroese)'West Nordic',Swedish)Proto-Nordic,Substrate);"
+        );
+        assert_eq!(
+            FamilyTree::new(&group(), true).newick(),
+            "(((Icelandic[&changes=\"p > f / _t\"],Faroese)'West Nordic',\
+             Swedish[&changes=\"k > tʃ / _{-back}\"])Proto-Nordic,Substrate);"
+        );
+    }
+
+    #[test]
+    fn draws_stages_of_past_epochs() {
+        let lang = |name: &str, ancestor: &str| Language {
+            name: name.to_string(),
+            ancestors: vec![ancestor.to_string()],
+            ..Default::default()
+        };
+        let mut group = LanguageGroup {
+            protolangs: vec![Protolanguage {
+                name: "Proto-X".to_string(),
+                ..Default::default()
+            }],
+            langs: vec![lang("Old-X", "Proto-X"), lang("Other", "Proto-X")],
+            ..Default::default()
+        };
+
+        group.epoch_languages(&["Old-X".to_string()]).unwrap();
+        group.langs.push(lang("Middle-X", "Old-X"));
+        group.epoch();
+        group.langs.push(lang("Modern-X", "Middle-X"));
+
+        let tree = FamilyTree::new(&group, false);
+        assert_eq!(tree.newick(), "(((Modern-X)Middle-X)Old-X,Other)Proto-X;");
+        assert!(tree.dot().contains("n1 -> n3;"));
+    }
+
+    #[test]
+    fn renders_dot_tree() {
+        assert_snapshot!(FamilyTree::new(&group(), true).dot());
+    }
+
+    #[test]
+    fn renders_svg_tree() {
+        let tree = FamilyTree::new(&group(), true);
+
+        assert_snapshot!(SvgTree::from(&tree).render().unwrap());
+    }
+}
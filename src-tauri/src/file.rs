@@ -48,6 +48,8 @@ pub enum Error {
     FamilyMismatch { current: Uuid, merge: Uuid },
     #[error("cannot merge nothing")]
     MergeEmpty,
+    #[error(transparent)]
+    FamilyTree(#[from] crate::data::LanguageGroupError),
 }
 
 impl Serialize for Error {
@@ -198,6 +200,7 @@ pub fn load_language_group(filename: String, project: State<Project>) -> Result<
             VersionReq::parse(VERSION_REQ).expect("INTERNAL failed to compile version req string");
 
         if version_req.matches(&lang_group.version) {
+            lang_group.validate_family_tree()?;
             *project.0.lock().unwrap() = (filename, lang_group);
             Ok(())
         } else {
@@ -14,6 +14,31 @@ pub enum Error {
     Serde(#[from] serde_json::Error),
     #[error(transparent)]
     LanguageGroup(#[from] crate::data::LanguageGroupError),
+    #[error(transparent)]
+    PhonemeSet(#[from] crate::data::PhonemeSetError),
+    #[error(transparent)]
+    SyllableTemplate(#[from] crate::data::SyllableTemplateError),
+    #[error(transparent)]
+    Syllabification(#[from] crate::data::SyllabificationError),
+    #[error(transparent)]
+    Stress(#[from] crate::data::StressError),
+    #[error(transparent)]
+    Environment(#[from] crate::data::EnvironmentError),
+    #[error(transparent)]
+    Phonotactics(#[from] crate::data::PhonotacticsError),
+    #[error(transparent)]
+    Generator(#[from] crate::data::GeneratorError),
+    #[error(transparent)]
+    SoundChange(#[from] crate::data::SoundChangeError),
+    #[error(transparent)]
+    PhonemeChange(#[from] crate::data::PhonemeChangeError),
+    #[error(transparent)]
+    Stage(#[from] crate::data::StageError),
+    #[error("{form}: {diagnostic}")]
+    Ipa {
+        form: String,
+        diagnostic: crate::data::IpaDiagnostic,
+    },
 }
 
 impl Serialize for Error {
@@ -1,9 +1,16 @@
 use super::Error;
-use crate::data::Language;
+use crate::data::{
+    parse_ipa, validate_phoneme_changes, CandidateWord, DerivationReport, EffectiveLanguage,
+    EnvironmentRule, FeatureMatrix, Generated, GeneratorSettings, InventoryChart, Language,
+    ParsedSyllable, Phone, Phoneme, PhonemeChange, PhonemeSet, Phonotactics, Prosody, Realization,
+    SoundChanges, StressedWord, SyllableTemplate, Validation,
+};
 use crate::file::Project;
 use crate::ServiceState;
+use std::collections::HashMap;
 use std::time::Duration;
 use tauri::{command, State, Window};
+use uuid::Uuid;
 
 #[command]
 pub fn create_language(project: State<Project>, name: String) -> Result<(), Error> {
@@ -24,6 +31,13 @@ pub fn get_language(project: State<Project>, name: String) -> Option<Language> {
     project.inner().0.lock().unwrap().1.language(name).cloned()
 }
 
+/// The language's phonemes and rules with those it inherits, and where each
+/// came from
+#[command]
+pub fn get_effective_language(project: State<Project>, name: String) -> Option<EffectiveLanguage> {
+    project.inner().0.lock().unwrap().1.effective_language(name)
+}
+
 #[command]
 pub fn get_language_description(
     project: State<Project>,
@@ -51,6 +65,328 @@ pub fn set_language_description(
     }
 }
 
+/// Sets the protolanguages a language descends from, if they exist
+#[command]
+pub fn set_language_ancestors(
+    project: State<Project>,
+    name: String,
+    ancestors: Vec<String>,
+) -> Result<(), Error> {
+    let mut project = project.inner().0.lock().unwrap();
+    Ok(project.1.set_language_ancestors(&name, ancestors)?)
+}
+
+#[command]
+pub fn get_language_natural_class(
+    project: State<Project>,
+    name: String,
+    class: FeatureMatrix,
+) -> Option<HashMap<Uuid, Phoneme>> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .language(name)
+        .map(|lang| lang.natural_class(&class))
+}
+
+#[command]
+pub fn get_language_chart(project: State<Project>, name: String) -> Option<InventoryChart> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .language(name)
+        .map(Language::chart)
+}
+
+#[command]
+pub fn create_language_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        lang.create_phoneme_set(set)?;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn get_language_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+) -> Option<PhonemeSet> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .language(name)
+        .and_then(|lang| lang.phoneme_sets.get(&set))
+        .cloned()
+}
+
+#[command]
+pub fn set_language_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+    value: PhonemeSet,
+) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        if let Some(existing) = lang.phoneme_sets.get_mut(&set) {
+            *existing = value;
+        }
+    }
+}
+
+#[command]
+pub fn delete_language_phoneme_set(project: State<Project>, name: String, set: String) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        lang.phoneme_sets.remove(&set);
+    }
+}
+
+/// The production set of a member of a phoneme set
+#[command]
+pub fn get_language_phoneme_set_productions(
+    project: State<Project>,
+    name: String,
+    set: String,
+    member: String,
+) -> Option<HashMap<String, Option<Phone>>> {
+    let project = project.inner().0.lock().unwrap();
+    let lang = project.1.language(name)?;
+
+    lang.phoneme_sets
+        .get(&set)?
+        .productions(&lang.phonemes, &member)
+}
+
+/// Sets the syllable template, if it only uses existing phoneme sets
+#[command]
+pub fn set_language_syllable_template(
+    project: State<Project>,
+    name: String,
+    template: Option<SyllableTemplate>,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        if let Some(template) = &template {
+            template.validate(&lang.phoneme_sets)?;
+        }
+        lang.syllable_template = template;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn syllabify_language_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<Vec<ParsedSyllable>>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.language(name) {
+        Some(lang) => Ok(Some(lang.syllabify(&phonemes)?)),
+        None => Ok(None),
+    }
+}
+
+#[command]
+pub fn set_language_prosody(project: State<Project>, name: String, prosody: Option<Prosody>) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        lang.prosody = prosody;
+    }
+}
+
+/// Syllabifies phonemes, by orthography, then stresses the syllables
+#[command]
+pub fn stress_language_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<StressedWord>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.language(name) {
+        Some(lang) => Ok(Some(lang.stress(&lang.syllabify(&phonemes)?)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets the environment rules, if they only use existing phoneme sets
+#[command]
+pub fn set_language_environment_rules(
+    project: State<Project>,
+    name: String,
+    rules: Vec<EnvironmentRule>,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        for rule in &rules {
+            rule.validate(&lang.phoneme_sets)?;
+        }
+        lang.environment_rules = rules;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn realize_language_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<Realization>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.language(name) {
+        Some(lang) => Ok(Some(lang.realize(&phonemes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets the phonotactics, if they only use existing phoneme sets
+#[command]
+pub fn set_language_phonotactics(
+    project: State<Project>,
+    name: String,
+    phonotactics: Phonotactics,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        phonotactics.validate(&lang.phoneme_sets)?;
+        lang.phonotactics = phonotactics;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn validate_language_word(
+    project: State<Project>,
+    name: String,
+    word: CandidateWord,
+) -> Result<Option<Validation>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.language(name) {
+        Some(lang) => Ok(Some(lang.validate_word(&word)?)),
+        None => Ok(None),
+    }
+}
+
+#[command]
+pub fn generate_language_words(
+    project: State<Project>,
+    name: String,
+    settings: GeneratorSettings,
+) -> Result<Option<Generated>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.language(name) {
+        Some(lang) => Ok(Some(lang.generate_words(&settings)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets the sound changes, if they parse
+#[command]
+pub fn set_language_sound_changes(
+    project: State<Project>,
+    name: String,
+    changes: String,
+) -> Result<(), Error> {
+    SoundChanges::parse(&changes)?;
+    if let Some(lang) = project.inner().0.lock().unwrap().1.language_mut(name) {
+        lang.sound_changes = changes;
+    }
+
+    Ok(())
+}
+
+/// Parses an IPA form, rejecting anything that would be skipped or guessed
+fn parse_form(form: &str) -> Result<Vec<Phone>, Error> {
+    parse_ipa(form)
+        .into_phones()
+        .map_err(|diagnostic| Error::Ipa {
+            form: form.to_string(),
+            diagnostic,
+        })
+}
+
+/// Applies the language's sound changes to an IPA form
+#[command]
+pub fn apply_language_sound_changes(
+    project: State<Project>,
+    name: String,
+    ipa: String,
+) -> Result<Option<String>, Error> {
+    let project = project.inner().0.lock().unwrap();
+    let Some(lang) = project.1.language(name) else {
+        return Ok(None);
+    };
+
+    let changes = SoundChanges::parse(&lang.sound_changes)?;
+    let changed = changes.apply(&parse_form(&ipa)?);
+
+    Ok(Some(changed.iter().map(Phone::to_string).collect()))
+}
+
+/// Sets the splits, mergers and losses, if they refer to inherited phonemes
+#[command]
+pub fn set_language_phoneme_changes(
+    project: State<Project>,
+    name: String,
+    changes: Vec<PhonemeChange>,
+) -> Result<(), Error> {
+    let mut project = project.inner().0.lock().unwrap();
+    let group = &mut project.1;
+    let Some(lang) = group.language(&name) else {
+        return Ok(());
+    };
+
+    let inherited = group.inherited_phonemes(&lang.ancestors);
+    let mut sets = group.inherited_phoneme_sets(&lang.ancestors);
+    sets.extend(lang.phoneme_sets.clone());
+    validate_phoneme_changes(&changes, &inherited, &lang.phonemes, &sets)?;
+
+    if let Some(lang) = group.language_mut(name) {
+        lang.phoneme_changes = changes;
+    }
+
+    Ok(())
+}
+
+/// Derives IPA forms through the language's sound changes, step by step
+#[command]
+pub fn derive_language_forms(
+    project: State<Project>,
+    name: String,
+    forms: Vec<String>,
+) -> Result<Option<DerivationReport>, Error> {
+    let project = project.inner().0.lock().unwrap();
+    let Some(lang) = project.1.language(name) else {
+        return Ok(None);
+    };
+
+    let changes = SoundChanges::parse(&lang.sound_changes)?;
+    let words = forms
+        .iter()
+        .map(|form| parse_form(form))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Some(changes.derive(&words)))
+}
+
 #[command]
 pub fn init_languages_server(
     project: State<Project>,
@@ -1,5 +1,5 @@
 use super::Error;
-use crate::data::LanguageGroup;
+use crate::data::{LanguageDiff, LanguageGroup};
 use crate::file::Project;
 use tauri::{command, State};
 
@@ -12,7 +12,28 @@ pub fn new_language_group(project: State<Project>) {
 pub fn epoch_language_group(project: State<Project>) {
     let mut project = project.0.lock().unwrap();
     project.1.epoch();
-    project.0 = format!("{}_epoch", project.0);
+}
+
+/// Creates a new epoch for only the named languages
+#[command]
+pub fn epoch_languages(project: State<Project>, names: Vec<String>) -> Result<(), Error> {
+    Ok(project.0.lock().unwrap().1.epoch_languages(&names)?)
+}
+
+#[command]
+pub fn rename_stage(project: State<Project>, index: usize, name: String) -> Result<(), Error> {
+    Ok(project.0.lock().unwrap().1.rename_stage(index, name)?)
+}
+
+/// Compares the protolanguages of a stage with their ancestors in an
+/// earlier one, where the current protolanguages come after the history
+#[command]
+pub fn diff_stages(
+    project: State<Project>,
+    from: usize,
+    to: usize,
+) -> Result<Vec<LanguageDiff>, Error> {
+    Ok(project.0.lock().unwrap().1.diff_stages(from, to)?)
 }
 
 #[command]
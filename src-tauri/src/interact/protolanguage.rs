@@ -1,9 +1,15 @@
 use super::Error;
-use crate::data::Protolanguage;
+use crate::data::{
+    CandidateWord, EffectiveLanguage, EnvironmentRule, FeatureMatrix, Generated, GeneratorSettings,
+    InventoryChart, ParsedSyllable, Phone, Phoneme, PhonemeSet, Phonotactics, Prosody,
+    Protolanguage, Realization, StressedWord, SyllableTemplate, Validation,
+};
 use crate::file::Project;
 use crate::ServiceState;
+use std::collections::HashMap;
 use std::time::Duration;
 use tauri::{command, State, Window};
+use uuid::Uuid;
 
 #[command]
 pub fn create_protolanguage(project: State<Project>, name: String) -> Result<(), Error> {
@@ -43,6 +49,22 @@ pub fn get_protolanguage(project: State<Project>, name: String) -> Option<Protol
         .cloned()
 }
 
+/// The protolanguage's phonemes and rules with those it inherits, and where
+/// each came from
+#[command]
+pub fn get_effective_protolanguage(
+    project: State<Project>,
+    name: String,
+) -> Option<EffectiveLanguage> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .effective_protolanguage(name)
+}
+
 #[command]
 pub fn get_protolanguage_description(
     project: State<Project>,
@@ -70,6 +92,240 @@ pub fn set_protolanguage_description(
     }
 }
 
+/// Sets the protolanguages a protolanguage descends from, if they exist and
+/// don't descend from it
+#[command]
+pub fn set_protolanguage_ancestors(
+    project: State<Project>,
+    name: String,
+    ancestors: Vec<String>,
+) -> Result<(), Error> {
+    let mut project = project.inner().0.lock().unwrap();
+    Ok(project.1.set_protolanguage_ancestors(&name, ancestors)?)
+}
+
+#[command]
+pub fn get_protolanguage_natural_class(
+    project: State<Project>,
+    name: String,
+    class: FeatureMatrix,
+) -> Option<HashMap<Uuid, Phoneme>> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .protolanguage(name)
+        .map(|lang| lang.natural_class(&class))
+}
+
+#[command]
+pub fn get_protolanguage_chart(project: State<Project>, name: String) -> Option<InventoryChart> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .protolanguage(name)
+        .map(Protolanguage::chart)
+}
+
+#[command]
+pub fn create_protolanguage_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        lang.create_phoneme_set(set)?;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn get_protolanguage_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+) -> Option<PhonemeSet> {
+    project
+        .inner()
+        .0
+        .lock()
+        .unwrap()
+        .1
+        .protolanguage(name)
+        .and_then(|lang| lang.phoneme_sets.get(&set))
+        .cloned()
+}
+
+#[command]
+pub fn set_protolanguage_phoneme_set(
+    project: State<Project>,
+    name: String,
+    set: String,
+    value: PhonemeSet,
+) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        if let Some(existing) = lang.phoneme_sets.get_mut(&set) {
+            *existing = value;
+        }
+    }
+}
+
+#[command]
+pub fn delete_protolanguage_phoneme_set(project: State<Project>, name: String, set: String) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        lang.phoneme_sets.remove(&set);
+    }
+}
+
+/// The production set of a member of a phoneme set
+#[command]
+pub fn get_protolanguage_phoneme_set_productions(
+    project: State<Project>,
+    name: String,
+    set: String,
+    member: String,
+) -> Option<HashMap<String, Option<Phone>>> {
+    let project = project.inner().0.lock().unwrap();
+    let lang = project.1.protolanguage(name)?;
+
+    lang.phoneme_sets
+        .get(&set)?
+        .productions(&lang.phonemes, &member)
+}
+
+/// Sets the syllable template, if it only uses existing phoneme sets
+#[command]
+pub fn set_protolanguage_syllable_template(
+    project: State<Project>,
+    name: String,
+    template: Option<SyllableTemplate>,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        if let Some(template) = &template {
+            template.validate(&lang.phoneme_sets)?;
+        }
+        lang.syllable_template = template;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn syllabify_protolanguage_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<Vec<ParsedSyllable>>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.protolanguage(name) {
+        Some(lang) => Ok(Some(lang.syllabify(&phonemes)?)),
+        None => Ok(None),
+    }
+}
+
+#[command]
+pub fn set_protolanguage_prosody(project: State<Project>, name: String, prosody: Option<Prosody>) {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        lang.prosody = prosody;
+    }
+}
+
+/// Syllabifies phonemes, by orthography, then stresses the syllables
+#[command]
+pub fn stress_protolanguage_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<StressedWord>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.protolanguage(name) {
+        Some(lang) => Ok(Some(lang.stress(&lang.syllabify(&phonemes)?)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets the environment rules, if they only use existing phoneme sets
+#[command]
+pub fn set_protolanguage_environment_rules(
+    project: State<Project>,
+    name: String,
+    rules: Vec<EnvironmentRule>,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        for rule in &rules {
+            rule.validate(&lang.phoneme_sets)?;
+        }
+        lang.environment_rules = rules;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn realize_protolanguage_phonemes(
+    project: State<Project>,
+    name: String,
+    phonemes: Vec<String>,
+) -> Result<Option<Realization>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.protolanguage(name) {
+        Some(lang) => Ok(Some(lang.realize(&phonemes)?)),
+        None => Ok(None),
+    }
+}
+
+/// Sets the phonotactics, if they only use existing phoneme sets
+#[command]
+pub fn set_protolanguage_phonotactics(
+    project: State<Project>,
+    name: String,
+    phonotactics: Phonotactics,
+) -> Result<(), Error> {
+    if let Some(lang) = project.inner().0.lock().unwrap().1.protolanguage_mut(name) {
+        phonotactics.validate(&lang.phoneme_sets)?;
+        lang.phonotactics = phonotactics;
+    }
+
+    Ok(())
+}
+
+#[command]
+pub fn validate_protolanguage_word(
+    project: State<Project>,
+    name: String,
+    word: CandidateWord,
+) -> Result<Option<Validation>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.protolanguage(name) {
+        Some(lang) => Ok(Some(lang.validate_word(&word)?)),
+        None => Ok(None),
+    }
+}
+
+#[command]
+pub fn generate_protolanguage_words(
+    project: State<Project>,
+    name: String,
+    settings: GeneratorSettings,
+) -> Result<Option<Generated>, Error> {
+    let project = project.inner().0.lock().unwrap();
+
+    match project.1.protolanguage(name) {
+        Some(lang) => Ok(Some(lang.generate_words(&settings)?)),
+        None => Ok(None),
+    }
+}
+
 #[command]
 pub fn init_protolanguages_server(
     project: State<Project>,
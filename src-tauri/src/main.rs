@@ -12,6 +12,7 @@ mod data;
 mod export;
 mod file;
 mod interact;
+mod synth;
 mod util;
 
 pub struct ServiceStateRaw {
@@ -59,11 +60,35 @@ fn main() {
             file::delete_language_group,
             export::export_language_group,
             export::test_export_language_group,
+            export::render_chart,
+            export::export_chart,
+            export::render_derivation,
+            export::export_derivation,
+            export::render_family_tree,
+            export::export_family_tree,
+            synth::synthesize_phone,
+            synth::synthesize_phones,
             util::from_branner,
             util::from_sil,
+            util::from_xsampa,
+            util::to_xsampa,
+            util::from_kirshenbaum,
+            util::to_kirshenbaum,
             util::display_phone,
+            util::display_phone_as,
+            util::display_phoneme,
+            util::display_syllable,
+            util::parse_tone,
+            util::parse_ipa,
+            util::get_phone_features,
+            util::get_phone_from_features,
+            util::get_phone_distance,
+            util::get_sequence_distance,
             interact::new_language_group,
             interact::epoch_language_group,
+            interact::epoch_languages,
+            interact::rename_stage,
+            interact::diff_stages,
             interact::dump_language_group,
             interact::get_project_name,
             interact::set_project_name,
@@ -71,14 +96,54 @@ fn main() {
             interact::create_language,
             interact::delete_language,
             interact::get_language,
+            interact::get_effective_language,
             interact::get_language_description,
             interact::set_language_description,
+            interact::set_language_ancestors,
+            interact::get_language_natural_class,
+            interact::get_language_chart,
+            interact::create_language_phoneme_set,
+            interact::get_language_phoneme_set,
+            interact::set_language_phoneme_set,
+            interact::delete_language_phoneme_set,
+            interact::get_language_phoneme_set_productions,
+            interact::set_language_syllable_template,
+            interact::syllabify_language_phonemes,
+            interact::set_language_prosody,
+            interact::stress_language_phonemes,
+            interact::set_language_environment_rules,
+            interact::realize_language_phonemes,
+            interact::set_language_phonotactics,
+            interact::validate_language_word,
+            interact::generate_language_words,
+            interact::set_language_sound_changes,
+            interact::apply_language_sound_changes,
+            interact::derive_language_forms,
+            interact::set_language_phoneme_changes,
             interact::init_languages_server,
             interact::create_protolanguage,
             interact::delete_protolanguage,
             interact::get_protolanguage,
+            interact::get_effective_protolanguage,
             interact::get_protolanguage_description,
             interact::set_protolanguage_description,
+            interact::set_protolanguage_ancestors,
+            interact::get_protolanguage_natural_class,
+            interact::get_protolanguage_chart,
+            interact::create_protolanguage_phoneme_set,
+            interact::get_protolanguage_phoneme_set,
+            interact::set_protolanguage_phoneme_set,
+            interact::delete_protolanguage_phoneme_set,
+            interact::get_protolanguage_phoneme_set_productions,
+            interact::set_protolanguage_syllable_template,
+            interact::syllabify_protolanguage_phonemes,
+            interact::set_protolanguage_prosody,
+            interact::stress_protolanguage_phonemes,
+            interact::set_protolanguage_environment_rules,
+            interact::realize_protolanguage_phonemes,
+            interact::set_protolanguage_phonotactics,
+            interact::validate_protolanguage_word,
+            interact::generate_protolanguage_words,
             interact::init_protolanguages_server,
         ])
         .run(tauri::generate_context!())
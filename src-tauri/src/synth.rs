@@ -0,0 +1,631 @@
+//! Formant synthesis of phones
+//!
+//! A small Klatt-style synthesizer: a glottal pulse train and a noise source
+//! are shaped by second-order resonators, a cascade of formants for the
+//! voice and a single resonator for frication. It is meant to make
+//! inventories audible, not to sound natural, and works fully offline.
+//!
+//! Every phone becomes one or more [`Segment`]s (e.g. a closure, a burst
+//! and aspiration for `pʰ`), which are rendered one after the other with
+//! smoothed amplitudes so that there are no clicks between them.
+
+use std::f64::consts::PI;
+
+use tauri::command;
+
+use crate::data::{
+    Backness, Height, Length, ObstruentAttachment, Phone, PhoneError, Place, SecondaryArticulation,
+};
+
+pub const SAMPLE_RATE: u32 = 16_000;
+/// Silence before and after every utterance, in seconds
+pub const PADDING: f64 = 0.02;
+/// Pitch of the voice, in Hz
+const PITCH: f64 = 120.0;
+/// Time constant of amplitude changes, in seconds
+const SMOOTHING: f64 = 0.003;
+/// Peak amplitude of the output, relative to full scale
+const HEADROOM: f64 = 0.8;
+
+/// Formant frequencies and bandwidths, in Hz
+type Formants = [(f64, f64); 3];
+
+/// A stretch of sound with constant articulation
+#[derive(Clone, Debug)]
+struct Segment {
+    /// In seconds
+    duration: f64,
+    /// Amplitude of the glottal source
+    voicing: f64,
+    /// Amplitude of noise through the formants
+    aspiration: f64,
+    /// Amplitude of noise through the frication resonator
+    frication: f64,
+    formants: Formants,
+    /// Center frequency and bandwidth of frication noise
+    noise: (f64, f64),
+    /// Adds a nasal formant to the cascade
+    nasal: bool,
+    /// Modulates the voice like the tongue tip of a trill
+    tremolo: bool,
+    /// Relative to [`PITCH`], lowered for creaky voice
+    pitch: f64,
+}
+
+impl Segment {
+    /// A silent segment, such as the closure of a voiceless plosive
+    fn silence(duration: f64) -> Segment {
+        Segment {
+            duration,
+            voicing: 0.0,
+            aspiration: 0.0,
+            frication: 0.0,
+            formants: NEUTRAL,
+            noise: (1000.0, 1000.0),
+            nasal: false,
+            tremolo: false,
+            pitch: 1.0,
+        }
+    }
+
+    fn voiced(duration: f64, voicing: f64, formants: Formants) -> Segment {
+        Segment {
+            voicing,
+            formants,
+            ..Segment::silence(duration)
+        }
+    }
+
+    fn fricated(duration: f64, frication: f64, noise: (f64, f64)) -> Segment {
+        Segment {
+            frication,
+            noise,
+            ..Segment::silence(duration)
+        }
+    }
+}
+
+/// The formants of a schwa, used where the tract has no clear shape
+const NEUTRAL: Formants = [(500.0, 80.0), (1500.0, 100.0), (2500.0, 150.0)];
+
+/// Where the second formant points at when the tract is closed at `place`
+fn locus(place: Place) -> Formants {
+    let (f1, f2, f3) = match place {
+        Place::Bilabial => (250.0, 900.0, 2300.0),
+        Place::Labiodental => (300.0, 1100.0, 2400.0),
+        Place::Dental => (300.0, 1500.0, 2600.0),
+        Place::Alveolar => (300.0, 1700.0, 2600.0),
+        Place::Postalveolar => (300.0, 2000.0, 2500.0),
+        Place::Retroflex => (300.0, 1600.0, 2000.0),
+        Place::Palatal => (280.0, 2300.0, 3000.0),
+        Place::Velar => (300.0, 2000.0, 2200.0),
+        Place::Uvular => (450.0, 1200.0, 2400.0),
+        Place::Pharyngeal => (700.0, 1100.0, 2500.0),
+        Place::Glottal => (500.0, 1500.0, 2500.0),
+    };
+
+    [(f1, 80.0), (f2, 100.0), (f3, 150.0)]
+}
+
+/// Center frequency and bandwidth of the noise made at `place`
+fn frication(place: Place) -> (f64, f64) {
+    match place {
+        Place::Bilabial => (1500.0, 3000.0),
+        Place::Labiodental => (5000.0, 4000.0),
+        Place::Dental => (6000.0, 3000.0),
+        Place::Alveolar => (6000.0, 1500.0),
+        Place::Postalveolar => (3500.0, 1000.0),
+        Place::Retroflex => (3000.0, 1000.0),
+        Place::Palatal => (4200.0, 1200.0),
+        Place::Velar => (2200.0, 800.0),
+        Place::Uvular => (1300.0, 800.0),
+        Place::Pharyngeal => (1000.0, 1000.0),
+        Place::Glottal => (2000.0, 4000.0),
+    }
+}
+
+fn vowel_formants(height: Height, backness: Backness, rounded: bool) -> Formants {
+    let h = height as usize as f64;
+    let f1 = [280.0, 360.0, 420.0, 500.0, 580.0, 700.0, 800.0][height as usize];
+    let (f2, f3) = match backness {
+        Backness::Front => (2300.0 - 100.0 * h, 2900.0),
+        Backness::Central => (1450.0, 2500.0),
+        Backness::Back => (800.0 + 50.0 * h, 2500.0),
+    };
+    let (f2, f3) = match (rounded, backness) {
+        (false, _) => (f2, f3),
+        (true, Backness::Front) => (f2 - 400.0, f3 - 300.0),
+        (true, _) => (f2 - 100.0, f3 - 300.0),
+    };
+
+    [(f1, 80.0), (f2, 100.0), (f3, 150.0)]
+}
+
+fn length_factor(length: Length) -> f64 {
+    match length {
+        Length::ExtraShort => 0.5,
+        Length::Short => 1.0,
+        Length::HalfLong => 1.5,
+        Length::Long => 2.0,
+    }
+}
+
+/// Shifts formants towards the secondary articulation's target
+fn apply_secondary(formants: &mut Formants, secondary: SecondaryArticulation) {
+    match secondary {
+        SecondaryArticulation::Labialized => formants[1].0 -= 200.0,
+        SecondaryArticulation::Palatalized => formants[1].0 += 300.0,
+        SecondaryArticulation::Velarized => formants[1].0 -= 300.0,
+        SecondaryArticulation::Pharyngealized => {
+            formants[0].0 += 150.0;
+            formants[1].0 -= 200.0;
+        }
+    }
+}
+
+/// The release of a plosive, after its closure
+fn release(place: Place, attachments: &[ObstruentAttachment]) -> Vec<Segment> {
+    let burst = Segment::fricated(0.015, 1.0, frication(place));
+
+    if attachments.contains(&ObstruentAttachment::Unreleased) {
+        vec![]
+    } else if attachments.contains(&ObstruentAttachment::NasalRelease) {
+        vec![Segment {
+            nasal: true,
+            ..Segment::voiced(0.04, 0.5, locus(place))
+        }]
+    } else if attachments.contains(&ObstruentAttachment::LateralRelease) {
+        vec![Segment::voiced(0.04, 0.6, LATERAL)]
+    } else if attachments.contains(&ObstruentAttachment::Ejective) {
+        vec![
+            Segment {
+                frication: 1.5,
+                ..burst
+            },
+            Segment::silence(0.04),
+        ]
+    } else {
+        vec![burst]
+    }
+}
+
+/// Formants of a lateral approximant
+const LATERAL: Formants = [(360.0, 80.0), (1300.0, 150.0), (2700.0, 200.0)];
+
+fn segments(phone: &Phone) -> Vec<Segment> {
+    let factor = length_factor(phone.length());
+    let attachments: Vec<_> = phone
+        .attachments()
+        .map(|attachments| attachments.iter().copied().collect())
+        .unwrap_or_default();
+    let voiced = match phone {
+        Phone::Plosive { voiced, .. }
+        | Phone::Affricative { voiced, .. }
+        | Phone::Fricative { voiced, .. }
+        | Phone::LateralFricative { voiced, .. }
+        | Phone::Nasal { voiced, .. }
+        | Phone::Trill { voiced, .. }
+        | Phone::Tap { voiced, .. }
+        | Phone::Approximant { voiced, .. }
+        | Phone::LateralApproximant { voiced, .. }
+        | Phone::Implosive { voiced, .. } => *voiced,
+        Phone::Click { .. } => false,
+        Phone::Vowel { .. } => true,
+    };
+    // Syllabic sonorants last as long as a vowel
+    let sonorant = if phone.syllabic() { 0.16 } else { 0.09 } * factor;
+    // Voiceless sonorants are noise through the same tract shape
+    let sonorant_source = |formants| Segment {
+        voicing: if voiced { 0.8 } else { 0.0 },
+        aspiration: if voiced { 0.0 } else { 0.3 },
+        formants,
+        ..Segment::silence(sonorant)
+    };
+
+    let mut segments = match phone {
+        Phone::Plosive { place, .. } | Phone::Implosive { place, .. } => {
+            let mut closure = Segment::voiced(0.07 * factor, 0.0, locus(*place));
+            if voiced {
+                // A voice bar, muffled by the closed tract
+                closure.voicing = 0.15;
+                closure.formants = [(200.0, 60.0), (locus(*place)[1].0, 300.0), (2500.0, 300.0)];
+            }
+
+            let mut segments = vec![closure];
+            segments.extend(release(*place, &attachments));
+            segments
+        }
+        Phone::Affricative { end_place, .. } => {
+            let mut fricative = Segment::fricated(0.08 * factor, 0.7, frication(*end_place));
+            if voiced {
+                fricative.voicing = 0.3;
+                fricative.formants = locus(*end_place);
+            }
+
+            vec![
+                Segment::voiced(0.06, if voiced { 0.15 } else { 0.0 }, locus(*end_place)),
+                Segment::fricated(0.01, 1.0, frication(*end_place)),
+                fricative,
+            ]
+        }
+        Phone::Fricative { place, .. } | Phone::LateralFricative { place, .. } => {
+            let mut fricative = Segment::fricated(0.12 * factor, 0.7, frication(*place));
+            if matches!(phone, Phone::LateralFricative { .. }) {
+                fricative.noise.1 *= 2.0;
+            }
+            if voiced {
+                fricative.voicing = 0.3;
+                fricative.formants = locus(*place);
+            }
+
+            vec![fricative]
+        }
+        Phone::Nasal { place, .. } => {
+            let [_, f2, f3] = locus(*place);
+
+            vec![Segment {
+                nasal: true,
+                ..sonorant_source([(250.0, 60.0), f2, f3])
+            }]
+        }
+        Phone::Trill { place, .. } => vec![Segment {
+            tremolo: true,
+            duration: 0.1 * factor,
+            ..sonorant_source(locus(*place))
+        }],
+        Phone::Tap { place, .. } => vec![Segment {
+            duration: 0.03 * factor,
+            ..sonorant_source(locus(*place))
+        }],
+        Phone::Approximant { place, .. } => {
+            let formants = match place {
+                Place::Palatal => vowel_formants(Height::Close, Backness::Front, false),
+                Place::Velar => vowel_formants(Height::Close, Backness::Back, false),
+                // Rhotics have a low third formant
+                Place::Alveolar | Place::Postalveolar | Place::Retroflex => {
+                    [(350.0, 80.0), (1200.0, 100.0), (1600.0, 150.0)]
+                }
+                _ => locus(*place),
+            };
+
+            vec![sonorant_source(formants)]
+        }
+        Phone::LateralApproximant { .. } => vec![sonorant_source(LATERAL)],
+        Phone::Click { place, .. } => vec![
+            Segment::silence(0.05),
+            Segment::fricated(0.01, 2.0, frication(*place)),
+        ],
+        Phone::Vowel {
+            height,
+            backness,
+            rounded,
+            nasalized,
+            ..
+        } => vec![Segment {
+            nasal: *nasalized,
+            ..Segment::voiced(
+                0.16 * factor,
+                1.0,
+                vowel_formants(*height, *backness, *rounded),
+            )
+        }],
+    };
+
+    for attachment in &attachments {
+        match attachment {
+            ObstruentAttachment::Aspirated => segments.push(Segment {
+                aspiration: 0.5,
+                ..Segment::silence(0.05)
+            }),
+            ObstruentAttachment::Preaspirated => segments.insert(
+                0,
+                Segment {
+                    aspiration: 0.5,
+                    ..Segment::silence(0.05)
+                },
+            ),
+            ObstruentAttachment::Breathy => {
+                for segment in &mut segments {
+                    segment.aspiration += segment.voicing * 0.4;
+                }
+            }
+            ObstruentAttachment::Creaky => {
+                for segment in &mut segments {
+                    segment.pitch = 0.5;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(secondary) = phone.secondary() {
+        for secondary in secondary {
+            for segment in &mut segments {
+                apply_secondary(&mut segment.formants, *secondary);
+            }
+        }
+    }
+
+    segments
+}
+
+/// A two-pole resonator, as in Klatt (1980)
+struct Resonator {
+    a: f64,
+    b: f64,
+    c: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Resonator {
+    fn new() -> Resonator {
+        Resonator {
+            a: 1.0,
+            b: 0.0,
+            c: 0.0,
+            y1: 0.0,
+            y2: 0.0,
+        }
+    }
+
+    /// Retunes the resonator, keeping its state, with unit gain at 0 Hz
+    fn tune(&mut self, (frequency, bandwidth): (f64, f64)) {
+        let t = 1.0 / SAMPLE_RATE as f64;
+
+        self.c = -(-2.0 * PI * bandwidth * t).exp();
+        self.b = 2.0 * (-PI * bandwidth * t).exp() * (2.0 * PI * frequency * t).cos();
+        self.a = 1.0 - self.b - self.c;
+    }
+
+    /// Retunes the resonator, keeping its state, with unit gain at its peak
+    fn tune_peak(&mut self, (frequency, bandwidth): (f64, f64)) {
+        self.tune((frequency, bandwidth));
+
+        let w = 2.0 * PI * frequency / SAMPLE_RATE as f64;
+        let re = 1.0 - self.b * w.cos() - self.c * (2.0 * w).cos();
+        let im = self.b * w.sin() + self.c * (2.0 * w).sin();
+        self.a = (re * re + im * im).sqrt();
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.a * x + self.b * self.y1 + self.c * self.y2;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+}
+
+/// Deterministic white noise, so that synthesis is reproducible
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f64 {
+        // xorshift32
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+
+        self.0 as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+}
+
+/// A Rosenberg glottal pulse, at `phase` from 0 to 1 through a period
+fn glottal_pulse(phase: f64) -> f64 {
+    const OPENING: f64 = 0.4;
+    const CLOSING: f64 = 0.16;
+
+    if phase < OPENING {
+        0.5 * (1.0 - (PI * phase / OPENING).cos())
+    } else if phase < OPENING + CLOSING {
+        (PI * (phase - OPENING) / (2.0 * CLOSING)).cos()
+    } else {
+        0.0
+    }
+}
+
+/// Renders phones to mono samples at [`SAMPLE_RATE`]
+pub fn synthesize(phones: &[Phone]) -> Vec<i16> {
+    let segments: Vec<_> = std::iter::once(Segment::silence(PADDING))
+        .chain(phones.iter().flat_map(segments))
+        .chain(std::iter::once(Segment::silence(PADDING)))
+        .collect();
+
+    let rate = SAMPLE_RATE as f64;
+    let smoothing = 1.0 - (-1.0 / (SMOOTHING * rate)).exp();
+    let mut cascade = [Resonator::new(), Resonator::new(), Resonator::new()];
+    let mut nasal = Resonator::new();
+    let mut fricator = Resonator::new();
+    let mut noise = Noise(0x2545_f491);
+    nasal.tune((270.0, 100.0));
+
+    let (mut voicing, mut aspiration, mut frication) = (0.0, 0.0, 0.0);
+    let (mut phase, mut pulse, mut time) = (0.0, 0.0, 0.0);
+    let mut output = vec![];
+
+    for segment in &segments {
+        for (resonator, formant) in cascade.iter_mut().zip(segment.formants) {
+            resonator.tune(formant);
+        }
+        fricator.tune_peak(segment.noise);
+
+        let samples = (segment.duration * rate).round() as usize;
+        let pitch = PITCH * segment.pitch;
+
+        for _ in 0..samples {
+            let tremolo = if segment.tremolo {
+                0.6 + 0.4 * (2.0 * PI * 30.0 * time).cos()
+            } else {
+                1.0
+            };
+            voicing += (segment.voicing * tremolo - voicing) * smoothing;
+            aspiration += (segment.aspiration - aspiration) * smoothing;
+            frication += (segment.frication - frication) * smoothing;
+
+            phase = (phase + pitch / rate) % 1.0;
+            let previous = pulse;
+            pulse = glottal_pulse(phase);
+            // Differentiating the pulse accounts for radiation at the lips
+            let source = (pulse - previous) * rate / pitch / 10.0;
+
+            let mut voice = source * voicing + noise.next() * aspiration * 0.3;
+            for resonator in &mut cascade {
+                voice = resonator.process(voice);
+            }
+            if segment.nasal {
+                voice = nasal.process(voice);
+            }
+            let fricated = fricator.process(noise.next() * frication * 0.5);
+
+            output.push(voice + fricated);
+            time += 1.0 / rate;
+        }
+    }
+
+    let peak = output.iter().fold(0.0, |peak: f64, x| peak.max(x.abs()));
+    let scale = if peak > 0.0 {
+        HEADROOM * i16::MAX as f64 / peak
+    } else {
+        0.0
+    };
+
+    output
+        .into_iter()
+        .map(|x| (x * scale).round() as i16)
+        .collect()
+}
+
+/// Encodes mono samples as a 16-bit PCM WAV file
+pub fn wav(samples: &[i16]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut bytes = Vec::with_capacity(44 + samples.len() * 2);
+
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    // PCM, mono
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2u16.to_le_bytes());
+    bytes.extend_from_slice(&16u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+
+    bytes
+}
+
+#[command]
+pub fn synthesize_phone(phone: Phone) -> Result<Vec<u8>, PhoneError> {
+    synthesize_phones(vec![phone])
+}
+
+#[command]
+pub fn synthesize_phones(phones: Vec<Phone>) -> Result<Vec<u8>, PhoneError> {
+    for phone in &phones {
+        phone.validate()?;
+    }
+
+    Ok(wav(&synthesize(&phones)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{synthesize, wav, PADDING, SAMPLE_RATE};
+    use crate::data::parse_ipa;
+    use std::f64::consts::PI;
+
+    fn render(ipa: &str) -> Vec<f64> {
+        let phones = parse_ipa(ipa).phones;
+
+        synthesize(&phones).into_iter().map(f64::from).collect()
+    }
+
+    fn duration(samples: &[f64]) -> f64 {
+        samples.len() as f64 / SAMPLE_RATE as f64 - 2.0 * PADDING
+    }
+
+    /// Share of the energy between `low` and `high` Hz, from Goertzel
+    /// filters every 20 Hz
+    fn band_energy(samples: &[f64], low: f64, high: f64) -> f64 {
+        let power = |frequency: f64| {
+            let coefficient = 2.0 * (2.0 * PI * frequency / SAMPLE_RATE as f64).cos();
+            let (mut s1, mut s2) = (0.0, 0.0);
+
+            for x in samples {
+                let s = x + coefficient * s1 - s2;
+                s2 = s1;
+                s1 = s;
+            }
+
+            s1 * s1 + s2 * s2 - coefficient * s1 * s2
+        };
+        let band = |low: f64, high: f64| {
+            (0..)
+                .map(|i| low + 20.0 * i as f64)
+                .take_while(|frequency| *frequency <= high)
+                .map(power)
+                .sum::<f64>()
+        };
+
+        band(low, high) / band(20.0, SAMPLE_RATE as f64 / 2.0 - 20.0)
+    }
+
+    #[test]
+    fn writes_pcm_wav() {
+        let samples = synthesize(&parse_ipa("a").phones);
+        let bytes = wav(&samples);
+
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(
+            u32::from_le_bytes(bytes[24..28].try_into().unwrap()),
+            SAMPLE_RATE
+        );
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(bytes.len(), 44 + samples.len() * 2);
+        assert!(samples.iter().any(|sample| *sample != 0));
+    }
+
+    #[test]
+    fn durations_follow_length() {
+        let short = duration(&render("a"));
+        let long = duration(&render("aː"));
+
+        assert!((short - 0.16).abs() < 0.001, "{short}");
+        assert!((long - 2.0 * short).abs() < 0.001, "{long}");
+        assert!(duration(&render("pʰa")) > duration(&render("pa")));
+        assert!(duration(&render("ɾ")) < duration(&render("r")));
+    }
+
+    #[test]
+    fn vowels_peak_at_their_formants() {
+        let i = render("i");
+        let u = render("u");
+        let a = render("a");
+
+        // Front vowels have a high second formant
+        assert!(band_energy(&i, 2000.0, 2600.0) > 4.0 * band_energy(&u, 2000.0, 2600.0));
+        // Open vowels have a high first formant
+        assert!(band_energy(&a, 650.0, 950.0) > 4.0 * band_energy(&i, 650.0, 950.0));
+    }
+
+    #[test]
+    fn fricatives_differ_in_noise() {
+        let s = render("s");
+        let sh = render("ʃ");
+        let z = render("z");
+
+        assert!(band_energy(&s, 5000.0, 7000.0) > band_energy(&sh, 5000.0, 7000.0));
+        assert!(band_energy(&sh, 3000.0, 4000.0) > band_energy(&s, 3000.0, 4000.0));
+        // Voicing adds energy at the pitch and its first harmonics
+        assert!(band_energy(&z, 80.0, 400.0) > 10.0 * band_energy(&s, 80.0, 400.0));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::data::Phone;
+use crate::data::{
+    FeatureMatrix, Notation, NotationError, ParsedIpa, Phone, PhoneError, Phoneme, Syllable, Tone,
+    Transliteration,
+};
 use ipa_translate::{branner_to_ipa, sil_to_ipa};
 use tauri::command;
 
@@ -13,6 +16,83 @@ pub fn from_sil(input: String) -> String {
 }
 
 #[command]
-pub fn display_phone(phone: Phone) -> String {
-    phone.to_string().replace('\u{200b}', "")
+pub fn from_xsampa(input: String) -> Transliteration {
+    crate::data::to_ipa(&input, Notation::XSampa)
+}
+
+#[command]
+pub fn to_xsampa(input: String) -> Transliteration {
+    crate::data::from_ipa(&input, Notation::XSampa)
+}
+
+#[command]
+pub fn from_kirshenbaum(input: String) -> Transliteration {
+    crate::data::to_ipa(&input, Notation::Kirshenbaum)
+}
+
+#[command]
+pub fn to_kirshenbaum(input: String) -> Transliteration {
+    crate::data::from_ipa(&input, Notation::Kirshenbaum)
+}
+
+#[command]
+pub fn display_phone(phone: Phone) -> Result<String, PhoneError> {
+    phone.validate()?;
+
+    Ok(phone.to_string().replace('\u{200b}', ""))
+}
+
+#[command]
+pub fn display_phone_as(phone: Phone, notation: Notation) -> Result<String, NotationError> {
+    phone.validate()?;
+
+    phone.to_notation(notation)
+}
+
+#[command]
+pub fn display_phoneme(phoneme: Phoneme) -> Result<String, PhoneError> {
+    phoneme.primary.validate()?;
+
+    Ok(phoneme.ipa().replace('\u{200b}', ""))
+}
+
+#[command]
+pub fn display_syllable(syllable: Syllable) -> Result<String, PhoneError> {
+    for phone in &syllable.phones {
+        phone.validate()?;
+    }
+
+    Ok(syllable.to_string().replace('\u{200b}', ""))
+}
+
+#[command]
+pub fn parse_tone(numerals: String) -> Option<Tone> {
+    Tone::from_chao(numerals.trim())
+}
+
+#[command]
+pub fn get_phone_features(phone: Phone) -> Result<FeatureMatrix, PhoneError> {
+    phone.validate()?;
+
+    Ok(phone.features())
+}
+
+#[command]
+pub fn get_phone_from_features(features: FeatureMatrix) -> Option<Phone> {
+    Phone::from_features(&features)
+}
+
+#[command]
+pub fn get_phone_distance(from: Phone, to: Phone) -> f64 {
+    from.distance(&to)
+}
+
+#[command]
+pub fn get_sequence_distance(from: Vec<Phone>, to: Vec<Phone>) -> f64 {
+    crate::data::sequence_distance(&from, &to)
+}
+
+#[command]
+pub fn parse_ipa(input: String) -> ParsedIpa {
+    crate::data::parse_ipa(&input)
 }